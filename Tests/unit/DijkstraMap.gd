@@ -10,7 +10,10 @@ func test_new():
 
 func test_clear():
     var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.recalculate_async(0)
     dijkstra_map.clear()
+    assert_false(dijkstra_map.is_recalculating())
 
 func test_duplicate_graph_from():
     var dijkstra_map = DijkstraMap.new()
@@ -20,7 +23,11 @@ func test_duplicate_graph_from():
     dijkstra_map.add_point(3)
     dijkstra_map.connect_points(1, 2, 1.0)
     var dijkstra_map_copy = DijkstraMap.new()
+    for id in range(10):
+        dijkstra_map_copy.add_point(id)
+    var revision = dijkstra_map_copy.get_revision()
     dijkstra_map_copy.duplicate_graph_from(dijkstra_map)
+    assert_gt(dijkstra_map_copy.get_revision(), revision)
     dijkstra_map.add_point(4)
     assert_true(dijkstra_map_copy.has_point(1))
     assert_true(dijkstra_map_copy.has_point(2))
@@ -28,6 +35,40 @@ func test_duplicate_graph_from():
     assert_true(dijkstra_map_copy.has_connection(1, 2))
     assert_false(dijkstra_map_copy.has_point(4))
 
+func test_load_edge_list_csv():
+    var dijkstra_map = DijkstraMap.new()
+    var res: int = dijkstra_map.load_edge_list_csv("source,target,weight\n0,1,2.5\n1,2,1,true")
+    assert_eq(res, OK)
+    assert_true(dijkstra_map.has_connection(0, 1))
+    assert_false(dijkstra_map.has_connection(1, 0))
+    assert_true(dijkstra_map.has_connection(2, 1))
+
+func test_load_dimacs():
+    var dijkstra_map = DijkstraMap.new()
+    var res: int = dijkstra_map.load_dimacs("c tiny graph\np sp 3 2\na 1 2 4\na 2 3 1")
+    assert_eq(res, OK)
+    assert_true(dijkstra_map.has_point(3))
+    assert_true(dijkstra_map.has_connection(1, 2))
+    assert_false(dijkstra_map.has_connection(2, 1))
+
+func test_load_ascii_grid():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.load_ascii_grid("..#\n.2.")
+    assert_eq(grid.size(), 5)
+    assert_eq(dijkstra_map.get_terrain_for_point(grid[Vector2(1, 1)]), 2)
+    assert_true(dijkstra_map.has_connection(grid[Vector2(1, 0)], grid[Vector2(1, 1)]))
+
+func test_fork():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.connect_points(0, 1)
+    var fork = dijkstra_map.fork()
+    fork.disable_point(1)
+    assert_true(fork.is_point_disabled(1))
+    assert_false(dijkstra_map.is_point_disabled(1))
+    assert_true(fork.has_connection(0, 1))
+
 func test_get_available_point_id():
     var dijkstra_map = DijkstraMap.new()
     dijkstra_map.add_point(0)
@@ -101,6 +142,13 @@ func test_is_point_disabled():
     assert_false(dijkstra_map.is_point_disabled(1)) # not disabled
     assert_false(dijkstra_map.is_point_disabled(2)) # not in the map
 
+func test_add_points():
+    var dijkstra_map = DijkstraMap.new()
+    assert_eq(dijkstra_map.add_points(PoolIntArray([0, 1, 2]), PoolIntArray([-1, 4, 4])), OK)
+    assert_eq(dijkstra_map.get_terrain_for_point(1), 4)
+    assert_eq(dijkstra_map.add_points(PoolIntArray([3, 2])), FAILED)
+    assert_false(dijkstra_map.has_point(3))
+
 func test_connect_points():
     var dijkstra_map = DijkstraMap.new()
     dijkstra_map.add_point(0)
@@ -119,6 +167,15 @@ func test_connect_points():
     assert_eq(dijkstra_map.connect_points(1, 5, 1.0), FAILED, "5 does not exists in the map")
     assert_eq(dijkstra_map.connect_points(1, 6, 1.0, true), FAILED, "6 does not exists in the map")
 
+func test_add_connections():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_points(PoolIntArray([0, 1, 2]))
+    assert_eq(dijkstra_map.add_connections(PoolIntArray([0, 1]), PoolIntArray([1, 2]), PoolRealArray([2.5, 1.0])), OK)
+    assert_true(dijkstra_map.has_connection(2, 1))
+    assert_eq(dijkstra_map.add_connections(PoolIntArray([0]), PoolIntArray([2]), PoolRealArray(), false), OK)
+    assert_false(dijkstra_map.has_connection(2, 0))
+    assert_eq(dijkstra_map.add_connections(PoolIntArray([0]), PoolIntArray([5])), FAILED)
+
 func test_remove_connection():
     var dijkstra_map = DijkstraMap.new()
     dijkstra_map.add_point(0)
@@ -140,6 +197,59 @@ func test_has_connection():
     assert_false(dijkstra_map.has_connection(1, 0))
     assert_false(dijkstra_map.has_connection(0, 2))
 
+func test_get_all_point_ids():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(4)
+    dijkstra_map.add_point(1)
+    assert_eq(Array(dijkstra_map.get_all_point_ids()), [1, 4])
+
+func test_get_disabled_points():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_points(PoolIntArray([0, 1, 2]))
+    dijkstra_map.disable_point(2)
+    dijkstra_map.disable_point(0)
+    assert_eq(Array(dijkstra_map.get_disabled_points()), [0, 2])
+
+func test_get_point_connections():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_points(PoolIntArray([0, 1, 2]))
+    dijkstra_map.connect_points(0, 1, 2.0)
+    dijkstra_map.connect_points(0, 2, 1.0, false)
+    assert_eq(dijkstra_map.get_point_connections(0), {1: 2.0, 2: 1.0})
+
+func test_get_point_incoming_connections():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_points(PoolIntArray([0, 1, 2]))
+    dijkstra_map.connect_points(0, 1, 2.0)
+    dijkstra_map.connect_points(2, 1, 1.0, false)
+    assert_eq(dijkstra_map.get_point_incoming_connections(1), {0: 2.0, 2: 1.0})
+
+func test_get_all_connections():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_points(PoolIntArray([0, 1, 2]))
+    dijkstra_map.connect_points(0, 1, 2.0)
+    dijkstra_map.connect_points(2, 1, 1.0, false)
+    var connections = dijkstra_map.get_all_connections()
+    assert_eq(Array(connections["sources"]), [0, 1, 2])
+    assert_eq(Array(connections["targets"]), [1, 0, 1])
+    assert_eq(Array(connections["weights"]), [2.0, 2.0, 1.0])
+
+func test_to_dot():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_points(PoolIntArray([0, 1]))
+    dijkstra_map.connect_points(0, 1, 1.5, false)
+    var dot = dijkstra_map.to_dot()
+    assert_true(dot.begins_with("digraph dijkstra_map {"))
+    assert_true("0 -> 1 [label=\"1.5\"];" in dot)
+
+func test_to_graphml():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_points(PoolIntArray([0, 1]))
+    dijkstra_map.connect_points(0, 1, 1.5, false)
+    dijkstra_map.recalculate(0)
+    var graphml = dijkstra_map.to_graphml(true)
+    assert_true('<data key="cost">1.5</data>' in graphml)
+
 func test_get_direction_at_point():
     var dijkstra_map = DijkstraMap.new()
     dijkstra_map.add_point(0)
@@ -180,6 +290,73 @@ func test_recalculate():
     # 2 is too far from 0, so because we set "maximum_cost" to 2.0, it is inaccessible.
     assert_eq(dijkstra_map.get_direction_at_point(2), -1)
 
+func test_compute_paths():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.add_point(2)
+    dijkstra_map.connect_points(0, 1)
+    var paths = dijkstra_map.compute_paths(0)
+    assert_eq(paths["cost_map"][1], 1.0)
+    assert_eq(paths["direction_map"][1], 0)
+    assert_false(paths["cost_map"].has(2))
+    # the map itself is unchanged
+    assert_eq(dijkstra_map.get_cost_at_point(1), INF)
+
+func test_recalculate_async():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.connect_points(0, 1)
+    var job_id = dijkstra_map.recalculate_async(0)
+    assert_ne(job_id, -1)
+    var finished_job_id = yield(dijkstra_map, "recalculated")
+    assert_eq(finished_job_id, job_id)
+    assert_eq(dijkstra_map.get_cost_at_point(1), 1.0)
+
+func test_cancel_recalculation():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    var job_id = dijkstra_map.recalculate_async(0)
+    assert_eq(dijkstra_map.cancel_recalculation(job_id), OK)
+    assert_eq(dijkstra_map.cancel_recalculation(job_id), FAILED)
+    # starting a job cancels the previous one
+    var first_job_id = dijkstra_map.recalculate_async(0)
+    var second_job_id = dijkstra_map.recalculate_async(0)
+    assert_eq(dijkstra_map.cancel_recalculation(first_job_id), FAILED)
+    assert_eq(dijkstra_map.cancel_recalculation(second_job_id), OK)
+
+func test_is_recalculating():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    assert_false(dijkstra_map.is_recalculating())
+    dijkstra_map.recalculate_async(0)
+    assert_true(dijkstra_map.is_recalculating())
+    yield(dijkstra_map, "recalculated")
+    assert_false(dijkstra_map.is_recalculating())
+
+func test_start_recalculation():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.add_point(2)
+    dijkstra_map.connect_points(0, 1)
+    dijkstra_map.connect_points(1, 2)
+    assert_eq(dijkstra_map.start_recalculation(0), OK)
+    assert_false(dijkstra_map.step_recalculation(2))
+    assert_eq(dijkstra_map.get_cost_at_point(2), 2.0)
+    assert_true(dijkstra_map.step_recalculation(2))
+
+func test_step_recalculation():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.connect_points(0, 1)
+    dijkstra_map.start_recalculation(0)
+    while not dijkstra_map.step_recalculation(1):
+        pass
+    assert_eq(dijkstra_map.get_cost_at_point(1), 1.0)
+
 func test_get_direction_at_points():
     var dijkstra_map = DijkstraMap.new()
     dijkstra_map.add_point(0)
@@ -231,3 +408,353 @@ func test_get_all_points_with_cost_between():
     dijkstra_map.recalculate(0)
     assert_eq(Array(dijkstra_map.get_all_points_with_cost_between(0.5, 1.5)), [1])
 
+func test_set_point_position():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    assert_eq(dijkstra_map.set_point_position(0, Vector2(10, 20)), OK)
+    assert_eq(dijkstra_map.get_point_position(0), Vector2(10, 20))
+    assert_eq(dijkstra_map.set_point_position(1, Vector2(10, 20)), FAILED)
+    dijkstra_map.begin_transaction()
+    dijkstra_map.set_point_position(0, Vector3(1, 2, 3))
+    dijkstra_map.commit_transaction()
+    assert_eq(dijkstra_map.get_point_position(0), Vector3(1, 2, 3))
+    dijkstra_map.undo()
+    assert_eq(dijkstra_map.get_point_position(0), Vector2(10, 20))
+
+func test_get_point_position():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 2, 2))
+    assert_eq(dijkstra_map.get_point_position(grid[Vector2(1, 0)]), Vector2(1, 0))
+    dijkstra_map.add_point(10)
+    assert_eq(dijkstra_map.get_point_position(10), null)
+
+func test_get_point_at_position():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 2, 2))
+    assert_eq(dijkstra_map.get_point_at_position(Vector2(1, 1)), grid[Vector2(1, 1)])
+    assert_eq(dijkstra_map.get_point_at_position(Vector2(5, 5)), -1)
+
+func test_get_nearest_point():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 10, 10))
+    dijkstra_map.disable_point(grid[Vector2(3, 4)])
+    assert_eq(dijkstra_map.get_nearest_point(Vector2(3.1, 3.6)), grid[Vector2(3, 4)])
+    assert_eq(dijkstra_map.get_nearest_point(Vector2(3.1, 3.6), true), grid[Vector2(3, 3)])
+
+func test_get_points_within_radius():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 10, 10))
+    var points = dijkstra_map.get_points_within_radius(Vector2(5, 5), 1.0)
+    assert_eq(points.size(), 5)
+    assert_eq(points[0], grid[Vector2(5, 5)])
+
+func test_set_spatial_cell_size():
+    var dijkstra_map = DijkstraMap.new()
+    assert_eq(dijkstra_map.set_spatial_cell_size(16.0), OK)
+    assert_eq(dijkstra_map.set_spatial_cell_size(0.0), FAILED)
+
+func test_get_shortest_path_positions_from_point():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 3, 1))
+    dijkstra_map.recalculate(grid[Vector2(2, 0)])
+    var path = dijkstra_map.get_shortest_path_positions_from_point(grid[Vector2(0, 0)])
+    assert_eq(Array(path), [Vector2(1, 0), Vector2(2, 0)])
+
+func test_get_shortest_path_positions_from_point_3d():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.connect_points(0, 1)
+    dijkstra_map.set_point_position(1, Vector3(1, 2, 3))
+    dijkstra_map.recalculate(1)
+    assert_eq(Array(dijkstra_map.get_shortest_path_positions_from_point_3d(0)), [Vector3(1, 2, 3)])
+
+func test_get_reachable_positions():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 3, 1))
+    dijkstra_map.disable_point(grid[Vector2(1, 0)])
+    dijkstra_map.recalculate(grid[Vector2(0, 0)])
+    assert_eq(Array(dijkstra_map.get_reachable_positions()), [Vector2(0, 0)])
+
+func test_get_all_positions_with_cost_between():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 4, 1))
+    dijkstra_map.recalculate(grid[Vector2(0, 0)])
+    var positions = dijkstra_map.get_all_positions_with_cost_between(0.5, 2.5)
+    assert_eq(Array(positions), [Vector2(1, 0), Vector2(2, 0)])
+
+func test_get_smoothed_path_from_point():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 4, 4))
+    dijkstra_map.recalculate(grid[Vector2(3, 3)])
+    var path = dijkstra_map.get_smoothed_path_from_point(grid[Vector2(0, 0)], grid, {}, Vector2(16, 16))
+    assert_eq(Array(path), [Vector2(0, 0), Vector2(48, 48)])
+
+func test_get_any_angle_path():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 6, 3))
+    for x in range(1, 5):
+        dijkstra_map.disable_point(grid[Vector2(x, 1)])
+    var path = dijkstra_map.get_any_angle_path(grid[Vector2(1, 0)], grid[Vector2(1, 2)], grid)
+    assert_eq(Array(path), [Vector2(1, 0), Vector2(0, 0), Vector2(0, 2), Vector2(1, 2)])
+
+func test_add_square_grid():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 5, 5), -1, 1.0, INF, true)
+    assert_true(dijkstra_map.has_connection(grid[Vector2(0, 2)], grid[Vector2(4, 2)]))
+    assert_false(dijkstra_map.has_connection(grid[Vector2(2, 0)], grid[Vector2(2, 4)]))
+
+func test_add_square_grid_with_ids():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid_with_ids(Rect2(0, 0, 10, 5), 100)
+    assert_eq(grid[Vector2(3, 2)], 123)
+    assert_true(dijkstra_map.add_square_grid_with_ids(Rect2(0, 0, 10, 5), 100).empty())
+
+func test_grid_cells_to_ids():
+    var dijkstra_map = DijkstraMap.new()
+    var ids = dijkstra_map.grid_cells_to_ids(Rect2(0, 0, 10, 5), 100, PoolVector2Array([Vector2(3, 2), Vector2(20, 0)]))
+    assert_eq(ids, [123, null])
+
+func test_grid_ids_to_cells():
+    var dijkstra_map = DijkstraMap.new()
+    var cells = dijkstra_map.grid_ids_to_cells(Rect2(0, 0, 10, 5), 100, PoolIntArray([123, 7]))
+    assert_eq(cells, [Vector2(3, 2), null])
+
+func test_add_hexagonal_grid_with_layout():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_hexagonal_grid_with_layout(Rect2(0, 0, 3, 3), "flat_top", "odd_offset")
+    assert_true(dijkstra_map.has_connection(grid[Vector2(1, 1)], grid[Vector2(0, 2)]))
+    assert_false(dijkstra_map.has_connection(grid[Vector2(1, 1)], grid[Vector2(0, 0)]))
+    var cube = dijkstra_map.add_hexagonal_grid_with_layout(Rect2(0, 0, 3, 3), "pointy_top", "cube")
+    assert_true(dijkstra_map.has_connection(cube[Vector3(1, 1, -2)], cube[Vector3(2, 0, -2)]))
+
+func test_add_isometric_grid():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_isometric_grid(Rect2(0, 0, 3, 3))
+    assert_true(dijkstra_map.has_connection(grid[Vector2(1, 1)], grid[Vector2(1, 0)]))
+    assert_eq(dijkstra_map.get_point_position(grid[Vector2(1, 0)]), Vector2(0.5, 0.25))
+
+func test_add_staggered_isometric_grid():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_staggered_isometric_grid(Rect2(0, 0, 3, 3))
+    assert_true(dijkstra_map.has_connection(grid[Vector2(1, 1)], grid[Vector2(2, 0)]))
+    assert_false(dijkstra_map.has_connection(grid[Vector2(1, 1)], grid[Vector2(0, 0)]))
+
+func test_extend_square_grid():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.extend_square_grid(Rect2(0, 0, 4, 4))
+    var chunk = dijkstra_map.extend_square_grid(Rect2(-4, 0, 4, 4))
+    var point = dijkstra_map.get_grid_point(Vector2(0, 2))
+    assert_true(dijkstra_map.has_connection(point, chunk[Vector2(-1, 2)]))
+    assert_true(dijkstra_map.has_connection(chunk[Vector2(-1, 2)], point))
+
+func test_register_grid_cells():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 4, 4))
+    assert_eq(dijkstra_map.register_grid_cells(grid), OK)
+    var chunk = dijkstra_map.extend_square_grid(Rect2(4, 0, 4, 4))
+    assert_true(dijkstra_map.has_connection(grid[Vector2(3, 2)], chunk[Vector2(4, 2)]))
+    assert_true(dijkstra_map.has_connection(chunk[Vector2(4, 2)], grid[Vector2(3, 2)]))
+
+func test_remove_grid_chunk():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.extend_square_grid(Rect2(0, 0, 8, 4))
+    var removed = dijkstra_map.remove_grid_chunk(Rect2(4, 0, 4, 4))
+    assert_eq(removed.size(), 16)
+    assert_eq(dijkstra_map.get_grid_point(Vector2(4, 0)), -1)
+
+func test_get_grid_cell():
+    var dijkstra_map = DijkstraMap.new()
+    var chunk = dijkstra_map.extend_square_grid(Rect2(2, 3, 2, 2))
+    assert_eq(dijkstra_map.get_grid_cell(chunk[Vector2(3, 4)]), Vector2(3, 4))
+    assert_eq(dijkstra_map.get_grid_cell(1000), null)
+
+func test_add_navigation_polygons():
+    var dijkstra_map = DijkstraMap.new()
+    var vertices = PoolVector2Array([Vector2(0, 0), Vector2(2, 0), Vector2(2, 2), Vector2(0, 2), Vector2(4, 0), Vector2(4, 2)])
+    var polygons = [PoolIntArray([0, 1, 2, 3]), PoolIntArray([1, 4, 5, 2])]
+    var points = dijkstra_map.add_navigation_polygons(vertices, polygons)
+    assert_true(dijkstra_map.has_connection(points["polygons"][0], points["polygons"][1]))
+    assert_eq(dijkstra_map.get_point_position(points["polygons"][1]), Vector2(3, 1))
+
+func test_get_weakly_connected_components():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.add_point(2)
+    dijkstra_map.connect_points(0, 1, 1.0, false)
+    var components = dijkstra_map.get_weakly_connected_components()
+    assert_eq(components[0], components[1])
+    assert_ne(components[0], components[2])
+
+func test_get_strongly_connected_components():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.add_point(2)
+    dijkstra_map.connect_points(0, 1)
+    dijkstra_map.connect_points(1, 2, 1.0, false)
+    var components = dijkstra_map.get_strongly_connected_components()
+    assert_eq(components[0], components[1])
+    assert_ne(components[1], components[2])
+
+func test_extract_regions():
+    var dijkstra_map = DijkstraMap.new()
+    # room (0, 1) -- corridor (2) -- room (3, 4)
+    for i in range(5):
+        dijkstra_map.add_point(i, 1 if i == 2 else -1)
+        if i > 0:
+            dijkstra_map.connect_points(i - 1, i)
+    var regions = dijkstra_map.extract_regions()
+    assert_eq(regions["map"].get_available_point_id(), 3)
+    assert_eq(regions["region_of"][4], 2)
+    assert_true(regions["map"].has_connection(1, 2))
+    assert_eq(regions["doorways"].size(), 4)
+    assert_eq(regions["centers"][0], 0)
+    # from 0 to 1 in the room, then to 2
+    regions["map"].recalculate(1, {"terrain_weights": {1: 1.0}})
+    assert_eq(regions["map"].get_cost_at_point(0), 2.0)
+
+func test_is_reachable():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.add_point(2)
+    dijkstra_map.connect_points(0, 1, 1.0, false)
+    assert_true(dijkstra_map.is_reachable(0, 1))
+    assert_false(dijkstra_map.is_reachable(1, 0))
+    assert_false(dijkstra_map.is_reachable(0, 2))
+    dijkstra_map.connect_points(1, 2)
+    assert_true(dijkstra_map.is_reachable(0, 2))
+
+func test_recalculate_all_pairs():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.add_point(2)
+    dijkstra_map.connect_points(0, 1)
+    dijkstra_map.connect_points(1, 2)
+    dijkstra_map.recalculate_all_pairs()
+    assert_eq(dijkstra_map.get_distance(0, 2), 2.0)
+    assert_eq(dijkstra_map.get_distance(2, 0), 2.0)
+    assert_eq(dijkstra_map.get_next_hop(0, 2), 1)
+
+func test_get_distance():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.add_point(2)
+    dijkstra_map.connect_points(0, 1, 1.0, false)
+    dijkstra_map.recalculate_all_pairs()
+    assert_eq(dijkstra_map.get_distance(0, 1), 1.0)
+    assert_eq(dijkstra_map.get_distance(1, 0), INF)
+    dijkstra_map.connect_points(1, 2)
+    assert_eq(dijkstra_map.get_distance(0, 1), INF, "paths must be recalculated")
+
+func test_get_next_hop():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.add_point(2)
+    dijkstra_map.connect_points(0, 1)
+    dijkstra_map.recalculate_all_pairs()
+    assert_eq(dijkstra_map.get_next_hop(0, 1), 1)
+    assert_eq(dijkstra_map.get_next_hop(0, 2), -1)
+
+func test_get_revision():
+    var dijkstra_map = DijkstraMap.new()
+    watch_signals(dijkstra_map)
+    # handlers can call methods of the map
+    dijkstra_map.connect("graph_changed", dijkstra_map, "has_point")
+    var revision = dijkstra_map.get_revision()
+    dijkstra_map.add_point(0)
+    assert_gt(dijkstra_map.get_revision(), revision)
+    yield(get_tree(), "idle_frame")
+    assert_signal_emitted_with_parameters(dijkstra_map, "graph_changed", [dijkstra_map.get_revision()])
+
+func test_is_stale():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    assert_true(dijkstra_map.is_stale())
+    dijkstra_map.recalculate(0)
+    assert_false(dijkstra_map.is_stale())
+    dijkstra_map.set_terrain_for_point(0, 2)
+    assert_true(dijkstra_map.is_stale())
+
+func test_get_changed_points_since():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.add_point(2)
+    var revision = dijkstra_map.get_revision()
+    dijkstra_map.connect_points(0, 2)
+    assert_eq(Array(dijkstra_map.get_changed_points_since(revision)), [0, 2])
+
+func test_begin_transaction():
+    var dijkstra_map = DijkstraMap.new()
+    assert_eq(dijkstra_map.begin_transaction(), OK)
+    assert_eq(dijkstra_map.begin_transaction(), FAILED)
+
+func test_commit_transaction():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.begin_transaction()
+    dijkstra_map.add_point(0)
+    assert_eq(dijkstra_map.commit_transaction(), OK)
+    assert_eq(dijkstra_map.commit_transaction(), FAILED)
+    assert_true(dijkstra_map.can_undo())
+
+func test_rollback_transaction():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0)
+    dijkstra_map.add_point(1)
+    dijkstra_map.connect_points(0, 1)
+    dijkstra_map.begin_transaction()
+    dijkstra_map.disable_point(1)
+    assert_false(dijkstra_map.is_reachable(0, 1))
+    assert_eq(dijkstra_map.rollback_transaction(), OK)
+    assert_true(dijkstra_map.is_reachable(0, 1))
+
+func test_undo():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.begin_transaction()
+    dijkstra_map.add_point(0)
+    dijkstra_map.commit_transaction()
+    assert_true(dijkstra_map.undo())
+    assert_false(dijkstra_map.has_point(0))
+    assert_false(dijkstra_map.undo())
+
+func test_redo():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.begin_transaction()
+    dijkstra_map.add_point(0)
+    dijkstra_map.commit_transaction()
+    dijkstra_map.undo()
+    assert_true(dijkstra_map.redo())
+    assert_true(dijkstra_map.has_point(0))
+    assert_false(dijkstra_map.redo())
+
+func test_can_undo():
+    var dijkstra_map = DijkstraMap.new()
+    assert_false(dijkstra_map.can_undo())
+    dijkstra_map.begin_transaction()
+    dijkstra_map.add_point(0)
+    dijkstra_map.commit_transaction()
+    assert_true(dijkstra_map.can_undo())
+
+func test_can_redo():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.begin_transaction()
+    dijkstra_map.add_point(0)
+    dijkstra_map.commit_transaction()
+    assert_false(dijkstra_map.can_redo())
+    dijkstra_map.undo()
+    assert_true(dijkstra_map.can_redo())
+
+func test_clear_history():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.begin_transaction()
+    dijkstra_map.add_point(0)
+    dijkstra_map.commit_transaction()
+    dijkstra_map.clear_history()
+    assert_false(dijkstra_map.can_undo())
+
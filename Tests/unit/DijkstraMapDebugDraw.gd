@@ -0,0 +1,27 @@
+extends "res://addons/gut/test.gd"
+
+# This file was automatically generated using [gdnative-doc-rs](https://github.com/arnaudgolfouse/gdnative-doc-rs)
+# 
+# Crate: dijkstra_map_gd
+# Source file: debug_draw.rs
+
+func test_set_dijkstra_map():
+    var dijkstra_map = DijkstraMap.new()
+    var debug_draw = DijkstraMapDebugDraw.new()
+    assert_eq(debug_draw.set_dijkstra_map(dijkstra_map), OK)
+    assert_eq(debug_draw.set_dijkstra_map(Reference.new()), FAILED)
+    assert_eq(debug_draw.set_dijkstra_map(null), OK)
+    debug_draw.free()
+
+func test_set_positions():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 4, 4))
+    var positions = {}
+    for cell in grid:
+        positions[grid[cell]] = cell * 32.0 + Vector2(16, 16)
+    var debug_draw = DijkstraMapDebugDraw.new()
+    debug_draw.set_dijkstra_map(dijkstra_map)
+    debug_draw.set_positions(positions)
+    dijkstra_map.recalculate(grid[Vector2(0, 0)])
+    debug_draw.free()
+
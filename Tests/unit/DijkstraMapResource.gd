@@ -0,0 +1,31 @@
+extends "res://addons/gut/test.gd"
+
+# This file was automatically generated using [gdnative-doc-rs](https://github.com/arnaudgolfouse/gdnative-doc-rs)
+# 
+# Crate: dijkstra_map_gd
+# Source file: resource.rs
+
+func test_store():
+    var dijkstra_map = DijkstraMap.new()
+    dijkstra_map.add_point(0, 2)
+    dijkstra_map.add_point(1)
+    dijkstra_map.connect_points(0, 1, 2.5, false)
+    var resource = DijkstraMapResource.new()
+    assert_eq(resource.store(dijkstra_map), OK)
+    assert_eq(Array(resource.point_ids), [0, 1])
+    assert_eq(Array(resource.terrains), [2, -1])
+    assert_eq(Array(resource.connection_weights), [2.5])
+
+func test_instantiate():
+    var dijkstra_map = DijkstraMap.new()
+    var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 3, 3))
+    dijkstra_map.disable_point(grid[Vector2(1, 1)])
+    var resource = DijkstraMapResource.new()
+    resource.store(dijkstra_map)
+    var copy = resource.instantiate()
+    assert_true(copy.has_connection(grid[Vector2(0, 0)], grid[Vector2(1, 0)]))
+    assert_true(copy.is_point_disabled(grid[Vector2(1, 1)]))
+    assert_eq(copy.get_point_position(grid[Vector2(2, 1)]), Vector2(2, 1))
+    resource.point_ids = PoolIntArray([0])
+    assert_eq(resource.instantiate(), null)
+
@@ -9,11 +9,47 @@ public class DijkstraMap : Node
 {
     private Object _dijkstraMap; 
 
+    [Signal]
+    public delegate void GraphChanged(long revision);
+
+    [Signal]
+    public delegate void Recalculated(long jobId);
+
+    [Signal]
+    public delegate void PathsChanged();
+
     public DijkstraMap()
     {
         var dijkstraMapScript = GD.Load("res://addons/dijkstra-map/Dijkstra_map_library/nativescript.gdns") as NativeScript;
         _dijkstraMap = dijkstraMapScript?.New() as Object;
         if (_dijkstraMap is null) throw new ArgumentNullException($"{nameof(_dijkstraMap)} cannot be null.");
+        _dijkstraMap.Connect("graph_changed", this, nameof(OnGraphChanged));
+        _dijkstraMap.Connect("recalculated", this, nameof(OnRecalculated));
+        _dijkstraMap.Connect("paths_changed", this, nameof(OnPathsChanged));
+    }
+
+    private DijkstraMap(Object dijkstraMap)
+    {
+        _dijkstraMap = dijkstraMap;
+        if (_dijkstraMap is null) throw new ArgumentNullException($"{nameof(_dijkstraMap)} cannot be null.");
+        _dijkstraMap.Connect("graph_changed", this, nameof(OnGraphChanged));
+        _dijkstraMap.Connect("recalculated", this, nameof(OnRecalculated));
+        _dijkstraMap.Connect("paths_changed", this, nameof(OnPathsChanged));
+    }
+
+    private void OnGraphChanged(long revision)
+    {
+        EmitSignal(nameof(GraphChanged), revision);
+    }
+
+    private void OnRecalculated(long jobId)
+    {
+        EmitSignal(nameof(Recalculated), jobId);
+    }
+
+    private void OnPathsChanged()
+    {
+        EmitSignal(nameof(PathsChanged));
     }
 
     public void Clear()
@@ -26,6 +62,44 @@ public class DijkstraMap : Node
         return (Error)_dijkstraMap.Call("duplicate_graph_from", sourceInstance._dijkstraMap);
     }
 
+    public Error LoadEdgeListCsv(string text, bool bidirectional = false)
+    {
+        return (Error)_dijkstraMap.Call("load_edge_list_csv", text, bidirectional);
+    }
+
+    public Error LoadDimacs(string text)
+    {
+        return (Error)_dijkstraMap.Call("load_dimacs", text);
+    }
+
+    public Godot.Collections.Dictionary<Vector2, int> LoadAsciiGrid(string text, float orthogonalCost = 1f,
+        float diagonalCost = float.PositiveInfinity)
+    {
+        var dictionary = _dijkstraMap.Call("load_ascii_grid", text, orthogonalCost, diagonalCost) as Dictionary;
+        return new Godot.Collections.Dictionary<Vector2, int>(dictionary);
+    }
+
+    public Error AttachDebugDraw(Node2D debugDraw)
+    {
+        return (Error)debugDraw.Call("set_dijkstra_map", _dijkstraMap);
+    }
+
+    public static DijkstraMap FromResource(Resource resource)
+    {
+        var dijkstraMap = resource.Call("instantiate") as Object;
+        return dijkstraMap is null ? null : new DijkstraMap(dijkstraMap);
+    }
+
+    public Error StoreInResource(Resource resource)
+    {
+        return (Error)resource.Call("store", _dijkstraMap);
+    }
+
+    public DijkstraMap Fork()
+    {
+        return new DijkstraMap(_dijkstraMap.Call("fork") as Object);
+    }
+
     public int GetAvailablePointId()
     {
         return (int)_dijkstraMap.Call("get_available_point_id");
@@ -36,6 +110,11 @@ public class DijkstraMap : Node
         return (Error)_dijkstraMap.Call("add_point", pointId, terrainType);
     }
     
+    public Error AddPoints(int[] pointIds, int[] terrainTypes = null)
+    {
+        return (Error)_dijkstraMap.Call("add_points", pointIds, terrainTypes ?? new int[0]);
+    }
+    
     public Error SetTerrainForPoint(int pointId, int terrainId = -1)
     {
         return (Error)_dijkstraMap.Call("set_terrain_for_point", pointId, terrainId);
@@ -76,6 +155,11 @@ public class DijkstraMap : Node
         return (Error)_dijkstraMap.Call("connect_points", source, target, weight, bidirectional);
     }
     
+    public Error AddConnections(int[] sources, int[] targets, float[] weights = null, bool bidirectional = true)
+    {
+        return (Error)_dijkstraMap.Call("add_connections", sources, targets, weights ?? new float[0], bidirectional);
+    }
+    
     public Error RemoveConnection(int source, int target, bool bidirectional = true)
     {
         return (Error)_dijkstraMap.Call("remove_connection", source, target, bidirectional);
@@ -85,6 +169,43 @@ public class DijkstraMap : Node
     {
         return (bool)_dijkstraMap.Call("has_connection", source, target);
     }
+    
+    public int[] GetAllPointIds()
+    {
+        return (int[])_dijkstraMap.Call("get_all_point_ids");
+    }
+    
+    public int[] GetDisabledPoints()
+    {
+        return (int[])_dijkstraMap.Call("get_disabled_points");
+    }
+    
+    public Godot.Collections.Dictionary<int, float> GetPointConnections(int pointId)
+    {
+        var dictionary = _dijkstraMap.Call("get_point_connections", pointId) as Dictionary;
+        return new Godot.Collections.Dictionary<int, float>(dictionary);
+    }
+    
+    public Godot.Collections.Dictionary<int, float> GetPointIncomingConnections(int pointId)
+    {
+        var dictionary = _dijkstraMap.Call("get_point_incoming_connections", pointId) as Dictionary;
+        return new Godot.Collections.Dictionary<int, float>(dictionary);
+    }
+    
+    public Dictionary GetAllConnections()
+    {
+        return _dijkstraMap.Call("get_all_connections") as Dictionary;
+    }
+
+    public string ToDot(bool includePaths = false)
+    {
+        return (string)_dijkstraMap.Call("to_dot", includePaths);
+    }
+
+    public string ToGraphml(bool includePaths = false)
+    {
+        return (string)_dijkstraMap.Call("to_graphml", includePaths);
+    }
 
     public int GetDirectionAtPoint(int pointId)
     {
@@ -116,6 +237,81 @@ public class DijkstraMap : Node
         return Recalculate(pointIds, GetGeneralizedOptions(options));
     }
 
+    public Dictionary ComputePaths(int pointId, Godot.Collections.Dictionary<string, object> options)
+    {
+        return _dijkstraMap.Call("compute_paths", pointId, options) as Dictionary;
+    }
+
+    public Dictionary ComputePaths(Array<int> pointIds, Godot.Collections.Dictionary<string, object> options)
+    {
+        return _dijkstraMap.Call("compute_paths", pointIds, options) as Dictionary;
+    }
+
+    public Dictionary ComputePaths(int pointId, IEnumerable<IDijkstraMapRecalculateOptions> options)
+    {
+        return ComputePaths(pointId, GetGeneralizedOptions(options));
+    }
+
+    public Dictionary ComputePaths(Array<int> pointIds, IEnumerable<IDijkstraMapRecalculateOptions> options)
+    {
+        return ComputePaths(pointIds, GetGeneralizedOptions(options));
+    }
+
+    public long RecalculateAsync(int pointId, Godot.Collections.Dictionary<string, object> options)
+    {
+        return (long)_dijkstraMap.Call("recalculate_async", pointId, options);
+    }
+
+    public long RecalculateAsync(Array<int> pointIds, Godot.Collections.Dictionary<string, object> options)
+    {
+        return (long)_dijkstraMap.Call("recalculate_async", pointIds, options);
+    }
+
+    public long RecalculateAsync(int pointId, IEnumerable<IDijkstraMapRecalculateOptions> options)
+    {
+        return RecalculateAsync(pointId, GetGeneralizedOptions(options));
+    }
+
+    public long RecalculateAsync(Array<int> pointIds, IEnumerable<IDijkstraMapRecalculateOptions> options)
+    {
+        return RecalculateAsync(pointIds, GetGeneralizedOptions(options));
+    }
+
+    public Error CancelRecalculation(long jobId)
+    {
+        return (Error)_dijkstraMap.Call("cancel_recalculation", jobId);
+    }
+
+    public bool IsRecalculating()
+    {
+        return (bool)_dijkstraMap.Call("is_recalculating");
+    }
+
+    public Error StartRecalculation(int pointId, Godot.Collections.Dictionary<string, object> options)
+    {
+        return (Error)_dijkstraMap.Call("start_recalculation", pointId, options);
+    }
+
+    public Error StartRecalculation(Array<int> pointIds, Godot.Collections.Dictionary<string, object> options)
+    {
+        return (Error)_dijkstraMap.Call("start_recalculation", pointIds, options);
+    }
+
+    public Error StartRecalculation(int pointId, IEnumerable<IDijkstraMapRecalculateOptions> options)
+    {
+        return StartRecalculation(pointId, GetGeneralizedOptions(options));
+    }
+
+    public Error StartRecalculation(Array<int> pointIds, IEnumerable<IDijkstraMapRecalculateOptions> options)
+    {
+        return StartRecalculation(pointIds, GetGeneralizedOptions(options));
+    }
+
+    public bool StepRecalculation(long maxExpansions)
+    {
+        return (bool)_dijkstraMap.Call("step_recalculation", maxExpansions);
+    }
+
     public Array<int> GetDirectionAtPoints(Array<int> points)
     {
         var array = _dijkstraMap.Call("get_direction_at_points", points) as Array;
@@ -151,23 +347,309 @@ public class DijkstraMap : Node
         var array = _dijkstraMap.Call("get_shortest_path_from_point", pointId) as Array;
         return new Array<int>(array);
     }
+
+    public Error SetPointPosition(int pointId, Vector2 position)
+    {
+        return (Error)_dijkstraMap.Call("set_point_position", pointId, position);
+    }
+
+    public Error SetPointPosition(int pointId, Vector3 position)
+    {
+        return (Error)_dijkstraMap.Call("set_point_position", pointId, position);
+    }
+
+    public object GetPointPosition(int pointId)
+    {
+        return _dijkstraMap.Call("get_point_position", pointId);
+    }
+
+    public int GetPointAtPosition(Vector2 position)
+    {
+        return (int)_dijkstraMap.Call("get_point_at_position", position);
+    }
+
+    public int GetPointAtPosition(Vector3 position)
+    {
+        return (int)_dijkstraMap.Call("get_point_at_position", position);
+    }
+
+    public int GetNearestPoint(Vector2 position, bool onlyEnabled = false)
+    {
+        return (int)_dijkstraMap.Call("get_nearest_point", position, onlyEnabled);
+    }
+
+    public int GetNearestPoint(Vector3 position, bool onlyEnabled = false)
+    {
+        return (int)_dijkstraMap.Call("get_nearest_point", position, onlyEnabled);
+    }
+
+    public int[] GetPointsWithinRadius(Vector2 position, float radius, bool onlyEnabled = false)
+    {
+        return (int[])_dijkstraMap.Call("get_points_within_radius", position, radius, onlyEnabled);
+    }
+
+    public int[] GetPointsWithinRadius(Vector3 position, float radius, bool onlyEnabled = false)
+    {
+        return (int[])_dijkstraMap.Call("get_points_within_radius", position, radius, onlyEnabled);
+    }
+
+    public Error SetSpatialCellSize(float cellSize)
+    {
+        return (Error)_dijkstraMap.Call("set_spatial_cell_size", cellSize);
+    }
+
+    public Vector2[] GetShortestPathPositionsFromPoint(int pointId)
+    {
+        return (Vector2[])_dijkstraMap.Call("get_shortest_path_positions_from_point", pointId);
+    }
+
+    public Vector3[] GetShortestPathPositionsFromPoint3D(int pointId)
+    {
+        return (Vector3[])_dijkstraMap.Call("get_shortest_path_positions_from_point_3d", pointId);
+    }
+
+    public Vector2[] GetReachablePositions()
+    {
+        return (Vector2[])_dijkstraMap.Call("get_reachable_positions");
+    }
+
+    public Vector3[] GetReachablePositions3D()
+    {
+        return (Vector3[])_dijkstraMap.Call("get_reachable_positions_3d");
+    }
+
+    public Vector2[] GetAllPositionsWithCostBetween(float minCost, float maxCost)
+    {
+        return (Vector2[])_dijkstraMap.Call("get_all_positions_with_cost_between", minCost, maxCost);
+    }
+
+    public Vector3[] GetAllPositionsWithCostBetween3D(float minCost, float maxCost)
+    {
+        return (Vector3[])_dijkstraMap.Call("get_all_positions_with_cost_between_3d", minCost, maxCost);
+    }
+
+    public Vector2[] GetSmoothedPathFromPoint(int pointId, Godot.Collections.Dictionary<Vector2, int> grid,
+        Godot.Collections.Dictionary<int, float> terrainWeights = null, Vector2? cellSize = null)
+    {
+        return (Vector2[])_dijkstraMap.Call("get_smoothed_path_from_point", pointId, grid,
+            terrainWeights ?? new Godot.Collections.Dictionary<int, float>(), cellSize ?? Vector2.One);
+    }
+
+    public Vector2[] GetAnyAnglePath(int source, int target, Godot.Collections.Dictionary<Vector2, int> grid,
+        Godot.Collections.Dictionary<int, float> terrainWeights = null, Vector2? cellSize = null)
+    {
+        return (Vector2[])_dijkstraMap.Call("get_any_angle_path", source, target, grid,
+            terrainWeights ?? new Godot.Collections.Dictionary<int, float>(), cellSize ?? Vector2.One);
+    }
     
     public Godot.Collections.Dictionary<Vector2, int> AddSquareGrid(Rect2 bounds, int terrainType = -1, 
+        float orthogonalCost = 1f, float diagonalCost = float.PositiveInfinity, bool wrapX = false, bool wrapY = false)
+    {
+        var dictionary = _dijkstraMap.Call("add_square_grid", bounds, terrainType, orthogonalCost, diagonalCost,
+            wrapX, wrapY) as Dictionary;
+        return new Godot.Collections.Dictionary<Vector2, int>(dictionary);
+    }
+    
+    public Godot.Collections.Dictionary<Vector2, int> AddHexagonalGrid(Rect2 bounds, int terrainType = -1, 
+        float weight = 1f, bool wrapX = false, bool wrapY = false)
+    {
+        var dictionary = _dijkstraMap.Call("add_hexagonal_grid", bounds, terrainType, weight, wrapX, wrapY) 
+            as Dictionary;
+        return new Godot.Collections.Dictionary<Vector2, int>(dictionary);
+    }
+    
+    public Godot.Collections.Dictionary<Vector2, int> AddSquareGridWithIds(Rect2 bounds, int baseId,
+        int terrainType = -1, float orthogonalCost = 1f, float diagonalCost = float.PositiveInfinity,
+        bool wrapX = false, bool wrapY = false)
+    {
+        var dictionary = _dijkstraMap.Call("add_square_grid_with_ids", bounds, baseId, terrainType, orthogonalCost,
+            diagonalCost, wrapX, wrapY) as Dictionary;
+        return new Godot.Collections.Dictionary<Vector2, int>(dictionary);
+    }
+    
+    public Godot.Collections.Dictionary<Vector2, int> AddHexagonalGridWithIds(Rect2 bounds, int baseId,
+        int terrainType = -1, float weight = 1f, bool wrapX = false, bool wrapY = false)
+    {
+        var dictionary = _dijkstraMap.Call("add_hexagonal_grid_with_ids", bounds, baseId, terrainType, weight,
+            wrapX, wrapY) as Dictionary;
+        return new Godot.Collections.Dictionary<Vector2, int>(dictionary);
+    }
+    
+    public Array GridCellsToIds(Rect2 bounds, int baseId, Vector2[] cells)
+    {
+        return _dijkstraMap.Call("grid_cells_to_ids", bounds, baseId, cells) as Array;
+    }
+    
+    public Array GridIdsToCells(Rect2 bounds, int baseId, int[] ids)
+    {
+        return _dijkstraMap.Call("grid_ids_to_cells", bounds, baseId, ids) as Array;
+    }
+    
+    public Godot.Collections.Dictionary<Vector2, int> AddHexagonalGridWithLayout(Rect2 bounds, string orientation,
+        string coordinates, int terrainType = -1, float weight = 1f)
+    {
+        var dictionary = _dijkstraMap.Call("add_hexagonal_grid_with_layout", bounds, orientation, coordinates,
+            terrainType, weight) as Dictionary;
+        return new Godot.Collections.Dictionary<Vector2, int>(dictionary);
+    }
+    
+    public Godot.Collections.Dictionary<Vector3, int> AddCubeHexagonalGrid(Rect2 bounds, string orientation,
+        int terrainType = -1, float weight = 1f)
+    {
+        var dictionary = _dijkstraMap.Call("add_hexagonal_grid_with_layout", bounds, orientation, "cube",
+            terrainType, weight) as Dictionary;
+        return new Godot.Collections.Dictionary<Vector3, int>(dictionary);
+    }
+    
+    public Godot.Collections.Dictionary<Vector2, int> AddIsometricGrid(Rect2 bounds, int terrainType = -1, 
         float orthogonalCost = 1f, float diagonalCost = float.PositiveInfinity)
     {
-        var dictionary = _dijkstraMap.Call("add_square_grid", bounds, terrainType, orthogonalCost, diagonalCost) 
+        var dictionary = _dijkstraMap.Call("add_isometric_grid", bounds, terrainType, orthogonalCost, diagonalCost) 
             as Dictionary;
         return new Godot.Collections.Dictionary<Vector2, int>(dictionary);
     }
     
-    public Godot.Collections.Dictionary<Vector2, int> AddHexagonalGrid(Rect2 bounds, int terrainType = -1, 
-        float weight = 1f)
+    public Godot.Collections.Dictionary<Vector2, int> AddStaggeredIsometricGrid(Rect2 bounds, int terrainType = -1, 
+        float orthogonalCost = 1f, float diagonalCost = float.PositiveInfinity)
     {
-        var dictionary = _dijkstraMap.Call("add_hexagonal_grid", bounds, terrainType, weight) 
+        var dictionary = _dijkstraMap.Call("add_staggered_isometric_grid", bounds, terrainType, orthogonalCost,
+            diagonalCost) as Dictionary;
+        return new Godot.Collections.Dictionary<Vector2, int>(dictionary);
+    }
+    
+    public Godot.Collections.Dictionary<Vector2, int> ExtendSquareGrid(Rect2 bounds, int terrainType = -1, 
+        float orthogonalCost = 1f, float diagonalCost = float.PositiveInfinity)
+    {
+        var dictionary = _dijkstraMap.Call("extend_square_grid", bounds, terrainType, orthogonalCost, diagonalCost) 
             as Dictionary;
         return new Godot.Collections.Dictionary<Vector2, int>(dictionary);
     }
     
+    public Error RegisterGridCells(Godot.Collections.Dictionary<Vector2, int> grid)
+    {
+        return (Error)_dijkstraMap.Call("register_grid_cells", grid);
+    }
+    
+    public int[] RemoveGridChunk(Rect2 bounds)
+    {
+        return (int[])_dijkstraMap.Call("remove_grid_chunk", bounds);
+    }
+    
+    public int GetGridPoint(Vector2 cell)
+    {
+        return (int) _dijkstraMap.Call("get_grid_point", cell);
+    }
+    
+    public Vector2? GetGridCell(int pointId)
+    {
+        return _dijkstraMap.Call("get_grid_cell", pointId) as Vector2?;
+    }
+    
+    public Dictionary AddNavigationPolygons(Vector2[] vertices, Array<int[]> polygons, int terrainType = -1,
+        bool addPortals = false)
+    {
+        return _dijkstraMap.Call("add_navigation_polygons", vertices, polygons, terrainType, addPortals)
+            as Dictionary;
+    }
+
+    public Godot.Collections.Dictionary<int, int> GetWeaklyConnectedComponents()
+    {
+        var dictionary = _dijkstraMap.Call("get_weakly_connected_components") as Dictionary;
+        return new Godot.Collections.Dictionary<int, int>(dictionary);
+    }
+
+    public Godot.Collections.Dictionary<int, int> GetStronglyConnectedComponents()
+    {
+        var dictionary = _dijkstraMap.Call("get_strongly_connected_components") as Dictionary;
+        return new Godot.Collections.Dictionary<int, int>(dictionary);
+    }
+
+    public DijkstraMap ExtractRegions(out Godot.Collections.Dictionary<int, int> regionOf,
+        out Array<int[]> doorways, out int[] centers)
+    {
+        var dictionary = _dijkstraMap.Call("extract_regions") as Dictionary;
+        regionOf = new Godot.Collections.Dictionary<int, int>(dictionary["region_of"] as Dictionary);
+        doorways = new Array<int[]>(dictionary["doorways"] as Array);
+        centers = (int[])dictionary["centers"];
+        return new DijkstraMap(dictionary["map"] as Object);
+    }
+
+    public bool IsReachable(int source, int target)
+    {
+        return (bool)_dijkstraMap.Call("is_reachable", source, target);
+    }
+    
+    public void RecalculateAllPairs(Godot.Collections.Dictionary<int, float> terrainWeights = null)
+    {
+        _dijkstraMap.Call("recalculate_all_pairs", terrainWeights ?? new Godot.Collections.Dictionary<int, float>());
+    }
+
+    public float GetDistance(int source, int target)
+    {
+        return (float)_dijkstraMap.Call("get_distance", source, target);
+    }
+
+    public int GetNextHop(int source, int target)
+    {
+        return (int)_dijkstraMap.Call("get_next_hop", source, target);
+    }
+    
+    public long GetRevision()
+    {
+        return (long)_dijkstraMap.Call("get_revision");
+    }
+
+    public bool IsStale()
+    {
+        return (bool)_dijkstraMap.Call("is_stale");
+    }
+
+    public Array<int> GetChangedPointsSince(long revision)
+    {
+        var array = _dijkstraMap.Call("get_changed_points_since", revision) as Array;
+        return new Array<int>(array);
+    }
+    
+    public Error BeginTransaction()
+    {
+        return (Error)_dijkstraMap.Call("begin_transaction");
+    }
+
+    public Error CommitTransaction()
+    {
+        return (Error)_dijkstraMap.Call("commit_transaction");
+    }
+
+    public Error RollbackTransaction()
+    {
+        return (Error)_dijkstraMap.Call("rollback_transaction");
+    }
+
+    public bool Undo()
+    {
+        return (bool)_dijkstraMap.Call("undo");
+    }
+
+    public bool Redo()
+    {
+        return (bool)_dijkstraMap.Call("redo");
+    }
+
+    public bool CanUndo()
+    {
+        return (bool)_dijkstraMap.Call("can_undo");
+    }
+
+    public bool CanRedo()
+    {
+        return (bool)_dijkstraMap.Call("can_redo");
+    }
+
+    public void ClearHistory()
+    {
+        _dijkstraMap.Call("clear_history");
+    }
+
     private static Godot.Collections.Dictionary<string, object> GetGeneralizedOptions(
         IEnumerable<IDijkstraMapRecalculateOptions> options)
     {
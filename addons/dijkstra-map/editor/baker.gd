@@ -0,0 +1,83 @@
+tool
+extends Node
+# Bakes the graph of a TileMap into a DijkstraMapResource in the editor, so
+# that the game does not build it at startup.
+#
+# Each used cell of the TileMap becomes a point, except the cells whose tile
+# is in `wall_tiles`. Its terrain is given by `tile_terrains`, or is the tile
+# ID if the tile is not in `tile_terrains`. Its position is its cell, so at
+# runtime, `get_point_at_position(cell)` and `get_point_position(id)` convert
+# between cells and point IDs.
+#
+# Press the "Rebake" button of the inspector after editing the TileMap. At
+# runtime, create the DijkstraMap with `create_dijkstra_map()`.
+
+# The TileMap to bake.
+export(NodePath) var tilemap_path: NodePath
+# Terrain of the tiles : keys are tile IDs, and values terrain IDs.
+export(Dictionary) var tile_terrains: Dictionary = {}
+# Tiles that are not walkable.
+export(Array, int) var wall_tiles: Array = []
+# Cost of the orthogonal connections. INF disables them.
+export(float) var orthogonal_cost: float = 1.0
+# Cost of the diagonal connections. INF disables them.
+export(float) var diagonal_cost: float = INF
+# The baked graph. If it is saved in its own file, the file is updated.
+export(Resource) var graph: Resource
+
+
+# Replaces `graph` by the graph of the TileMap.
+func bake() -> int:
+	var baked: Resource = bake_graph()
+	if baked == null:
+		return FAILED
+	graph = baked
+	property_list_changed_notify()
+	return OK
+
+
+# Bakes the graph of the TileMap, or returns null on failure.
+#
+# If `graph` is saved in its own file, it is updated, saved and returned.
+# Else, returns a new DijkstraMapResource without modifying `graph`, so that
+# the editor can assign it as an undoable action.
+func bake_graph() -> Resource:
+	var tilemap: TileMap = get_node_or_null(tilemap_path) as TileMap
+	if tilemap == null:
+		push_error("DijkstraMapBaker : 'tilemap_path' must point to a TileMap")
+		return null
+	if graph != null and not graph is DijkstraMapResource:
+		push_error("DijkstraMapBaker : 'graph' must be a DijkstraMapResource")
+		return null
+
+	var dijkstra_map = DijkstraMap.new()
+	var grid: Dictionary = dijkstra_map.add_square_grid(
+		tilemap.get_used_rect(), -1, orthogonal_cost, diagonal_cost
+	)
+	for cell in grid:
+		var tile: int = tilemap.get_cellv(cell)
+		if tile == TileMap.INVALID_CELL or tile in wall_tiles:
+			dijkstra_map.remove_point(grid[cell])
+		else:
+			dijkstra_map.set_terrain_for_point(grid[cell], tile_terrains.get(tile, tile))
+
+	# built-in resources are saved with their scene
+	if graph != null and graph.resource_path != "" and not ("::" in graph.resource_path):
+		graph.store(dijkstra_map)
+		var error: int = ResourceSaver.save(graph.resource_path, graph)
+		if error != OK:
+			push_error("DijkstraMapBaker : failed to save " + graph.resource_path)
+			return null
+		return graph
+	var baked: Resource = DijkstraMapResource.new()
+	baked.store(dijkstra_map)
+	return baked
+
+
+# Creates the DijkstraMap of the baked graph, or returns null if it was not
+# baked.
+func create_dijkstra_map() -> Reference:
+	if graph == null:
+		push_error("DijkstraMapBaker : the graph was not baked")
+		return null
+	return graph.instantiate()
@@ -0,0 +1,35 @@
+tool
+extends EditorInspectorPlugin
+# Adds a "Rebake" button to the inspector of the DijkstraMapBaker nodes.
+
+const Baker = preload("baker.gd")
+
+# Undo history of the editor, set by the plugin.
+var undo_redo: UndoRedo
+
+
+func can_handle(object: Object) -> bool:
+	return object is Baker
+
+
+func parse_begin(object: Object) -> void:
+	var button: Button = Button.new()
+	button.text = "Rebake"
+	button.connect("pressed", self, "_rebake", [object])
+	add_custom_control(button)
+
+
+# Bakes `baker`, and assigns its new graph through the undo history, so that
+# the edited scene is marked as modified.
+func _rebake(baker: Baker) -> void:
+	var previous: Resource = baker.graph
+	var baked: Resource = baker.bake_graph()
+	# graphs saved in their own file are updated in place
+	if baked == null or baked == previous:
+		return
+	undo_redo.create_action("Rebake DijkstraMapBaker")
+	undo_redo.add_do_property(baker, "graph", baked)
+	undo_redo.add_undo_property(baker, "graph", previous)
+	undo_redo.add_do_method(baker, "property_list_changed_notify")
+	undo_redo.add_undo_method(baker, "property_list_changed_notify")
+	undo_redo.commit_action()
@@ -0,0 +1,16 @@
+tool
+extends EditorPlugin
+
+var inspector_plugin: EditorInspectorPlugin
+
+
+func _enter_tree() -> void:
+	add_custom_type("DijkstraMapBaker", "Node", preload("editor/baker.gd"), preload("dm_icon.png"))
+	inspector_plugin = preload("editor/baker_inspector.gd").new()
+	inspector_plugin.undo_redo = get_undo_redo()
+	add_inspector_plugin(inspector_plugin)
+
+
+func _exit_tree() -> void:
+	remove_inspector_plugin(inspector_plugin)
+	remove_custom_type("DijkstraMapBaker")
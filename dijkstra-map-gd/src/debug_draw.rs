@@ -0,0 +1,314 @@
+use super::{position_to_vector2, type_warning, variant_to_interface, Interface, FAILED, OK};
+use dijkstra_map::PointId;
+use fnv::FnvHashMap;
+use gdnative::api::Gradient;
+use gdnative::core_types::VariantType;
+use gdnative::prelude::*;
+
+/// Signals of the `DijkstraMap` after which the overlay is redrawn.
+const REDRAW_SIGNALS: [&str; 2] = ["graph_changed", "paths_changed"];
+
+/// Node drawing a debug overlay of a `DijkstraMap` : cost heatmap,
+/// direction arrows, disabled points and connections.
+///
+/// # Usage
+/// 1. Add a `DijkstraMapDebugDraw` to the scene, above the nodes it
+/// annotates.
+/// 2. Give it the map to draw with
+/// [set_dijkstra_map](#func-set_dijkstra_map), and the position of the
+/// points with [set_positions](#func-set_positions).
+///
+///     Points without a position there are drawn at their
+/// [position in the map](DijkstraMap.md#func-get_point_position), and
+/// points without either are not drawn.
+/// 3. The overlay is redrawn automatically whenever the graph or the paths
+/// of the map change (after [recalculate](DijkstraMap.md#func-recalculate)
+/// for example). Call `update` to redraw it after changing its
+/// properties.
+///
+/// # Properties
+/// - `draw_costs` (default : [true]) : draw a disc on each reached point,
+/// coloured by its cost.
+/// - `draw_directions` (default : [true]) : draw an arrow from each reached
+/// point toward its direction.
+/// - `draw_disabled_points` (default : [true]) : draw a cross on disabled
+/// points.
+/// - `draw_connections` (default : [false]) : draw a line for each
+/// connection.
+/// - `cost_gradient` (default : `null`) : [Gradient] giving the colour of
+/// the costs, from `0` to `max_cost`. If `null`, costs go from green to red.
+/// - `max_cost` (default : `0.0`) : cost drawn with the end of
+/// `cost_gradient`. If not positive, the highest cost reached is used.
+/// - `point_radius` (default : `6.0`) : radius of the cost discs, and size
+/// of the crosses and arrow heads.
+/// - `line_width` (default : `1.0`) : width of the arrows, crosses and
+/// connections.
+/// - `direction_color`, `disabled_color`, `connection_color` : colours of
+/// the arrows, crosses and connections.
+#[derive(NativeClass)]
+#[inherit(Node2D)]
+pub struct DebugDraw {
+    /// Map drawn by the overlay.
+    dijkstra_map: Option<Instance<Interface, Shared>>,
+    /// Positions set with [set_positions](#func-set_positions).
+    positions: FnvHashMap<PointId, Vector2>,
+    #[property(default = true)]
+    draw_costs: bool,
+    #[property(default = true)]
+    draw_directions: bool,
+    #[property(default = true)]
+    draw_disabled_points: bool,
+    #[property(default = false)]
+    draw_connections: bool,
+    #[property]
+    cost_gradient: Option<Ref<Gradient, Shared>>,
+    #[property(default = 0.0)]
+    max_cost: f32,
+    #[property(default = 6.0)]
+    point_radius: f32,
+    #[property(default = 1.0)]
+    line_width: f32,
+    #[property]
+    direction_color: Color,
+    #[property]
+    disabled_color: Color,
+    #[property]
+    connection_color: Color,
+}
+
+/// Colour of the costs when `cost_gradient` is not set : green to red.
+fn default_cost_color(offset: f32) -> Color {
+    Color::from_rgba(offset, 1.0 - offset, 0.0, 0.8)
+}
+
+#[methods]
+impl DebugDraw {
+    fn new(_base: &Node2D) -> Self {
+        DebugDraw {
+            dijkstra_map: None,
+            positions: FnvHashMap::default(),
+            draw_costs: true,
+            draw_directions: true,
+            draw_disabled_points: true,
+            draw_connections: false,
+            cost_gradient: None,
+            max_cost: 0.0,
+            point_radius: 6.0,
+            line_width: 1.0,
+            direction_color: Color::from_rgba(1.0, 1.0, 1.0, 1.0),
+            disabled_color: Color::from_rgba(0.0, 0.0, 0.0, 1.0),
+            connection_color: Color::from_rgba(0.5, 0.5, 0.5, 0.5),
+        }
+    }
+
+    /// Sets the `DijkstraMap` drawn by the overlay, or removes it if
+    /// `dijkstra_map` is `null`.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [FAILED] if `dijkstra_map` is neither `null`
+    /// nor a `DijkstraMap`, in which case the previous map is kept. Else it
+    /// returns [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var debug_draw = DijkstraMapDebugDraw.new()
+    /// assert_eq(debug_draw.set_dijkstra_map(dijkstra_map), OK)
+    /// assert_eq(debug_draw.set_dijkstra_map(Reference.new()), FAILED)
+    /// assert_eq(debug_draw.set_dijkstra_map(null), OK)
+    /// debug_draw.free()
+    /// ```
+    #[method]
+    pub fn set_dijkstra_map(&mut self, #[base] base: TRef<Node2D>, dijkstra_map: Variant) -> i64 {
+        let instance = variant_to_interface(&dijkstra_map);
+        if instance.is_none() && !dijkstra_map.is_nil() {
+            godot_error!("Failed to convert Variant to DijkstraMap.");
+            return FAILED;
+        }
+        if let Some(previous) = self.dijkstra_map.take() {
+            let previous = unsafe { previous.base().assume_safe() };
+            for &signal in REDRAW_SIGNALS.iter() {
+                previous.disconnect(signal, base, "_on_dijkstra_map_changed");
+            }
+        }
+        if let Some(instance) = instance.as_ref() {
+            let object = unsafe { instance.base().assume_safe() };
+            for &signal in REDRAW_SIGNALS.iter() {
+                // cannot fail : the signal exists and the method too
+                let _ = object.connect(
+                    signal,
+                    base,
+                    "_on_dijkstra_map_changed",
+                    VariantArray::new_shared(),
+                    0,
+                );
+            }
+        }
+        self.dijkstra_map = instance;
+        base.update();
+        OK
+    }
+
+    /// Sets the position where each point is drawn.
+    ///
+    /// Keys of `positions` are point IDs, and values their position
+    /// ([Vector2]) in the local coordinates of this node. Entries of
+    /// incorrect types are ignored with a warning.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 4, 4))
+    /// var positions = {}
+    /// for cell in grid:
+    ///     positions[grid[cell]] = cell * 32.0 + Vector2(16, 16)
+    /// var debug_draw = DijkstraMapDebugDraw.new()
+    /// debug_draw.set_dijkstra_map(dijkstra_map)
+    /// debug_draw.set_positions(positions)
+    /// dijkstra_map.recalculate(grid[Vector2(0, 0)])
+    /// debug_draw.free()
+    /// ```
+    #[method]
+    pub fn set_positions(&mut self, #[base] base: TRef<Node2D>, positions: Dictionary) {
+        self.positions.clear();
+        for (point, position) in positions.iter() {
+            match (point.try_to::<i32>(), position.try_to::<Vector2>()) {
+                (Ok(point), Ok(position)) => {
+                    self.positions.insert(PointId(point), position);
+                }
+                (Err(_), _) => type_warning("key", VariantType::I64, point.get_type(), line!()),
+                (_, Err(_)) => {
+                    type_warning("value", VariantType::Vector2, position.get_type(), line!())
+                }
+            }
+        }
+        base.update();
+    }
+
+    /// Internal : redraws the overlay when the map changes.
+    #[method]
+    fn _on_dijkstra_map_changed(&self, #[base] base: TRef<Node2D>, #[opt] _argument: Variant) {
+        base.update();
+    }
+
+    /// Internal : draws the overlay.
+    #[method]
+    fn _draw(&self, #[base] base: TRef<Node2D>) {
+        let instance = match self.dijkstra_map.as_ref() {
+            Some(instance) => unsafe { instance.assume_safe() },
+            None => return,
+        };
+        let _ = instance.map(|interface, _| self.draw_map(&base, interface));
+    }
+}
+
+impl DebugDraw {
+    /// Position where `point` is drawn, if any.
+    fn position_of(&self, interface: &Interface, point: PointId) -> Option<Vector2> {
+        self.positions.get(&point).copied().or_else(|| {
+            interface
+                .dijkstra
+                .get_point_position(point)
+                .map(position_to_vector2)
+        })
+    }
+
+    /// Colour of `cost`, where `max_cost` is drawn with the end of the
+    /// gradient.
+    fn cost_color(&self, cost: f32, max_cost: f32) -> Color {
+        let offset = if max_cost > 0.0 {
+            (cost / max_cost).max(0.0).min(1.0)
+        } else {
+            0.0
+        };
+        match self.cost_gradient.as_ref() {
+            Some(gradient) => unsafe { gradient.assume_safe() }.interpolate(offset as f64),
+            None => default_cost_color(offset),
+        }
+    }
+
+    /// Draws the overlay of `interface` on `base`.
+    fn draw_map(&self, base: &Node2D, interface: &Interface) {
+        let dijkstra = &interface.dijkstra;
+        let width = self.line_width as f64;
+        let radius = self.point_radius;
+
+        if self.draw_connections {
+            for source in dijkstra.points() {
+                let from = match self.position_of(interface, source) {
+                    Some(from) => from,
+                    None => continue,
+                };
+                for (target, _) in dijkstra.connections_of(source) {
+                    if let Some(to) = self.position_of(interface, target) {
+                        base.draw_line(from, to, self.connection_color, width, true);
+                    }
+                }
+            }
+        }
+
+        let computed = dijkstra.get_path_results().get_direction_and_cost_map();
+        if self.draw_costs {
+            let max_cost = if self.max_cost > 0.0 {
+                self.max_cost
+            } else {
+                computed
+                    .values()
+                    .map(|info| info.cost.0)
+                    .filter(|cost| cost.is_finite())
+                    .fold(0.0, f32::max)
+            };
+            for (&point, info) in computed.iter() {
+                if let Some(position) = self.position_of(interface, point) {
+                    let color = self.cost_color(info.cost.0, max_cost);
+                    base.draw_circle(position, radius as f64, color);
+                }
+            }
+        }
+
+        if self.draw_directions {
+            for (&point, info) in computed.iter() {
+                if info.direction == point {
+                    // origins point to themselves
+                    continue;
+                }
+                let positions = (
+                    self.position_of(interface, point),
+                    self.position_of(interface, info.direction),
+                );
+                if let (Some(from), Some(toward)) = positions {
+                    if from == toward {
+                        // zero-length arrows have no direction
+                        continue;
+                    }
+                    let to = from + (toward - from) * 0.4;
+                    let back = (from - to).normalized() * radius;
+                    base.draw_line(from, to, self.direction_color, width, true);
+                    for &angle in [-0.5, 0.5].iter() {
+                        let head = to + back.rotated(angle);
+                        base.draw_line(to, head, self.direction_color, width, true);
+                    }
+                }
+            }
+        }
+
+        if self.draw_disabled_points {
+            let corner = Vector2::new(radius, radius);
+            let other_corner = Vector2::new(radius, -radius);
+            for point in dijkstra.disabled_points() {
+                if let Some(position) = self.position_of(interface, point) {
+                    let color = self.disabled_color;
+                    base.draw_line(position - corner, position + corner, color, width, true);
+                    base.draw_line(
+                        position - other_corner,
+                        position + other_corner,
+                        color,
+                        width,
+                        true,
+                    );
+                }
+            }
+        }
+    }
+}
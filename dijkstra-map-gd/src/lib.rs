@@ -11,21 +11,38 @@
 //! Just like [AStar], `DijkstraMap` operates on directed weighted
 //! graph. To match the naming convention with [AStar], vertices are
 //! called points and edges are called connections. Points are always
-//! referred to by their unique [integer](int) ID. Unlike [AStar],
-//! `DijkstraMap` does not store information about their real positions.
-//! Users have to store that information themselves, if they want it;
-//! for example, in a [Dictionary].
+//! referred to by their unique [integer](int) ID. Unlike [AStar], points
+//! do not need a position : it can optionally be set with
+//! [set_point_position](#func-set_point_position), to find the points
+//! nearest to a position and to return paths as positions.
 
-use dijkstra_map::{Cost, DijkstraMap, PointId, Read, TerrainType, Weight};
+use dijkstra_map::{
+    Components, Cost, DijkstraMap, GridIds, GridIdsError, GridWrap, HexCoordinates, HexOrientation,
+    OddWrappingWidth, PathResults, PointId, Position, Reachability, Read, TerrainType, Weight,
+};
+use euclid::Vector2D;
 use fnv::FnvHashMap;
 use fnv::FnvHashSet;
+use gdnative::core_types::VariantType;
 use gdnative::init::*;
 use gdnative::prelude::*;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Node drawing a debug overlay of the graph and paths of a `DijkstraMap`.
+mod debug_draw;
+/// Resource storing a graph, saved and loaded like other Godot resources.
+mod resource;
 
 /// Integer representing success in gdscript
 const OK: i64 = 0;
 /// Integer representing failure in gdscript
 const FAILED: i64 = 1;
+/// Number of points expanded by `recalculate_async` between two checks for
+/// cancellation.
+const JOB_CHUNK_SIZE: usize = 4096;
 
 /// Interface exported to Godot
 ///
@@ -67,10 +84,135 @@ const FAILED: i64 = 1;
 ///   If the `"input_is_destination"` argument was set to `false`, paths
 /// point towards the destination and inspected points are assumed to be
 /// origins.
+///
+/// # Signals
+/// Signals are emitted at the end of the frame, so that their handlers can
+/// call methods of the map.
+/// - `graph_changed(revision: int)` : emitted after every method that
+/// modifies the graph (points, connections, terrains or disabled points),
+/// with the new [revision](#func-get_revision).
+/// - `recalculated(job_id: int)` : emitted when the paths computed by
+/// [recalculate_async](#func-recalculate_async) are ready.
+/// - `paths_changed()` : emitted after every method that modifies the
+/// computed paths : [recalculate](#func-recalculate),
+/// [start_recalculation](#func-start_recalculation),
+/// [step_recalculation](#func-step_recalculation), and when the paths of
+/// [recalculate_async](#func-recalculate_async) are ready.
 #[derive(NativeClass)]
+// methods taking `&self` can run concurrently from several threads
+#[user_data(gdnative::export::user_data::RwLockData<Interface>)]
+#[register_with(Self::register_signals)]
 // #[no_constructor]
 pub struct Interface {
     dijkstra: DijkstraMap,
+    /// Cached result of [is_reachable](#func-is_reachable), along with the
+    /// revision of the graph it was computed at.
+    reachability: Option<(u64, Reachability)>,
+    /// Revision sent with the last `graph_changed` signal.
+    notified_revision: u64,
+    /// Recalculation running in the background, if any.
+    job: Option<Job>,
+    /// ID of the next job started by [recalculate_async](#func-recalculate_async).
+    next_job_id: i64,
+}
+
+/// Recalculation running in the background, started by
+/// [recalculate_async](#func-recalculate_async).
+struct Job {
+    /// ID returned by [recalculate_async](#func-recalculate_async).
+    id: i64,
+    /// Set when the job is cancelled, so that the search stops at the next
+    /// chunk of points.
+    cancelled: Arc<AtomicBool>,
+    /// Thread computing the paths, on a fork of the map.
+    thread: JoinHandle<Option<PathResults>>,
+}
+
+impl Interface {
+    /// Wraps `dijkstra` in a new `Interface`.
+    fn from_dijkstra_map(dijkstra: DijkstraMap) -> Self {
+        let notified_revision = dijkstra.revision();
+        Self {
+            dijkstra,
+            reachability: None,
+            notified_revision,
+            job: None,
+            next_job_id: 0,
+        }
+    }
+
+    /// Returns the positions of `points`, skipping the points without one.
+    fn positions_of<'a>(
+        &'a self,
+        points: impl IntoIterator<Item = PointId> + 'a,
+    ) -> impl Iterator<Item = Position> + 'a {
+        points
+            .into_iter()
+            .filter_map(move |point| self.dijkstra.get_point_position(point))
+    }
+
+    /// Points reached by the last recalculation, sorted by cost.
+    fn reachable_points(&self) -> &[PointId] {
+        self.dijkstra
+            .get_all_points_with_cost_between(Cost(f32::NEG_INFINITY), Cost(f32::INFINITY))
+    }
+
+    fn register_signals(builder: &ClassBuilder<Self>) {
+        builder
+            .signal("graph_changed")
+            .with_param("revision", VariantType::I64)
+            .done();
+        builder
+            .signal("recalculated")
+            .with_param("job_id", VariantType::I64)
+            .done();
+        builder.signal("paths_changed").done();
+    }
+
+    /// Must be called after every method that may modify the graph.
+    ///
+    /// Emits the `graph_changed` signal if the revision of the graph changed.
+    fn graph_changed(&mut self, base: &Reference) {
+        if self.dijkstra.revision() != self.notified_revision {
+            self.notified_revision = self.dijkstra.revision();
+            emit_deferred(
+                base,
+                "graph_changed",
+                &[(self.notified_revision as i64).to_variant()],
+            );
+        }
+    }
+
+    /// Replaces the graph by `dijkstra`, loaded from a text format.
+    ///
+    /// The recalculation running in the background is cancelled, since its
+    /// paths are computed on the previous graph.
+    fn replace_graph(&mut self, base: &Reference, dijkstra: DijkstraMap) {
+        self.cancel_job();
+        self.dijkstra.replace_graph(dijkstra);
+        self.graph_changed(base);
+    }
+
+    /// Cancels the recalculation running in the background, if any.
+    fn cancel_job(&mut self) {
+        if let Some(job) = self.job.take() {
+            job.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Emits `signal` with `args` once the current method returned.
+///
+/// Handlers may call methods of the map, which would deadlock if they ran
+/// while the method emitting the signal still holds the map.
+fn emit_deferred(base: &Reference, signal: &str, args: &[Variant]) {
+    let mut call_args = Vec::with_capacity(args.len() + 1);
+    call_args.push(signal.to_variant());
+    call_args.extend_from_slice(args);
+    // `emit_signal` exists on every object and takes any arguments
+    unsafe {
+        base.call_deferred("emit_signal", &call_args);
+    }
 }
 
 /// Change a Rust's [`Result`] to an integer (which is how errors are reported
@@ -106,6 +248,481 @@ fn variant_to_width_and_height(bounds: Variant) -> Option<(usize, usize, usize,
         })
         .ok()
 }
+
+/// Try to convert the given [`Variant`] into a rectangle whose position may
+/// be negative.
+///
+/// Only works if `bounds` is a [`Rect2D`].
+///
+/// # Return
+///
+/// `(offset, width, height)`
+fn variant_to_rect(bounds: Variant) -> Option<(Vector2D<i32, i32>, usize, usize)> {
+    bounds
+        .try_to()
+        .map(|rect: Rect2| {
+            (
+                Vector2D::new(rect.position.x as i32, rect.position.y as i32),
+                rect.size.x as usize,
+                rect.size.y as usize,
+            )
+        })
+        .ok()
+}
+
+/// Deterministic IDs of the grid covering `bounds`, whose top-left cell has
+/// the ID `base_id`.
+fn variant_to_grid_ids(bounds: Variant, base_id: i32) -> Option<GridIds> {
+    variant_to_rect(bounds).map(|(offset, width, height)| GridIds {
+        base: PointId(base_id),
+        offset,
+        width,
+        height,
+    })
+}
+
+/// Message logged when adding a grid with
+/// [add_square_grid_with_ids](Interface::add_square_grid_with_ids) fails.
+fn grid_ids_error_message(error: GridIdsError) -> &'static str {
+    match error {
+        GridIdsError::PointAlreadyExists => "The IDs of the grid are already taken",
+        GridIdsError::IdOverflow => "The IDs of the grid overflow the range of point IDs",
+        GridIdsError::OddWrappingWidth => {
+            "Hexagonal grids can only wrap horizontally with an even width"
+        }
+    }
+}
+
+/// Returns the `DijkstraMap` held by `variant`, if any.
+fn variant_to_interface(variant: &Variant) -> Option<Instance<Interface, Shared>> {
+    variant
+        .to_object::<Reference>()
+        .and_then(|object| unsafe { object.assume_safe() }.cast_instance::<Interface>())
+        .map(|instance| instance.claim())
+}
+
+/// Human-readable name of a [`VariantType`], for warnings.
+fn display_type(t: VariantType) -> &'static str {
+    match t {
+        VariantType::Nil => "nil",
+        VariantType::Bool => "bool",
+        VariantType::I64 => "integer",
+        VariantType::F64 => "float",
+        VariantType::GodotString => "string",
+        VariantType::Vector2 => "Vector2",
+        VariantType::Rect2 => "Rect2",
+        VariantType::Vector3 => "Vector3",
+        VariantType::Transform2D => "Transform2D",
+        VariantType::Plane => "Plane",
+        VariantType::Quat => "Quat",
+        VariantType::Aabb => "Aabb",
+        VariantType::Basis => "Basis",
+        VariantType::Transform => "Transform",
+        VariantType::Color => "Color",
+        VariantType::NodePath => "NodePath",
+        VariantType::Rid => "Rid",
+        VariantType::Object => "Object",
+        VariantType::Dictionary => "Dictionary",
+        VariantType::VariantArray => "array",
+        VariantType::ByteArray => "array of bytes",
+        VariantType::Int32Array => "array of integers",
+        VariantType::Float32Array => "array of floats",
+        VariantType::StringArray => "array of strings",
+        VariantType::Vector2Array => "array of Vector2",
+        VariantType::Vector3Array => "array of Vector3",
+        VariantType::ColorArray => "array of Color",
+    }
+}
+
+/// Helper function for type warnings
+///
+/// Ensure the style of warning reporting is consistent.
+fn type_warning(object: &str, expected: VariantType, got: VariantType, line: u32) {
+    godot_warn!(
+        "[{}:{}] {} has incorrect type : expected {}, got {}",
+        file!(),
+        line,
+        object,
+        display_type(expected),
+        display_type(got)
+    );
+}
+
+/// Convert a [`Dictionary`] of terrain IDs to weights.
+///
+/// Keys that are not integers are ignored with a warning.
+fn dictionary_to_terrain_weights(dict: &Dictionary) -> FnvHashMap<TerrainType, Weight> {
+    let mut terrain_weights = FnvHashMap::<TerrainType, Weight>::default();
+    for key in dict.keys() {
+        if let Some(id) = key.to::<i64>() {
+            terrain_weights.insert(
+                TerrainType::from(id as i32),
+                Weight(dict.get(key).unwrap().to::<f64>().unwrap_or(1.0) as f32),
+            );
+        } else {
+            type_warning(
+                "key in 'terrain_weights'",
+                VariantType::I64,
+                key.get_type(),
+                line!(),
+            );
+        }
+    }
+    terrain_weights
+}
+
+/// Convert a [Vector2] or [Vector3] [`Variant`] to a [`Position`].
+///
+/// The boolean is [`true`] for a [Vector3].
+fn variant_to_position(variant: &Variant) -> Option<(Position, bool)> {
+    match variant.get_type() {
+        VariantType::Vector2 => variant
+            .try_to::<Vector2>()
+            .ok()
+            .map(|position| (Position::new(position.x, position.y, 0.0), false)),
+        VariantType::Vector3 => variant
+            .try_to::<Vector3>()
+            .ok()
+            .map(|position| (Position::new(position.x, position.y, position.z), true)),
+        _ => None,
+    }
+}
+
+/// Drops the `z` coordinate of `position`.
+fn position_to_vector2(position: Position) -> Vector2 {
+    Vector2::new(position.x, position.y)
+}
+
+fn position_to_vector3(position: Position) -> Vector3 {
+    Vector3::new(position.x, position.y, position.z)
+}
+
+/// Convert a [`Dictionary`] returned by
+/// [add_square_grid](Interface::add_square_grid) back to grid positions.
+///
+/// Entries that are not `Vector2` to integer are ignored with a warning.
+fn dictionary_to_grid(dict: &Dictionary) -> FnvHashMap<Vector2D<i32, i32>, PointId> {
+    let mut grid = FnvHashMap::<Vector2D<i32, i32>, PointId>::default();
+    for (key, value) in dict.iter() {
+        match (key.try_to::<Vector2>(), value.to::<i64>()) {
+            (Ok(position), Some(id)) => {
+                grid.insert(
+                    Vector2D::new(position.x as i32, position.y as i32),
+                    PointId(id as i32),
+                );
+            }
+            (Err(_), _) => type_warning(
+                "key in 'grid'",
+                VariantType::Vector2,
+                key.get_type(),
+                line!(),
+            ),
+            (_, None) => type_warning(
+                "value in 'grid'",
+                VariantType::I64,
+                value.get_type(),
+                line!(),
+            ),
+        }
+    }
+    grid
+}
+
+/// Convert a grid returned by the grid builders of [`DijkstraMap`] to a
+/// [`Dictionary`] from grid positions to IDs.
+fn grid_to_dictionary(grid: &FnvHashMap<Vector2D<i32, i32>, PointId>) -> Dictionary {
+    let dict = Dictionary::new();
+    for (&k, &v) in grid.iter() {
+        dict.insert(
+            Vector2::new(k.x as f32, k.y as f32).to_variant(),
+            i32::from(v),
+        );
+    }
+    dict.into_shared()
+}
+
+/// Convert a path on a square grid to world positions, by scaling the grid
+/// positions of its points by `cell_size`.
+fn path_to_world_positions(
+    path: &[PointId],
+    grid: &FnvHashMap<Vector2D<i32, i32>, PointId>,
+    cell_size: Vector2,
+) -> Vector2Array {
+    let positions: FnvHashMap<PointId, Vector2D<i32, i32>> =
+        grid.iter().map(|(&position, &id)| (id, position)).collect();
+    Vector2Array::from_vec(
+        path.iter()
+            .filter_map(|point| positions.get(point))
+            .map(|position| {
+                Vector2::new(
+                    position.x as f32 * cell_size.x,
+                    position.y as f32 * cell_size.y,
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Convert [`Components`] to a [`Dictionary`] mapping points' IDs to their
+/// component's ID.
+fn components_to_dictionary(components: &Components) -> Dictionary {
+    let dict = Dictionary::new();
+    for (point, component) in components.iter() {
+        dict.insert(i32::from(point), component as i32);
+    }
+    dict.into_shared()
+}
+
+/// Arguments of [`DijkstraMap::recalculate`].
+struct RecalculateArgs {
+    origins: Vec<PointId>,
+    read: Option<Read>,
+    max_cost: Option<Cost>,
+    initial_costs: Vec<Cost>,
+    terrain_weights: FnvHashMap<TerrainType, Weight>,
+    termination_points: FnvHashSet<PointId>,
+}
+
+/// Parse the arguments of [recalculate](Interface::recalculate).
+///
+/// Returns [`None`] (after reporting the error) if they are invalid.
+fn parse_recalculate_args(
+    origin: Variant,
+    optional_params: Option<Dictionary>,
+) -> Option<RecalculateArgs> {
+    const TERRAIN_WEIGHT: &str = "terrain_weights";
+    const TERMINATION_POINTS: &str = "termination_points";
+    const INPUT_IS_DESTINATION: &str = "input_is_destination";
+    const MAXIMUM_COST: &str = "maximum_cost";
+    const INITIAL_COSTS: &str = "initial_costs";
+    const VALID_KEYS: [&str; 5] = [
+        TERRAIN_WEIGHT,
+        TERMINATION_POINTS,
+        INPUT_IS_DESTINATION,
+        MAXIMUM_COST,
+        INITIAL_COSTS,
+    ];
+
+    let optional_params = optional_params.unwrap_or_default();
+
+    // verify keys makes sense
+    for k in optional_params.keys().into_iter() {
+        let string: String = k.to_string();
+        if !VALID_KEYS.contains(&string.as_str()) {
+            godot_error!("Invalid Key `{}` in parameter", string);
+            return None;
+        }
+    }
+
+    // get origin points
+    let mut res_origins = Vec::<PointId>::new();
+    match origin.get_type() {
+        gdnative::core_types::VariantType::I64 => {
+            res_origins.push((origin.to::<i64>().unwrap() as i32).into())
+        }
+        gdnative::core_types::VariantType::Int32Array => {
+            res_origins = origin
+                .to::<gdnative::core_types::Int32Array>()
+                .unwrap()
+                .read()
+                .iter()
+                .map(|&x| x.into())
+                .collect();
+        }
+        gdnative::core_types::VariantType::VariantArray => {
+            for i in origin
+                .to::<gdnative::core_types::VariantArray>()
+                .unwrap()
+                .iter()
+            {
+                match i.to::<i64>() {
+                    Some(intval) => res_origins.push(PointId(intval as i32)),
+                    None => type_warning(
+                        "element of 'origin'",
+                        VariantType::I64,
+                        i.get_type(),
+                        line!(),
+                    ),
+                }
+            }
+        }
+        _ => {
+            godot_error!("Invalid argument type : Expected int or Array of ints");
+            return None;
+        }
+    };
+
+    // ===================
+    // Optional parameters
+    // ===================
+    let read: Option<Read> = {
+        // we need to check that the parameter exists first, because
+        // `optional_params.get` will create a `Nil` entry if it does not.
+        if optional_params.contains(INPUT_IS_DESTINATION) {
+            let value = optional_params.get(INPUT_IS_DESTINATION).unwrap();
+            match value.to::<bool>() {
+                Some(b) => Some(if b {
+                    Read::InputIsDestination
+                } else {
+                    Read::InputIsOrigin
+                }),
+                None => {
+                    type_warning(
+                        "'input_is_destination' key",
+                        VariantType::Bool,
+                        value.get_type(),
+                        line!(),
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        }
+    };
+
+    let max_cost: Option<Cost> = {
+        if optional_params.contains(MAXIMUM_COST) {
+            let value = optional_params.get(MAXIMUM_COST).unwrap();
+            match value.to::<f64>() {
+                Some(f) => Some(Cost(f as f32)),
+                None => {
+                    type_warning(
+                        "'max_cost' key",
+                        VariantType::F64,
+                        value.get_type(),
+                        line!(),
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        }
+    };
+
+    let initial_costs: Vec<Cost> = {
+        if optional_params.contains(INITIAL_COSTS) {
+            let mut initial_costs = Vec::<Cost>::new();
+            let value = optional_params.get(INITIAL_COSTS).unwrap();
+            match value.get_type() {
+                gdnative::core_types::VariantType::Float32Array => {
+                    for f in value
+                        .to::<gdnative::core_types::Float32Array>()
+                        .unwrap()
+                        .read()
+                        .iter()
+                    {
+                        initial_costs.push(Cost(*f))
+                    }
+                }
+                gdnative::core_types::VariantType::VariantArray => {
+                    for f in value
+                        .to::<gdnative::core_types::VariantArray>()
+                        .unwrap()
+                        .iter()
+                    {
+                        initial_costs.push(match f.to::<f64>() {
+                            Some(fval) => Cost(fval as f32),
+                            None => {
+                                type_warning(
+                                    "element of 'initial_costs'",
+                                    VariantType::F64,
+                                    f.get_type(),
+                                    line!(),
+                                );
+                                Cost(0.0)
+                            }
+                        })
+                    }
+                }
+                incorrect_type => type_warning(
+                    "'initial_costs' key",
+                    VariantType::Float32Array,
+                    incorrect_type,
+                    line!(),
+                ),
+            }
+            initial_costs
+        } else {
+            Vec::new()
+        }
+    };
+
+    let mut terrain_weights = FnvHashMap::<TerrainType, Weight>::default();
+    if optional_params.contains(TERRAIN_WEIGHT) {
+        let value = optional_params.get(TERRAIN_WEIGHT).unwrap();
+        if let Some(dict) = value.to::<gdnative::core_types::Dictionary>() {
+            terrain_weights = dictionary_to_terrain_weights(&dict);
+        } else {
+            type_warning(
+                "'terrain_weights' key",
+                VariantType::Int32Array,
+                value.get_type(),
+                line!(),
+            );
+        }
+    }
+
+    if terrain_weights.is_empty() {
+        godot_warn!("no terrain weights specified : all terrains will have infinite cost !")
+    }
+
+    let termination_points = if optional_params.contains(TERMINATION_POINTS) {
+        let value = optional_params.get(TERMINATION_POINTS).unwrap();
+        match value.get_type() {
+            gdnative::core_types::VariantType::I64 => {
+                std::iter::once(PointId(value.to::<i64>().unwrap() as i32)).collect()
+            }
+            gdnative::core_types::VariantType::Int32Array => value
+                .to::<gdnative::core_types::Int32Array>()
+                .unwrap()
+                .read()
+                .iter()
+                .map(|&x| PointId::from(x))
+                .collect(),
+            gdnative::core_types::VariantType::VariantArray => value
+                .to::<gdnative::core_types::VariantArray>()
+                .unwrap()
+                .iter()
+                .filter_map(|i| {
+                    let int = i.to::<i64>();
+                    if int.is_none() {
+                        type_warning(
+                            "value in 'termination_points'",
+                            VariantType::I64,
+                            i.get_type(),
+                            line!(),
+                        );
+                    }
+                    int
+                })
+                .map(|ival| PointId(ival as i32))
+                .collect(),
+            incorrect_type => {
+                type_warning(
+                    "'termination_points' key",
+                    VariantType::Int32Array,
+                    incorrect_type,
+                    line!(),
+                );
+                FnvHashSet::<PointId>::default()
+            }
+        }
+    } else {
+        FnvHashSet::default()
+    };
+
+    Some(RecalculateArgs {
+        origins: res_origins,
+        read,
+        max_cost,
+        initial_costs,
+        terrain_weights,
+        termination_points,
+    })
+}
+
 #[methods]
 impl Interface {
     /// Create a new empty `DijkstraMap`.
@@ -115,26 +732,37 @@ impl Interface {
     /// var dijkstra_map = DijkstraMap.new()
     /// ```
     pub fn new(_: &Reference) -> Self {
-        Self {
-            dijkstra: DijkstraMap::default(),
-        }
+        Self::from_dijkstra_map(DijkstraMap::default())
     }
 
     /// Clears the `DijkstraMap` of all points and connections.
     ///
+    /// This cancels the job started by
+    /// [recalculate_async](#func-recalculate_async), if any.
+    ///
     /// # Example
     /// ```gdscript
     /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.recalculate_async(0)
     /// dijkstra_map.clear()
+    /// assert_false(dijkstra_map.is_recalculating())
     /// ```
     #[method]
-    pub fn clear(&mut self) {
-        self.dijkstra.clear()
+    pub fn clear(&mut self, #[base] base: &Reference) {
+        self.cancel_job();
+        self.dijkstra.clear();
+        self.graph_changed(base);
     }
 
     /// If `source_instance` is a `DijkstraMap`, it is cloned into
     /// `self`.
     ///
+    /// This cancels the job started by
+    /// [recalculate_async](#func-recalculate_async), if any. The
+    /// [revision](#func-get_revision) of `self` keeps increasing, so it
+    /// can still be compared with the ones sent before.
+    ///
     /// # Errors
     ///
     /// This function returns [FAILED] if `source_instance` is not a
@@ -149,7 +777,11 @@ impl Interface {
     /// dijkstra_map.add_point(3)
     /// dijkstra_map.connect_points(1, 2, 1.0)
     /// var dijkstra_map_copy = DijkstraMap.new()
+    /// for id in range(10):
+    ///     dijkstra_map_copy.add_point(id)
+    /// var revision = dijkstra_map_copy.get_revision()
     /// dijkstra_map_copy.duplicate_graph_from(dijkstra_map)
+    /// assert_gt(dijkstra_map_copy.get_revision(), revision)
     /// dijkstra_map.add_point(4)
     /// assert_true(dijkstra_map_copy.has_point(1))
     /// assert_true(dijkstra_map_copy.has_point(2))
@@ -158,101 +790,290 @@ impl Interface {
     /// assert_false(dijkstra_map_copy.has_point(4))
     /// ```
     #[method]
-    pub fn duplicate_graph_from(&mut self, source_instance: Variant) -> i64 {
+    pub fn duplicate_graph_from(
+        &mut self,
+        #[base] base: &Reference,
+        source_instance: Variant,
+    ) -> i64 {
         let source_instance = source_instance.to_object::<Reference>().unwrap();
         let source_instance = unsafe { source_instance.assume_safe() };
         let source_instance: Option<TInstance<Interface, _>> =
             source_instance.cast_instance::<Interface>();
         match source_instance.and_then(|interface: TInstance<Interface, Shared>| {
             interface
-                .map(|interface, _| {
-                    self.dijkstra = interface.dijkstra.clone();
-                })
+                .map(|interface, _| interface.dijkstra.clone())
                 .ok()
         }) {
-            Some(_) => OK,
+            Some(dijkstra) => {
+                self.cancel_job();
+                self.dijkstra.replace_graph(dijkstra);
+                self.graph_changed(base);
+                OK
+            }
             None => {
                 godot_error!("Failed to convert Variant to DijkstraMap.");
                 FAILED
             }
         }
     }
-    /// Returns the first positive available id.
+
+    /// Replaces the graph by an edge list in CSV format, one connection per
+    /// line : `source,target,weight[,bidirectional]`.
+    ///
+    /// Points are created with the default terrain when they first appear.
+    /// The optional `bidirectional` column is `true`, `false`, `1` or `0`.
+    /// Empty lines, lines starting with `#` and a header line starting with
+    /// `source` are ignored.
+    ///
+    /// # Parameters
+    ///
+    /// - `text` : Content of the CSV file.
+    /// - `bidirectional` (default : [false]) : whether connections are
+    /// bidirectional when the `bidirectional` column is missing.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [FAILED] and prints the line of the first
+    /// malformed record if the text is malformed, in which case the map is
+    /// not modified. Else it returns [OK].
     ///
     /// # Example
     /// ```gdscript
     /// var dijkstra_map = DijkstraMap.new()
-    /// dijkstra_map.add_point(0)
-    /// dijkstra_map.add_point(1)
-    /// assert_eq(dijkstra_map.get_available_point_id(), 2)
+    /// var res: int = dijkstra_map.load_edge_list_csv("source,target,weight\n0,1,2.5\n1,2,1,true")
+    /// assert_eq(res, OK)
+    /// assert_true(dijkstra_map.has_connection(0, 1))
+    /// assert_false(dijkstra_map.has_connection(1, 0))
+    /// assert_true(dijkstra_map.has_connection(2, 1))
     /// ```
     #[method]
-    pub fn get_available_point_id(&mut self) -> i32 {
-        self.dijkstra.get_available_id(None).into()
+    pub fn load_edge_list_csv(
+        &mut self,
+        #[base] base: &Reference,
+        text: String,
+        #[opt] bidirectional: Option<bool>,
+    ) -> i64 {
+        match DijkstraMap::from_edge_list_csv(&text, bidirectional.unwrap_or(false)) {
+            Ok(dijkstra) => {
+                self.replace_graph(base, dijkstra);
+                OK
+            }
+            Err(error) => {
+                godot_error!("Invalid edge list : {}", error);
+                FAILED
+            }
+        }
     }
 
-    /// Add a new point with the given `terrain_type`.
+    /// Replaces the graph by a graph in the DIMACS shortest-path format
+    /// (`.gr` files).
     ///
-    /// If `terrain_type` is not specified, `-1` is used.
+    /// The problem line `p sp <points> <arcs>` creates the points `1` to
+    /// `<points>`, and each line `a <source> <target> <weight>` adds a
+    /// directed connection. Lines starting with `c` are comments.
     ///
     /// # Errors
     ///
-    /// If a point with the given id already exists, the map is unchanged and
-    /// [FAILED] is returned, else it returns [OK].
+    /// This function returns [FAILED] and prints the line of the first
+    /// malformed line if the text is malformed, in which case the map is not
+    /// modified. Else it returns [OK].
     ///
     /// # Example
     /// ```gdscript
-    /// var res: int
     /// var dijkstra_map = DijkstraMap.new()
-    /// res = dijkstra_map.add_point(0) # default terrain_type is -1
+    /// var res: int = dijkstra_map.load_dimacs("c tiny graph\np sp 3 2\na 1 2 4\na 2 3 1")
     /// assert_eq(res, OK)
-    /// res = dijkstra_map.add_point(1, 0) # terrain_type is 0
-    /// assert_eq(res, OK, "you may add a point once")
-    /// res = dijkstra_map.add_point(1, 0)
-    /// assert_eq(res, FAILED, "but not twice")
-    /// res = dijkstra_map.add_point(1, 1)
-    /// assert_eq(res, FAILED, "you cannot even change the terrain this way")
+    /// assert_true(dijkstra_map.has_point(3))
+    /// assert_true(dijkstra_map.has_connection(1, 2))
+    /// assert_false(dijkstra_map.has_connection(2, 1))
     /// ```
     #[method]
-    pub fn add_point(&mut self, point_id: i32, #[opt] terrain_type: Option<i32>) -> i64 {
-        let terrain_type: TerrainType = terrain_type.unwrap_or(-1).into();
-        let res = self.dijkstra.add_point(point_id.into(), terrain_type);
-        result_to_int(res)
+    pub fn load_dimacs(&mut self, #[base] base: &Reference, text: String) -> i64 {
+        match DijkstraMap::from_dimacs(&text) {
+            Ok(dijkstra) => {
+                self.replace_graph(base, dijkstra);
+                OK
+            }
+            Err(error) => {
+                godot_error!("Invalid DIMACS graph : {}", error);
+                FAILED
+            }
+        }
     }
 
-    /// Set the terrain type for `point_id`.
+    /// Replaces the graph by a square grid drawn in ASCII art, one row per
+    /// line : `#` is a wall, `.` a point with the default terrain and a
+    /// digit a point with that terrain.
     ///
-    /// If `terrain_id` is not specified, `-1` is used.
+    /// Point IDs are numbered from `0` in reading order, and the position
+    /// of each point is set to its coordinates.
     ///
-    /// # Errors
-    /// If the given id does not exists in the map, [FAILED] is returned, else
-    /// [OK].
+    /// # Parameters
+    ///
+    /// - `text` : The ASCII-art map.
+    /// - `orthogonal_cost` (default : `1.0`) : specifies cost of
+    /// orthogonal connections (up, down, right and left).
+    /// - `diagonal_cost` (default : [INF]) : specifies cost of
+    /// diagonal connections. \
+    ///   If `diagonal_cost` is [INF] or [NAN], diagonal connections
+    /// are disabled.
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] where keys are coordinates
+    /// of points ([Vector2]) and values are their corresponding point
+    /// IDs. If the text contains an unknown character, the map is not
+    /// modified, the line of the character is printed and the dictionary
+    /// is empty.
     ///
     /// # Example
     /// ```gdscript
-    /// var res: int
     /// var dijkstra_map = DijkstraMap.new()
-    /// dijkstra_map.add_point(0, 2)
-    /// res = dijkstra_map.set_terrain_for_point(0, 1)
-    /// assert_eq(res, OK, "you can set the point's terrain")
-    /// assert_eq(dijkstra_map.get_terrain_for_point(0), 1, "the terrain corresponds")
-    /// res = dijkstra_map.set_terrain_for_point(0)
-    /// assert_eq(res, OK, "multiple times if you want")
-    /// assert_eq(dijkstra_map.get_terrain_for_point(0), -1, "default terrain is -1")
+    /// var grid = dijkstra_map.load_ascii_grid("..#\n.2.")
+    /// assert_eq(grid.size(), 5)
+    /// assert_eq(dijkstra_map.get_terrain_for_point(grid[Vector2(1, 1)]), 2)
+    /// assert_true(dijkstra_map.has_connection(grid[Vector2(1, 0)], grid[Vector2(1, 1)]))
     /// ```
     #[method]
-    pub fn set_terrain_for_point(&mut self, point_id: i32, #[opt] terrain_id: Option<i32>) -> i64 {
-        let terrain_id = terrain_id.unwrap_or(-1);
-        let terrain: TerrainType = terrain_id.into();
-        let res = self
-            .dijkstra
-            .set_terrain_for_point(point_id.into(), terrain);
-        result_to_int(res)
-    }
-
-    /// Get the terrain type for the given point.
-    ///
-    /// This function returns `-1` if no point with the given id exists in the
+    pub fn load_ascii_grid(
+        &mut self,
+        #[base] base: &Reference,
+        text: String,
+        #[opt] orthogonal_cost: Option<f32>,
+        #[opt] diagonal_cost: Option<f32>,
+    ) -> Dictionary {
+        match DijkstraMap::from_ascii_grid(
+            &text,
+            orthogonal_cost.map(Weight),
+            diagonal_cost.map(Weight),
+        ) {
+            Ok((dijkstra, grid)) => {
+                self.replace_graph(base, dijkstra);
+                grid_to_dictionary(&grid)
+            }
+            Err(error) => {
+                godot_error!("Invalid ASCII grid : {}", error);
+                Dictionary::new().into_shared()
+            }
+        }
+    }
+
+    /// Returns a new `DijkstraMap` with the same graph as `self`.
+    ///
+    /// This is meant for trying out modifications, for example to check how
+    /// paths change if a bridge collapses. Unlike
+    /// [duplicate_graph_from](#func-duplicate_graph_from), the new map is
+    /// created by this call. Both maps share their points until they are
+    /// modified, so forking takes constant time even for large graphs, and
+    /// only the modified points use more memory.
+    ///
+    /// The fork has no computed paths, and an empty undo/redo history.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.connect_points(0, 1)
+    /// var fork = dijkstra_map.fork()
+    /// fork.disable_point(1)
+    /// assert_true(fork.is_point_disabled(1))
+    /// assert_false(dijkstra_map.is_point_disabled(1))
+    /// assert_true(fork.has_connection(0, 1))
+    /// ```
+    #[method]
+    pub fn fork(&self) -> Instance<Interface, Unique> {
+        Instance::emplace(Interface::from_dijkstra_map(self.dijkstra.fork()))
+    }
+
+    /// Returns the first positive available id.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// assert_eq(dijkstra_map.get_available_point_id(), 2)
+    /// ```
+    #[method]
+    pub fn get_available_point_id(&self) -> i32 {
+        self.dijkstra.get_available_id(None).into()
+    }
+
+    /// Add a new point with the given `terrain_type`.
+    ///
+    /// If `terrain_type` is not specified, `-1` is used.
+    ///
+    /// # Errors
+    ///
+    /// If a point with the given id already exists, the map is unchanged and
+    /// [FAILED] is returned, else it returns [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var res: int
+    /// var dijkstra_map = DijkstraMap.new()
+    /// res = dijkstra_map.add_point(0) # default terrain_type is -1
+    /// assert_eq(res, OK)
+    /// res = dijkstra_map.add_point(1, 0) # terrain_type is 0
+    /// assert_eq(res, OK, "you may add a point once")
+    /// res = dijkstra_map.add_point(1, 0)
+    /// assert_eq(res, FAILED, "but not twice")
+    /// res = dijkstra_map.add_point(1, 1)
+    /// assert_eq(res, FAILED, "you cannot even change the terrain this way")
+    /// ```
+    #[method]
+    pub fn add_point(
+        &mut self,
+        #[base] base: &Reference,
+        point_id: i32,
+        #[opt] terrain_type: Option<i32>,
+    ) -> i64 {
+        let terrain_type: TerrainType = terrain_type.unwrap_or(-1).into();
+        let res = self.dijkstra.add_point(point_id.into(), terrain_type);
+        self.graph_changed(base);
+        result_to_int(res)
+    }
+
+    /// Set the terrain type for `point_id`.
+    ///
+    /// If `terrain_id` is not specified, `-1` is used.
+    ///
+    /// # Errors
+    /// If the given id does not exists in the map, [FAILED] is returned, else
+    /// [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var res: int
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0, 2)
+    /// res = dijkstra_map.set_terrain_for_point(0, 1)
+    /// assert_eq(res, OK, "you can set the point's terrain")
+    /// assert_eq(dijkstra_map.get_terrain_for_point(0), 1, "the terrain corresponds")
+    /// res = dijkstra_map.set_terrain_for_point(0)
+    /// assert_eq(res, OK, "multiple times if you want")
+    /// assert_eq(dijkstra_map.get_terrain_for_point(0), -1, "default terrain is -1")
+    /// ```
+    #[method]
+    pub fn set_terrain_for_point(
+        &mut self,
+        #[base] base: &Reference,
+        point_id: i32,
+        #[opt] terrain_id: Option<i32>,
+    ) -> i64 {
+        let terrain_id = terrain_id.unwrap_or(-1);
+        let terrain: TerrainType = terrain_id.into();
+        let res = self
+            .dijkstra
+            .set_terrain_for_point(point_id.into(), terrain);
+        self.graph_changed(base);
+        result_to_int(res)
+    }
+
+    /// Get the terrain type for the given point.
+    ///
+    /// This function returns `-1` if no point with the given id exists in the
     /// map.
     ///
     /// # Example
@@ -266,7 +1087,7 @@ impl Interface {
     /// assert_eq(dijkstra_map.get_terrain_for_point(2), -1)
     /// ```
     #[method]
-    pub fn get_terrain_for_point(&mut self, point_id: i32) -> i32 {
+    pub fn get_terrain_for_point(&self, point_id: i32) -> i32 {
         // TODO : TerrainType::DefaultTerrain also convert into -1, so this function cannot separate points that exists and have a default terrain, and those that do not exist.
         // We need a different convention here.
         self.dijkstra
@@ -290,8 +1111,9 @@ impl Interface {
     /// assert_eq(dijkstra_map.remove_point(0), FAILED)
     /// ```
     #[method]
-    pub fn remove_point(&mut self, point_id: i32) -> i64 {
+    pub fn remove_point(&mut self, #[base] base: &Reference, point_id: i32) -> i64 {
         let res = self.dijkstra.remove_point(point_id.into());
+        self.graph_changed(base);
         if res.is_some() {
             OK
         } else {
@@ -311,7 +1133,7 @@ impl Interface {
     /// assert_false(dijkstra_map.has_point(2))
     /// ```
     #[method]
-    pub fn has_point(&mut self, point_id: i32) -> bool {
+    pub fn has_point(&self, point_id: i32) -> bool {
         self.dijkstra.has_point(point_id.into())
     }
 
@@ -329,8 +1151,9 @@ impl Interface {
     /// assert_eq(dijkstra_map.disable_point(1), FAILED)
     /// ```
     #[method]
-    pub fn disable_point(&mut self, point_id: i32) -> i64 {
+    pub fn disable_point(&mut self, #[base] base: &Reference, point_id: i32) -> i64 {
         let res = self.dijkstra.disable_point(point_id.into());
+        self.graph_changed(base);
         result_to_int(res)
     }
 
@@ -350,8 +1173,9 @@ impl Interface {
     /// assert_eq(dijkstra_map.enable_point(1), FAILED)
     /// ```
     #[method]
-    pub fn enable_point(&mut self, point_id: i32) -> i64 {
+    pub fn enable_point(&mut self, #[base] base: &Reference, point_id: i32) -> i64 {
         let res = self.dijkstra.enable_point(point_id.into());
+        self.graph_changed(base);
         result_to_int(res)
     }
 
@@ -369,10 +1193,62 @@ impl Interface {
     /// assert_false(dijkstra_map.is_point_disabled(2)) # not in the map
     /// ```
     #[method]
-    pub fn is_point_disabled(&mut self, point_id: i32) -> bool {
+    pub fn is_point_disabled(&self, point_id: i32) -> bool {
         self.dijkstra.is_point_disabled(point_id.into())
     }
 
+    /// Adds many points at once, in a single native call.
+    ///
+    /// # Parameters
+    ///
+    /// - `point_ids` : IDs of the new points.
+    /// - `terrain_types` (default : `-1` for every point) : terrain type of
+    /// each point, in the same order as `point_ids`.
+    ///
+    /// This is equivalent to calling [add_point](#func-add_point) on each
+    /// point, but much faster for large procedurally generated graphs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [FAILED] without modifying the map if a point already exists
+    /// or appears twice, or if `terrain_types` is not empty and does not
+    /// have the same size as `point_ids`. Else, returns [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// assert_eq(dijkstra_map.add_points(PoolIntArray([0, 1, 2]), PoolIntArray([-1, 4, 4])), OK)
+    /// assert_eq(dijkstra_map.get_terrain_for_point(1), 4)
+    /// assert_eq(dijkstra_map.add_points(PoolIntArray([3, 2])), FAILED)
+    /// assert_false(dijkstra_map.has_point(3))
+    /// ```
+    #[method]
+    pub fn add_points(
+        &mut self,
+        #[base] base: &Reference,
+        point_ids: Int32Array,
+        #[opt] terrain_types: Option<Int32Array>,
+    ) -> i64 {
+        let point_ids = point_ids.read();
+        let terrain_types = terrain_types.unwrap_or_default();
+        let terrain_types = terrain_types.read();
+        if !terrain_types.is_empty() && terrain_types.len() != point_ids.len() {
+            godot_error!("'terrain_types' must be empty or have the same size as 'point_ids'");
+            return FAILED;
+        }
+        let points: Vec<(PointId, TerrainType)> = point_ids
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| {
+                let terrain = terrain_types.get(i).copied().unwrap_or(-1);
+                (PointId(id), terrain.into())
+            })
+            .collect();
+        let res = self.dijkstra.add_points(&points);
+        self.graph_changed(base);
+        result_to_int(res)
+    }
+
     /// Connects the two given points.
     ///
     /// # Parameters
@@ -408,17 +1284,88 @@ impl Interface {
     #[method]
     pub fn connect_points(
         &mut self,
+        #[base] base: &Reference,
         source: i32,
         target: i32,
         #[opt] weight: Option<f32>,
         #[opt] bidirectional: Option<bool>,
     ) -> i64 {
-        result_to_int(self.dijkstra.connect_points(
+        let res = self.dijkstra.connect_points(
             source.into(),
             target.into(),
             weight.map(Weight),
             bidirectional,
-        ))
+        );
+        self.graph_changed(base);
+        result_to_int(res)
+    }
+
+    /// Adds many connections at once, in a single native call.
+    ///
+    /// # Parameters
+    ///
+    /// - `sources` : source point of each connection.
+    /// - `targets` : target point of each connection, in the same order as
+    /// `sources`.
+    /// - `weights` (default : `1.0` for every connection) : weight of each
+    /// connection, in the same order as `sources`.
+    /// - `bidirectional` (default : [true]): whether or not the reciprocal
+    /// connections should be made.
+    ///
+    /// This is equivalent to calling [connect_points](#func-connect_points)
+    /// on each connection, but much faster for large procedurally generated
+    /// graphs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [FAILED] without modifying the map if one of the points does
+    /// not exist, if `sources` and `targets` have different sizes, or if
+    /// `weights` is not empty and does not have the same size as `sources`.
+    /// Else, returns [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_points(PoolIntArray([0, 1, 2]))
+    /// assert_eq(dijkstra_map.add_connections(PoolIntArray([0, 1]), PoolIntArray([1, 2]), PoolRealArray([2.5, 1.0])), OK)
+    /// assert_true(dijkstra_map.has_connection(2, 1))
+    /// assert_eq(dijkstra_map.add_connections(PoolIntArray([0]), PoolIntArray([2]), PoolRealArray(), false), OK)
+    /// assert_false(dijkstra_map.has_connection(2, 0))
+    /// assert_eq(dijkstra_map.add_connections(PoolIntArray([0]), PoolIntArray([5])), FAILED)
+    /// ```
+    #[method]
+    pub fn add_connections(
+        &mut self,
+        #[base] base: &Reference,
+        sources: Int32Array,
+        targets: Int32Array,
+        #[opt] weights: Option<Float32Array>,
+        #[opt] bidirectional: Option<bool>,
+    ) -> i64 {
+        let sources = sources.read();
+        let targets = targets.read();
+        let weights = weights.unwrap_or_default();
+        let weights = weights.read();
+        if sources.len() != targets.len() {
+            godot_error!("'sources' and 'targets' must have the same size");
+            return FAILED;
+        }
+        if !weights.is_empty() && weights.len() != sources.len() {
+            godot_error!("'weights' must be empty or have the same size as 'sources'");
+            return FAILED;
+        }
+        let connections: Vec<(PointId, PointId, Weight)> = sources
+            .iter()
+            .zip(targets.iter())
+            .enumerate()
+            .map(|(i, (&source, &target))| {
+                let weight = weights.get(i).copied().unwrap_or(1.0);
+                (PointId(source), PointId(target), Weight(weight))
+            })
+            .collect();
+        let res = self.dijkstra.add_connections(&connections, bidirectional);
+        self.graph_changed(base);
+        result_to_int(res)
     }
 
     /// Remove a connection between the two given points.
@@ -450,14 +1397,16 @@ impl Interface {
     #[method]
     pub fn remove_connection(
         &mut self,
+        #[base] base: &Reference,
         source: i32,
         target: i32,
         #[opt] bidirectional: Option<bool>,
     ) -> i64 {
-        result_to_int(
-            self.dijkstra
-                .remove_connection(source.into(), target.into(), bidirectional),
-        )
+        let res = self
+            .dijkstra
+            .remove_connection(source.into(), target.into(), bidirectional);
+        self.graph_changed(base);
+        result_to_int(res)
     }
 
     /// Returns [true] if there is a connection from `source` to
@@ -474,10 +1423,191 @@ impl Interface {
     /// assert_false(dijkstra_map.has_connection(0, 2))
     /// ```
     #[method]
-    pub fn has_connection(&mut self, source: i32, target: i32) -> bool {
+    pub fn has_connection(&self, source: i32, target: i32) -> bool {
         self.dijkstra.has_connection(source.into(), target.into())
     }
 
+    /// Returns the IDs of all the points of the map, sorted in increasing
+    /// order.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(4)
+    /// dijkstra_map.add_point(1)
+    /// assert_eq(Array(dijkstra_map.get_all_point_ids()), [1, 4])
+    /// ```
+    #[method]
+    pub fn get_all_point_ids(&self) -> Int32Array {
+        let mut points: Vec<i32> = self.dijkstra.points().map(i32::from).collect();
+        points.sort_unstable();
+        Int32Array::from_vec(points)
+    }
+
+    /// Returns the disabled points, sorted in increasing order.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_points(PoolIntArray([0, 1, 2]))
+    /// dijkstra_map.disable_point(2)
+    /// dijkstra_map.disable_point(0)
+    /// assert_eq(Array(dijkstra_map.get_disabled_points()), [0, 2])
+    /// ```
+    #[method]
+    pub fn get_disabled_points(&self) -> Int32Array {
+        let mut points: Vec<i32> = self.dijkstra.disabled_points().map(i32::from).collect();
+        points.sort_unstable();
+        Int32Array::from_vec(points)
+    }
+
+    /// Returns the connections from `point_id`, as a [Dictionary] where keys
+    /// are the targets and values the weights.
+    ///
+    /// The dictionary is empty if the point does not exist.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_points(PoolIntArray([0, 1, 2]))
+    /// dijkstra_map.connect_points(0, 1, 2.0)
+    /// dijkstra_map.connect_points(0, 2, 1.0, false)
+    /// assert_eq(dijkstra_map.get_point_connections(0), {1: 2.0, 2: 1.0})
+    /// ```
+    #[method]
+    pub fn get_point_connections(&self, point_id: i32) -> Dictionary {
+        let dict = Dictionary::new();
+        for (target, weight) in self.dijkstra.connections_of(PointId(point_id)) {
+            dict.insert(i32::from(target), weight.0);
+        }
+        dict.into_shared()
+    }
+
+    /// Returns the connections to `point_id`, as a [Dictionary] where keys
+    /// are the sources and values the weights.
+    ///
+    /// The dictionary is empty if the point does not exist.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_points(PoolIntArray([0, 1, 2]))
+    /// dijkstra_map.connect_points(0, 1, 2.0)
+    /// dijkstra_map.connect_points(2, 1, 1.0, false)
+    /// assert_eq(dijkstra_map.get_point_incoming_connections(1), {0: 2.0, 2: 1.0})
+    /// ```
+    #[method]
+    pub fn get_point_incoming_connections(&self, point_id: i32) -> Dictionary {
+        let dict = Dictionary::new();
+        for (source, weight) in self.dijkstra.incoming_of(PointId(point_id)) {
+            dict.insert(i32::from(source), weight.0);
+        }
+        dict.into_shared()
+    }
+
+    /// Returns every connection of the map, sorted by source then target.
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] with the following keys :
+    /// - `"sources"` : [PoolIntArray] of the source of each connection.
+    /// - `"targets"` : [PoolIntArray] of the target of each connection.
+    /// - `"weights"` : [PoolRealArray] of the weight of each connection.
+    ///
+    /// Bidirectional connections appear once in each direction.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_points(PoolIntArray([0, 1, 2]))
+    /// dijkstra_map.connect_points(0, 1, 2.0)
+    /// dijkstra_map.connect_points(2, 1, 1.0, false)
+    /// var connections = dijkstra_map.get_all_connections()
+    /// assert_eq(Array(connections["sources"]), [0, 1, 2])
+    /// assert_eq(Array(connections["targets"]), [1, 0, 1])
+    /// assert_eq(Array(connections["weights"]), [2.0, 2.0, 1.0])
+    /// ```
+    #[method]
+    pub fn get_all_connections(&self) -> Dictionary {
+        let mut connections: Vec<(PointId, PointId, Weight)> = self
+            .dijkstra
+            .points()
+            .flat_map(|source| {
+                self.dijkstra
+                    .connections_of(source)
+                    .map(move |(target, weight)| (source, target, weight))
+            })
+            .collect();
+        connections.sort_unstable_by_key(|&(source, target, _)| (source, target));
+        let dict = Dictionary::new();
+        dict.insert(
+            "sources",
+            Int32Array::from_vec(connections.iter().map(|c| c.0.into()).collect()),
+        );
+        dict.insert(
+            "targets",
+            Int32Array::from_vec(connections.iter().map(|c| c.1.into()).collect()),
+        );
+        dict.insert(
+            "weights",
+            Float32Array::from_vec(connections.iter().map(|c| (c.2).0).collect()),
+        );
+        dict.into_shared()
+    }
+
+    /// Returns the graph in the DOT language of Graphviz, to inspect it
+    /// visually.
+    ///
+    /// Each point is a node labelled with its ID and terrain, and each
+    /// connection a directed edge labelled with its weight. Disabled points
+    /// are dashed.
+    ///
+    /// # Parameters
+    ///
+    /// - `include_paths` (default : `false`) : if `true`, the nodes reached by
+    ///   the last [recalculate](#func-recalculate) also have their cost and
+    ///   direction, and the edges along the shortest paths are bold.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_points(PoolIntArray([0, 1]))
+    /// dijkstra_map.connect_points(0, 1, 1.5, false)
+    /// var dot = dijkstra_map.to_dot()
+    /// assert_true(dot.begins_with("digraph dijkstra_map {"))
+    /// assert_true("0 -> 1 [label=\"1.5\"];" in dot)
+    /// ```
+    #[method]
+    pub fn to_dot(&self, #[opt] include_paths: Option<bool>) -> String {
+        self.dijkstra.to_dot(include_paths.unwrap_or(false))
+    }
+
+    /// Returns the graph in the GraphML format, read by graph editors such
+    /// as yEd or Gephi.
+    ///
+    /// Nodes are the point IDs prefixed by `n`, with the `terrain` and
+    /// `disabled` data. Edges have the `weight` data.
+    ///
+    /// # Parameters
+    ///
+    /// - `include_paths` (default : `false`) : if `true`, the nodes reached by
+    ///   the last [recalculate](#func-recalculate) also have their `cost`
+    ///   and `direction` data.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_points(PoolIntArray([0, 1]))
+    /// dijkstra_map.connect_points(0, 1, 1.5, false)
+    /// dijkstra_map.recalculate(0)
+    /// var graphml = dijkstra_map.to_graphml(true)
+    /// assert_true('<data key="cost">1.5</data>' in graphml)
+    /// ```
+    #[method]
+    pub fn to_graphml(&self, #[opt] include_paths: Option<bool>) -> String {
+        self.dijkstra.to_graphml(include_paths.unwrap_or(false))
+    }
+
     /// Given a point, returns the id of the next point along the
     /// shortest path toward the target.
     ///
@@ -499,7 +1629,7 @@ impl Interface {
     /// assert_eq(dijkstra_map.get_direction_at_point(2), -1)
     /// ```
     #[method]
-    pub fn get_direction_at_point(&mut self, point_id: i32) -> i32 {
+    pub fn get_direction_at_point(&self, point_id: i32) -> i32 {
         self.dijkstra
             .get_direction_at_point(point_id.into())
             .unwrap_or(PointId(-1))
@@ -524,7 +1654,7 @@ impl Interface {
     /// assert_eq(dijkstra_map.get_cost_at_point(2), INF)
     /// ```
     #[method]
-    pub fn get_cost_at_point(&mut self, point_id: i32) -> f32 {
+    pub fn get_cost_at_point(&self, point_id: i32) -> f32 {
         self.dijkstra.get_cost_at_point(point_id.into()).into()
     }
 
@@ -592,303 +1722,331 @@ impl Interface {
     #[method]
     pub fn recalculate(
         &mut self,
+        #[base] base: &Reference,
         origin: gdnative::core_types::Variant,
         #[opt] optional_params: Option<Dictionary>,
     ) -> i64 {
-        use gdnative::core_types::VariantType;
-
-        const TERRAIN_WEIGHT: &str = "terrain_weights";
-        const TERMINATION_POINTS: &str = "termination_points";
-        const INPUT_IS_DESTINATION: &str = "input_is_destination";
-        const MAXIMUM_COST: &str = "maximum_cost";
-        const INITIAL_COSTS: &str = "initial_costs";
-        const VALID_KEYS: [&str; 5] = [
-            TERRAIN_WEIGHT,
-            TERMINATION_POINTS,
-            INPUT_IS_DESTINATION,
-            MAXIMUM_COST,
-            INITIAL_COSTS,
-        ];
-
-        fn display_type(t: VariantType) -> &'static str {
-            match t {
-                VariantType::Nil => "nil",
-                VariantType::Bool => "bool",
-                VariantType::I64 => "integer",
-                VariantType::F64 => "float",
-                VariantType::GodotString => "string",
-                VariantType::Vector2 => "Vector2",
-                VariantType::Rect2 => "Rect2",
-                VariantType::Vector3 => "Vector3",
-                VariantType::Transform2D => "Transform2D",
-                VariantType::Plane => "Plane",
-                VariantType::Quat => "Quat",
-                VariantType::Aabb => "Aabb",
-                VariantType::Basis => "Basis",
-                VariantType::Transform => "Transform",
-                VariantType::Color => "Color",
-                VariantType::NodePath => "NodePath",
-                VariantType::Rid => "Rid",
-                VariantType::Object => "Object",
-                VariantType::Dictionary => "Dictionary",
-                VariantType::VariantArray => "array",
-                VariantType::ByteArray => "array of bytes",
-                VariantType::Int32Array => "array of integers",
-                VariantType::Float32Array => "array of floats",
-                VariantType::StringArray => "array of strings",
-                VariantType::Vector2Array => "array of Vector2",
-                VariantType::Vector3Array => "array of Vector3",
-                VariantType::ColorArray => "array of Color",
-            }
-        }
-
-        /// Helper function for type warnings
-        ///
-        /// Ensure the style of warning reporting is consistent.
-        fn type_warning(object: &str, expected: VariantType, got: VariantType, line: u32) {
-            godot_warn!(
-                "[{}:{}] {} has incorrect type : expected {}, got {}",
-                file!(),
-                line,
-                object,
-                display_type(expected),
-                display_type(got)
-            );
-        }
-
-        let optional_params = optional_params.unwrap_or_default();
-
-        // verify keys makes sense
-        for k in optional_params.keys().into_iter() {
-            let string: String = k.to_string();
-            if !VALID_KEYS.contains(&string.as_str()) {
-                godot_error!("Invalid Key `{}` in parameter", string);
-                return FAILED;
+        match parse_recalculate_args(origin, optional_params) {
+            Some(args) => {
+                self.dijkstra.recalculate(
+                    &args.origins,
+                    args.read,
+                    args.max_cost,
+                    args.initial_costs,
+                    args.terrain_weights,
+                    args.termination_points,
+                );
+                emit_deferred(base, "paths_changed", &[]);
+                OK
             }
+            None => FAILED,
         }
+    }
 
-        // get origin points
-        let mut res_origins = Vec::<PointId>::new();
-        match origin.get_type() {
-            gdnative::core_types::VariantType::I64 => {
-                res_origins.push((origin.to::<i64>().unwrap() as i32).into())
-            }
-            gdnative::core_types::VariantType::Int32Array => {
-                res_origins = origin
-                    .to::<gdnative::core_types::Int32Array>()
-                    .unwrap()
-                    .read()
-                    .iter()
-                    .map(|&x| x.into())
-                    .collect();
-            }
-            gdnative::core_types::VariantType::VariantArray => {
-                for i in origin
-                    .to::<gdnative::core_types::VariantArray>()
-                    .unwrap()
-                    .iter()
-                {
-                    match i.to::<i64>() {
-                        Some(intval) => res_origins.push(PointId(intval as i32)),
-                        None => type_warning(
-                            "element of 'origin'",
-                            VariantType::I64,
-                            i.get_type(),
-                            line!(),
-                        ),
-                    }
-                }
-            }
-            _ => {
-                godot_error!("Invalid argument type : Expected int or Array of ints");
-                return FAILED;
+    /// Computes shortest paths like [recalculate](#func-recalculate), but
+    /// returns them instead of storing them in the map.
+    ///
+    /// This does not modify the map, so it can be called from several
+    /// [Thread]s at once on the same `DijkstraMap`, for example to compute
+    /// paths for many units in parallel.
+    ///
+    /// Takes the same arguments as [recalculate](#func-recalculate), and
+    /// returns a [Dictionary] with the keys :
+    /// - `"cost_map"` : same as [get_cost_map](#func-get_cost_map).
+    /// - `"direction_map"` : same as [get_direction_map](#func-get_direction_map).
+    ///
+    /// # Errors
+    ///
+    /// Returns an empty [Dictionary] if the arguments are invalid (see
+    /// [recalculate](#func-recalculate)).
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.add_point(2)
+    /// dijkstra_map.connect_points(0, 1)
+    /// var paths = dijkstra_map.compute_paths(0)
+    /// assert_eq(paths["cost_map"][1], 1.0)
+    /// assert_eq(paths["direction_map"][1], 0)
+    /// assert_false(paths["cost_map"].has(2))
+    /// # the map itself is unchanged
+    /// assert_eq(dijkstra_map.get_cost_at_point(1), INF)
+    /// ```
+    #[method]
+    pub fn compute_paths(
+        &self,
+        origin: gdnative::core_types::Variant,
+        #[opt] optional_params: Option<Dictionary>,
+    ) -> Dictionary {
+        let dict = Dictionary::new();
+        if let Some(args) = parse_recalculate_args(origin, optional_params) {
+            let results = self.dijkstra.compute_paths(
+                &args.origins,
+                args.read,
+                args.max_cost,
+                args.initial_costs,
+                args.terrain_weights,
+                args.termination_points,
+            );
+            let cost_map = Dictionary::new();
+            let direction_map = Dictionary::new();
+            for (&point, info) in results.get_direction_and_cost_map().iter() {
+                let point: i32 = point.into();
+                cost_map.insert(point, f32::from(info.cost));
+                direction_map.insert(point, i32::from(info.direction));
             }
-        };
+            dict.insert("cost_map", cost_map);
+            dict.insert("direction_map", direction_map);
+        }
+        dict.into_shared()
+    }
 
-        // ===================
-        // Optional parameters
-        // ===================
-        let read: Option<Read> = {
-            // we need to check that the parameter exists first, because
-            // `optional_params.get` will create a `Nil` entry if it does not.
-            if optional_params.contains(INPUT_IS_DESTINATION) {
-                let value = optional_params.get(INPUT_IS_DESTINATION).unwrap();
-                match value.to::<bool>() {
-                    Some(b) => Some(if b {
-                        Read::InputIsDestination
-                    } else {
-                        Read::InputIsOrigin
-                    }),
-                    None => {
-                        type_warning(
-                            "'input_is_destination' key",
-                            VariantType::Bool,
-                            value.get_type(),
-                            line!(),
-                        );
-                        None
-                    }
-                }
-            } else {
-                None
-            }
+    /// Starts computing paths like [recalculate](#func-recalculate), but in
+    /// a background thread, so that the main thread is not blocked on large
+    /// maps.
+    ///
+    /// The paths are computed on a snapshot of the graph, taken when this
+    /// method is called : like [fork](#func-fork), this takes constant time,
+    /// and the graph can be modified while the job runs. When they are
+    /// ready, they replace the paths of the map, and the `recalculated`
+    /// signal is emitted with the returned job ID.
+    /// If the graph was modified in the meantime, [is_stale](#func-is_stale)
+    /// returns [true].
+    ///
+    /// Only one job runs at a time : starting a job cancels the previous one,
+    /// whose paths would be replaced anyway. The paths of a job are also
+    /// dropped if [recalculate](#func-recalculate) was called on a newer
+    /// revision of the graph while it was running.
+    ///
+    /// Takes the same arguments as [recalculate](#func-recalculate).
+    ///
+    /// # Errors
+    ///
+    /// Returns `-1` if the arguments are invalid (see
+    /// [recalculate](#func-recalculate)), else the ID of the job.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.connect_points(0, 1)
+    /// var job_id = dijkstra_map.recalculate_async(0)
+    /// assert_ne(job_id, -1)
+    /// var finished_job_id = yield(dijkstra_map, "recalculated")
+    /// assert_eq(finished_job_id, job_id)
+    /// assert_eq(dijkstra_map.get_cost_at_point(1), 1.0)
+    /// ```
+    #[method]
+    pub fn recalculate_async(
+        &mut self,
+        #[base] base: &Reference,
+        origin: gdnative::core_types::Variant,
+        #[opt] optional_params: Option<Dictionary>,
+    ) -> i64 {
+        let args = match parse_recalculate_args(origin, optional_params) {
+            Some(args) => args,
+            None => return -1,
         };
-
-        let max_cost: Option<Cost> = {
-            if optional_params.contains(MAXIMUM_COST) {
-                let value = optional_params.get(MAXIMUM_COST).unwrap();
-                match value.to::<f64>() {
-                    Some(f) => Some(Cost(f as f32)),
-                    None => {
-                        type_warning(
-                            "'max_cost' key",
-                            VariantType::F64,
-                            value.get_type(),
-                            line!(),
-                        );
-                        None
+        self.cancel_job();
+        let job_id = self.next_job_id;
+        self.next_job_id += 1;
+        let snapshot = self.dijkstra.fork();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        // the thread only keeps the ID of the map, so that it is never the
+        // one releasing the last reference to it
+        let instance_id = base.get_instance_id();
+        let thread = {
+            let cancelled = cancelled.clone();
+            std::thread::spawn(move || {
+                let mut search = snapshot.start_search(
+                    &args.origins,
+                    args.read,
+                    args.max_cost,
+                    args.initial_costs,
+                    args.terrain_weights,
+                    args.termination_points,
+                );
+                let results = loop {
+                    if cancelled.load(Ordering::Relaxed) {
+                        break None;
                     }
-                }
-            } else {
-                None
-            }
-        };
-
-        let initial_costs: Vec<Cost> = {
-            if optional_params.contains(INITIAL_COSTS) {
-                let mut initial_costs = Vec::<Cost>::new();
-                let value = optional_params.get(INITIAL_COSTS).unwrap();
-                match value.get_type() {
-                    gdnative::core_types::VariantType::Float32Array => {
-                        for f in value
-                            .to::<gdnative::core_types::Float32Array>()
-                            .unwrap()
-                            .read()
-                            .iter()
-                        {
-                            initial_costs.push(Cost(*f))
-                        }
+                    if search.step(&snapshot, Some(JOB_CHUNK_SIZE)) {
+                        break Some(search.into_results());
                     }
-                    gdnative::core_types::VariantType::VariantArray => {
-                        for f in value
-                            .to::<gdnative::core_types::VariantArray>()
-                            .unwrap()
-                            .iter()
-                        {
-                            initial_costs.push(match f.to::<f64>() {
-                                Some(fval) => Cost(fval as f32),
-                                None => {
-                                    type_warning(
-                                        "element of 'initial_costs'",
-                                        VariantType::F64,
-                                        f.get_type(),
-                                        line!(),
-                                    );
-                                    Cost(0.0)
-                                }
-                            })
-                        }
+                };
+                // results are swapped in on the main thread, unless the map
+                // was freed in the meantime
+                unsafe {
+                    if let Some(base) = Reference::try_from_instance_id(instance_id) {
+                        base.call_deferred("_finish_recalculation", &[job_id.to_variant()]);
                     }
-                    incorrect_type => type_warning(
-                        "'initial_costs' key",
-                        VariantType::Float32Array,
-                        incorrect_type,
-                        line!(),
-                    ),
                 }
-                initial_costs
-            } else {
-                Vec::new()
-            }
+                results
+            })
         };
+        self.job = Some(Job {
+            id: job_id,
+            cancelled,
+            thread,
+        });
+        job_id
+    }
 
-        let mut terrain_weights = FnvHashMap::<TerrainType, Weight>::default();
-        if optional_params.contains(TERRAIN_WEIGHT) {
-            let value = optional_params.get(TERRAIN_WEIGHT).unwrap();
-            if let Some(dict) = value.to::<gdnative::core_types::Dictionary>() {
-                for key in dict.keys() {
-                    if let Some(id) = key.to::<i64>() {
-                        terrain_weights.insert(
-                            TerrainType::from(id as i32),
-                            Weight(dict.get(key).unwrap().to::<f64>().unwrap_or(1.0) as f32),
-                        );
-                    } else {
-                        type_warning(
-                            "key in 'terrain_weights'",
-                            VariantType::I64,
-                            key.get_type(),
-                            line!(),
-                        );
-                    }
-                }
-            } else {
-                type_warning(
-                    "'terrain_weights' key",
-                    VariantType::Int32Array,
-                    value.get_type(),
-                    line!(),
-                );
+    /// Cancels a job started by [recalculate_async](#func-recalculate_async).
+    ///
+    /// Its search stops shortly after, its paths will not replace the ones
+    /// of the map, and the `recalculated` signal will not be emitted for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [FAILED] if this job is not running (it finished, was
+    /// cancelled or replaced by a newer job), else [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// var job_id = dijkstra_map.recalculate_async(0)
+    /// assert_eq(dijkstra_map.cancel_recalculation(job_id), OK)
+    /// assert_eq(dijkstra_map.cancel_recalculation(job_id), FAILED)
+    /// # starting a job cancels the previous one
+    /// var first_job_id = dijkstra_map.recalculate_async(0)
+    /// var second_job_id = dijkstra_map.recalculate_async(0)
+    /// assert_eq(dijkstra_map.cancel_recalculation(first_job_id), FAILED)
+    /// assert_eq(dijkstra_map.cancel_recalculation(second_job_id), OK)
+    /// ```
+    #[method]
+    pub fn cancel_recalculation(&mut self, job_id: i64) -> i64 {
+        match self.job.as_ref() {
+            Some(job) if job.id == job_id => {
+                self.cancel_job();
+                OK
             }
+            _ => FAILED,
         }
+    }
 
-        if terrain_weights.is_empty() {
-            godot_warn!("no terrain weights specified : all terrains will have infinite cost !")
-        }
+    /// Returns [true] if a job started by
+    /// [recalculate_async](#func-recalculate_async) is still running.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// assert_false(dijkstra_map.is_recalculating())
+    /// dijkstra_map.recalculate_async(0)
+    /// assert_true(dijkstra_map.is_recalculating())
+    /// yield(dijkstra_map, "recalculated")
+    /// assert_false(dijkstra_map.is_recalculating())
+    /// ```
+    #[method]
+    pub fn is_recalculating(&self) -> bool {
+        self.job.is_some()
+    }
 
-        let termination_points = if optional_params.contains(TERMINATION_POINTS) {
-            let value = optional_params.get(TERMINATION_POINTS).unwrap();
-            match value.get_type() {
-                gdnative::core_types::VariantType::I64 => {
-                    std::iter::once(PointId(value.to::<i64>().unwrap() as i32)).collect()
-                }
-                gdnative::core_types::VariantType::Int32Array => value
-                    .to::<gdnative::core_types::Int32Array>()
-                    .unwrap()
-                    .read()
-                    .iter()
-                    .map(|&x| PointId::from(x))
-                    .collect(),
-                gdnative::core_types::VariantType::VariantArray => value
-                    .to::<gdnative::core_types::VariantArray>()
-                    .unwrap()
-                    .iter()
-                    .filter_map(|i| {
-                        let int = i.to::<i64>();
-                        if int.is_none() {
-                            type_warning(
-                                "value in 'termination_points'",
-                                VariantType::I64,
-                                i.get_type(),
-                                line!(),
-                            );
-                        }
-                        int
-                    })
-                    .map(|ival| PointId(ival as i32))
-                    .collect(),
-                incorrect_type => {
-                    type_warning(
-                        "'termination_points' key",
-                        VariantType::Int32Array,
-                        incorrect_type,
-                        line!(),
-                    );
-                    FnvHashSet::<PointId>::default()
-                }
+    /// Internal : called on the main thread when a job started by
+    /// [recalculate_async](#func-recalculate_async) is done.
+    #[method]
+    fn _finish_recalculation(&mut self, #[base] base: &Reference, job_id: i64) {
+        // cancelled and replaced jobs were already removed
+        let job = match self.job.take() {
+            Some(job) if job.id == job_id => job,
+            other => {
+                self.job = other;
+                return;
             }
-        } else {
-            FnvHashSet::default()
         };
+        if let Ok(Some(results)) = job.thread.join() {
+            // `recalculate` may have computed paths on a newer graph since
+            if results.revision() >= self.dijkstra.get_path_results().revision() {
+                self.dijkstra.set_path_results(results);
+                emit_deferred(base, "paths_changed", &[]);
+                emit_deferred(base, "recalculated", &[job_id.to_variant()]);
+            }
+        }
+    }
 
-        self.dijkstra.recalculate(
-            &res_origins,
-            read,
-            max_cost,
-            initial_costs,
-            terrain_weights,
-            termination_points,
-        );
-        OK
+    /// Starts a time-sliced [recalculate](#func-recalculate), with the same
+    /// arguments.
+    ///
+    /// The paths are computed a few points at a time by
+    /// [step_recalculation](#func-step_recalculation), for example once per
+    /// frame. This avoids hitches on large maps, without using threads. In
+    /// the meantime, the `get_***` methods return the paths computed so far.
+    ///
+    /// # Errors
+    ///
+    /// Returns [FAILED] if the arguments are invalid (see
+    /// [recalculate](#func-recalculate)), else [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.add_point(2)
+    /// dijkstra_map.connect_points(0, 1)
+    /// dijkstra_map.connect_points(1, 2)
+    /// assert_eq(dijkstra_map.start_recalculation(0), OK)
+    /// assert_false(dijkstra_map.step_recalculation(2))
+    /// assert_eq(dijkstra_map.get_cost_at_point(2), 2.0)
+    /// assert_true(dijkstra_map.step_recalculation(2))
+    /// ```
+    #[method]
+    pub fn start_recalculation(
+        &mut self,
+        #[base] base: &Reference,
+        origin: gdnative::core_types::Variant,
+        #[opt] optional_params: Option<Dictionary>,
+    ) -> i64 {
+        match parse_recalculate_args(origin, optional_params) {
+            Some(args) => {
+                self.dijkstra.start_recalculation(
+                    &args.origins,
+                    args.read,
+                    args.max_cost,
+                    args.initial_costs,
+                    args.terrain_weights,
+                    args.termination_points,
+                );
+                emit_deferred(base, "paths_changed", &[]);
+                OK
+            }
+            None => FAILED,
+        }
+    }
+
+    /// Continues the recalculation started by
+    /// [start_recalculation](#func-start_recalculation), processing at most
+    /// `max_expansions` points.
+    ///
+    /// If the graph was modified since the recalculation started, it is
+    /// restarted.
+    ///
+    /// Returns [true] once the recalculation is finished, or if there is none
+    /// in progress.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.connect_points(0, 1)
+    /// dijkstra_map.start_recalculation(0)
+    /// while not dijkstra_map.step_recalculation(1):
+    ///     pass
+    /// assert_eq(dijkstra_map.get_cost_at_point(1), 1.0)
+    /// ```
+    #[method]
+    pub fn step_recalculation(&mut self, #[base] base: &Reference, max_expansions: i64) -> bool {
+        let finished = self
+            .dijkstra
+            .step_recalculation(Some(max_expansions.max(0) as usize));
+        emit_deferred(base, "paths_changed", &[]);
+        finished
     }
 
     /// For each point in the given array, returns the id of the next
@@ -908,7 +2066,7 @@ impl Interface {
     /// assert_eq(Array(dijkstra_map.get_direction_at_points(PoolIntArray([0, 1, 2]))), [0, 0, -1])
     /// ```
     #[method]
-    pub fn get_direction_at_points(&mut self, points: Int32Array) -> Int32Array {
+    pub fn get_direction_at_points(&self, points: Int32Array) -> Int32Array {
         Int32Array::from_vec(
             points
                 .read()
@@ -941,7 +2099,7 @@ impl Interface {
     /// ```
     #[method]
     pub fn get_cost_at_points(
-        &mut self,
+        &self,
         points: gdnative::core_types::Int32Array,
     ) -> gdnative::core_types::Float32Array {
         Float32Array::from_vec(
@@ -977,7 +2135,7 @@ impl Interface {
     ///     assert_eq(computed_cost_map[id], cost_map[id])
     /// ```
     #[method]
-    pub fn get_cost_map(&mut self) -> Dictionary {
+    pub fn get_cost_map(&self) -> Dictionary {
         let dict = Dictionary::new();
         for (&point, info) in self.dijkstra.get_direction_and_cost_map().iter() {
             let point: i32 = point.into();
@@ -1010,7 +2168,7 @@ impl Interface {
     ///     assert_eq(computed_direction_map[id], direction_map[id])
     /// ```
     #[method]
-    pub fn get_direction_map(&mut self) -> Dictionary {
+    pub fn get_direction_map(&self) -> Dictionary {
         let dict = Dictionary::new();
         for (&point, info) in self.dijkstra.get_direction_and_cost_map().iter() {
             let point: i32 = point.into();
@@ -1037,7 +2195,7 @@ impl Interface {
     /// ```
     #[method]
     pub fn get_all_points_with_cost_between(
-        &mut self,
+        &self,
         min_cost: f32,
         max_cost: f32,
     ) -> gdnative::core_types::Int32Array {
@@ -1061,10 +2219,7 @@ impl Interface {
     ///
     /// [array]: gdnative::core_types::Int32Array
     #[method]
-    pub fn get_shortest_path_from_point(
-        &mut self,
-        point_id: i32,
-    ) -> gdnative::core_types::Int32Array {
+    pub fn get_shortest_path_from_point(&self, point_id: i32) -> gdnative::core_types::Int32Array {
         let res = self
             .dijkstra
             .get_shortest_path_from_point(point_id.into())
@@ -1074,87 +2229,508 @@ impl Interface {
         Int32Array::from_vec(res)
     }
 
-    /// Adds a square grid of connected points.
+    /// Sets the position of a point in the world, as a [Vector2] or a
+    /// [Vector3].
     ///
-    /// # Parameters
+    /// Positions are only used to convert points to positions and back (see
+    /// [get_point_at_position](#func-get_point_at_position) and
+    /// [get_shortest_path_positions_from_point](#func-get_shortest_path_positions_from_point)),
+    /// they do not change the paths. [add_square_grid](#func-add_square_grid)
+    /// and [add_hexagonal_grid](#func-add_hexagonal_grid) set the position of
+    /// each new point to its coordinates in the grid.
     ///
-    /// - `bounds` : Dimensions of the grid. At the moment, only
-    /// [Rect2] is supported.
-    /// - `terrain_type` (default : `-1`) : Terrain to use for all
-    /// points of the grid.
-    /// - `orthogonal_cost` (default : `1.0`) : specifies cost of
-    /// orthogonal connections (up, down, right and left). \
-    ///   If `orthogonal_cost` is [INF] or [NAN], orthogonal
-    /// connections are disabled.
-    /// - `diagonal_cost` (default : [INF]) : specifies cost of
-    /// diagonal connections. \
-    ///   If `diagonal_cost` is [INF] or [NAN], diagonal connections
-    /// are disabled.
+    /// Positions are removed along with their point, and are part of the
+    /// undo/redo history.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// This function returns a [Dictionary] where keys are coordinates
-    /// of points ([Vector2]) and values are their corresponding point
-    /// IDs.
+    /// Returns [FAILED] if the point does not exist or `position` is neither
+    /// a [Vector2] nor a [Vector3], else [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// assert_eq(dijkstra_map.set_point_position(0, Vector2(10, 20)), OK)
+    /// assert_eq(dijkstra_map.get_point_position(0), Vector2(10, 20))
+    /// assert_eq(dijkstra_map.set_point_position(1, Vector2(10, 20)), FAILED)
+    /// dijkstra_map.begin_transaction()
+    /// dijkstra_map.set_point_position(0, Vector3(1, 2, 3))
+    /// dijkstra_map.commit_transaction()
+    /// assert_eq(dijkstra_map.get_point_position(0), Vector3(1, 2, 3))
+    /// dijkstra_map.undo()
+    /// assert_eq(dijkstra_map.get_point_position(0), Vector2(10, 20))
+    /// ```
     #[method]
-    pub fn add_square_grid(
-        &mut self,
-        bounds: Variant,
-        #[opt] terrain_type: Option<i32>,
-        #[opt] orthogonal_cost: Option<f32>,
-        #[opt] diagonal_cost: Option<f32>,
-    ) -> Dictionary {
-        let (x_offset, y_offset, width, height) =
-            variant_to_width_and_height(bounds).expect("couldn't use bounds variant");
-        let dict = Dictionary::new();
-        for (&k, &v) in self
-            .dijkstra
-            .add_square_grid(
-                width,
-                height,
-                Some((x_offset, y_offset).into()),
-                terrain_type.unwrap_or(-1).into(),
-                orthogonal_cost.map(Weight),
-                diagonal_cost.map(Weight),
-            )
-            .iter()
-        {
-            dict.insert(
-                Vector2::new(k.x as f32, k.y as f32).to_variant(),
-                i32::from(v),
-            );
+    pub fn set_point_position(&mut self, point_id: i32, position: Variant) -> i64 {
+        let point = PointId(point_id);
+        if !self.dijkstra.has_point(point) {
+            return FAILED;
+        }
+        match variant_to_position(&position) {
+            Some((position, true)) => {
+                result_to_int(self.dijkstra.set_point_position_3d(point, position))
+            }
+            Some((position, false)) => {
+                result_to_int(self.dijkstra.set_point_position(point, Some(position)))
+            }
+            None => {
+                godot_error!(
+                    "Invalid position type : expected Vector2 or Vector3, got {}",
+                    display_type(position.get_type())
+                );
+                FAILED
+            }
         }
-        dict.into_shared()
     }
 
-    /// Adds a hexagonal grid of connected points.
+    /// Returns the position of a point, or `null` if it has none.
     ///
-    /// # Parameters
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 2, 2))
+    /// assert_eq(dijkstra_map.get_point_position(grid[Vector2(1, 0)]), Vector2(1, 0))
+    /// dijkstra_map.add_point(10)
+    /// assert_eq(dijkstra_map.get_point_position(10), null)
+    /// ```
+    #[method]
+    pub fn get_point_position(&self, point_id: i32) -> Variant {
+        let point = PointId(point_id);
+        match self.dijkstra.get_point_position(point) {
+            Some(position) if self.dijkstra.is_point_position_3d(point) => {
+                position_to_vector3(position).to_variant()
+            }
+            Some(position) => position_to_vector2(position).to_variant(),
+            None => Variant::nil(),
+        }
+    }
+
+    /// Returns the point at the given position ([Vector2] or [Vector3]), or
+    /// `-1` if there is none.
     ///
-    /// - `bounds` : Dimensions of the grid.
-    /// - `terrain_type` (default : `-1`) : specifies terrain to be used.
-    /// - `weight` (default : `1.0`) : specifies cost of connections.
+    /// A [Vector2] is the same position as a [Vector3] with a `z` coordinate
+    /// of `0`. If several points share the same position, the one with the
+    /// smallest ID is returned.
     ///
-    /// # Returns
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 2, 2))
+    /// assert_eq(dijkstra_map.get_point_at_position(Vector2(1, 1)), grid[Vector2(1, 1)])
+    /// assert_eq(dijkstra_map.get_point_at_position(Vector2(5, 5)), -1)
+    /// ```
+    #[method]
+    pub fn get_point_at_position(&self, position: Variant) -> i32 {
+        variant_to_position(&position)
+            .and_then(|(position, _)| self.dijkstra.get_point_at_position(position))
+            .map_or(-1, i32::from)
+    }
+
+    /// Returns the point closest to `position` ([Vector2] or [Vector3]), or
+    /// `-1` if no point has a position.
     ///
-    /// This function returns a [Dictionary] where keys are
-    /// coordinates of points ([Vector2]) and values are their
-    /// corresponding point IDs.
+    /// If `only_enabled` is [true], disabled points are ignored. This is
+    /// useful to snap a unit or a click onto the graph.
     ///
-    /// # Note
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 10, 10))
+    /// dijkstra_map.disable_point(grid[Vector2(3, 4)])
+    /// assert_eq(dijkstra_map.get_nearest_point(Vector2(3.1, 3.6)), grid[Vector2(3, 4)])
+    /// assert_eq(dijkstra_map.get_nearest_point(Vector2(3.1, 3.6), true), grid[Vector2(3, 3)])
+    /// ```
+    #[method]
+    pub fn get_nearest_point(&self, position: Variant, #[opt] only_enabled: Option<bool>) -> i32 {
+        variant_to_position(&position)
+            .and_then(|(position, _)| {
+                self.dijkstra
+                    .get_nearest_point(position, only_enabled.unwrap_or(false))
+            })
+            .map_or(-1, i32::from)
+    }
+
+    /// Returns the points at a distance of at most `radius` from `position`
+    /// ([Vector2] or [Vector3]), sorted by distance.
     ///
-    /// Hexgrid is in the "pointy" orientation by default (see example
-    /// below).
+    /// If `only_enabled` is [true], disabled points are ignored.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 10, 10))
+    /// var points = dijkstra_map.get_points_within_radius(Vector2(5, 5), 1.0)
+    /// assert_eq(points.size(), 5)
+    /// assert_eq(points[0], grid[Vector2(5, 5)])
+    /// ```
+    #[method]
+    pub fn get_points_within_radius(
+        &self,
+        position: Variant,
+        radius: f32,
+        #[opt] only_enabled: Option<bool>,
+    ) -> Int32Array {
+        let points = match variant_to_position(&position) {
+            Some((position, _)) => self.dijkstra.get_points_within_radius(
+                position,
+                radius,
+                only_enabled.unwrap_or(false),
+            ),
+            None => Vec::new(),
+        };
+        Int32Array::from_vec(points.into_iter().map(i32::from).collect())
+    }
+
+    /// Sets the size of the cells of the spatial index used by
+    /// [get_nearest_point](#func-get_nearest_point) and
+    /// [get_points_within_radius](#func-get_points_within_radius) (`1.0` by
+    /// default).
+    ///
+    /// Queries are fastest when this is about the distance between
+    /// neighbouring points, for example the size of a tile.
+    ///
+    /// # Errors
     ///
-    /// To switch to "flat" orientation, swap `width` and `height`, and
-    /// switch `x` and `y` coordinates of the keys in the return
-    /// `Dictionary`. ([Transform2D] may be convenient there)
+    /// Returns [FAILED] if `cell_size` is not strictly positive, else [OK].
     ///
     /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// assert_eq(dijkstra_map.set_spatial_cell_size(16.0), OK)
+    /// assert_eq(dijkstra_map.set_spatial_cell_size(0.0), FAILED)
+    /// ```
+    #[method]
+    pub fn set_spatial_cell_size(&mut self, cell_size: f32) -> i64 {
+        if cell_size > 0.0 && cell_size.is_finite() {
+            self.dijkstra.set_spatial_cell_size(cell_size);
+            OK
+        } else {
+            FAILED
+        }
+    }
+
+    /// Same as
+    /// [get_shortest_path_from_point](#func-get_shortest_path_from_point),
+    /// but returns the positions of the points of the path.
     ///
-    /// This is what `dijkstra_map.add_hexagonal_grid(Rect2(1, 4, 2, 3), ...)` would produce:
+    /// Points without a position are skipped, and the `z` coordinate of 3D
+    /// positions is dropped (see
+    /// [get_shortest_path_positions_from_point_3d](#func-get_shortest_path_positions_from_point_3d)).
     ///
-    ///```text
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 3, 1))
+    /// dijkstra_map.recalculate(grid[Vector2(2, 0)])
+    /// var path = dijkstra_map.get_shortest_path_positions_from_point(grid[Vector2(0, 0)])
+    /// assert_eq(Array(path), [Vector2(1, 0), Vector2(2, 0)])
+    /// ```
+    #[method]
+    pub fn get_shortest_path_positions_from_point(&self, point_id: i32) -> Vector2Array {
+        let path = self.dijkstra.get_shortest_path_from_point(point_id.into());
+        Vector2Array::from_vec(self.positions_of(path).map(position_to_vector2).collect())
+    }
+
+    /// Same as
+    /// [get_shortest_path_positions_from_point](#func-get_shortest_path_positions_from_point),
+    /// but returns 3D positions. 2D positions have a `z` coordinate of `0`.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.connect_points(0, 1)
+    /// dijkstra_map.set_point_position(1, Vector3(1, 2, 3))
+    /// dijkstra_map.recalculate(1)
+    /// assert_eq(Array(dijkstra_map.get_shortest_path_positions_from_point_3d(0)), [Vector3(1, 2, 3)])
+    /// ```
+    #[method]
+    pub fn get_shortest_path_positions_from_point_3d(&self, point_id: i32) -> Vector3Array {
+        let path = self.dijkstra.get_shortest_path_from_point(point_id.into());
+        Vector3Array::from_vec(self.positions_of(path).map(position_to_vector3).collect())
+    }
+
+    /// Returns the positions of all the points reached by the last
+    /// recalculation, sorted by cost.
+    ///
+    /// Points without a position are skipped, and the `z` coordinate of 3D
+    /// positions is dropped (see
+    /// [get_reachable_positions_3d](#func-get_reachable_positions_3d)).
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 3, 1))
+    /// dijkstra_map.disable_point(grid[Vector2(1, 0)])
+    /// dijkstra_map.recalculate(grid[Vector2(0, 0)])
+    /// assert_eq(Array(dijkstra_map.get_reachable_positions()), [Vector2(0, 0)])
+    /// ```
+    #[method]
+    pub fn get_reachable_positions(&self) -> Vector2Array {
+        let points = self.reachable_points().iter().copied();
+        Vector2Array::from_vec(self.positions_of(points).map(position_to_vector2).collect())
+    }
+
+    /// Same as [get_reachable_positions](#func-get_reachable_positions), but
+    /// returns 3D positions. 2D positions have a `z` coordinate of `0`.
+    #[method]
+    pub fn get_reachable_positions_3d(&self) -> Vector3Array {
+        let points = self.reachable_points().iter().copied();
+        Vector3Array::from_vec(self.positions_of(points).map(position_to_vector3).collect())
+    }
+
+    /// Same as
+    /// [get_all_points_with_cost_between](#func-get_all_points_with_cost_between),
+    /// but returns the positions of the points, sorted by cost.
+    ///
+    /// Points without a position are skipped, and the `z` coordinate of 3D
+    /// positions is dropped (see
+    /// [get_all_positions_with_cost_between_3d](#func-get_all_positions_with_cost_between_3d)).
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 4, 1))
+    /// dijkstra_map.recalculate(grid[Vector2(0, 0)])
+    /// var positions = dijkstra_map.get_all_positions_with_cost_between(0.5, 2.5)
+    /// assert_eq(Array(positions), [Vector2(1, 0), Vector2(2, 0)])
+    /// ```
+    #[method]
+    pub fn get_all_positions_with_cost_between(
+        &self,
+        min_cost: f32,
+        max_cost: f32,
+    ) -> Vector2Array {
+        let points = self
+            .dijkstra
+            .get_all_points_with_cost_between(min_cost.into(), max_cost.into())
+            .iter()
+            .copied();
+        Vector2Array::from_vec(self.positions_of(points).map(position_to_vector2).collect())
+    }
+
+    /// Same as
+    /// [get_all_positions_with_cost_between](#func-get_all_positions_with_cost_between),
+    /// but returns 3D positions. 2D positions have a `z` coordinate of `0`.
+    #[method]
+    pub fn get_all_positions_with_cost_between_3d(
+        &self,
+        min_cost: f32,
+        max_cost: f32,
+    ) -> Vector3Array {
+        let points = self
+            .dijkstra
+            .get_all_points_with_cost_between(min_cost.into(), max_cost.into())
+            .iter()
+            .copied();
+        Vector3Array::from_vec(self.positions_of(points).map(position_to_vector3).collect())
+    }
+
+    /// Returns the shortest path from `point_id` on a square grid, smoothed
+    /// by removing the points that can be skipped by walking in a straight
+    /// line.
+    ///
+    /// Unlike [get_shortest_path_from_point](#func-get_shortest_path_from_point),
+    /// the path starts with `point_id` itself. If there is no path from
+    /// `point_id`, the returned array is empty.
+    ///
+    /// # Parameters
+    ///
+    /// - `point_id` : starting point of the path.
+    /// - `grid` : the [Dictionary] returned by
+    /// [add_square_grid](#func-add_square_grid).
+    /// - `terrain_weights` (default : `{}`) : weights of the terrain types.
+    /// A cell can be walked through if its point is enabled and its terrain
+    /// type has a weight (the default terrain `-1` always has a weight of
+    /// `1.0`).
+    /// - `cell_size` (default : `Vector2(1, 1)`) : size of a cell in the
+    /// world. Positions are the grid positions multiplied by `cell_size`.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 4, 4))
+    /// dijkstra_map.recalculate(grid[Vector2(3, 3)])
+    /// var path = dijkstra_map.get_smoothed_path_from_point(grid[Vector2(0, 0)], grid, {}, Vector2(16, 16))
+    /// assert_eq(Array(path), [Vector2(0, 0), Vector2(48, 48)])
+    /// ```
+    #[method]
+    pub fn get_smoothed_path_from_point(
+        &self,
+        point_id: i32,
+        grid: Dictionary,
+        #[opt] terrain_weights: Option<Dictionary>,
+        #[opt] cell_size: Option<Vector2>,
+    ) -> Vector2Array {
+        let point = PointId(point_id);
+        if self.dijkstra.get_direction_at_point(point).is_none() {
+            return Vector2Array::new();
+        }
+        let grid = dictionary_to_grid(&grid);
+        let terrain_weights = dictionary_to_terrain_weights(&terrain_weights.unwrap_or_default());
+        let path: Vec<PointId> = std::iter::once(point)
+            .chain(self.dijkstra.get_shortest_path_from_point(point))
+            .collect();
+        let smoothed = self.dijkstra.smooth_path(&path, &grid, &terrain_weights);
+        path_to_world_positions(&smoothed, &grid, cell_size.unwrap_or(Vector2::ONE))
+    }
+
+    /// Finds an any-angle path from `source` to `target` on a square grid,
+    /// with the Theta* algorithm.
+    ///
+    /// The path can go in a straight line between any two points with a line
+    /// of sight, instead of following the connections of the grid. The cost
+    /// of a straight line is its length times the largest weight of the
+    /// cells it crosses.
+    ///
+    /// Returns the world positions of the turning points of the path,
+    /// starting with `source` and ending with `target`, or an empty array if
+    /// there is no path.
+    ///
+    /// # Parameters
+    ///
+    /// - `source`, `target` : ends of the path.
+    /// - `grid` : the [Dictionary] returned by
+    /// [add_square_grid](#func-add_square_grid).
+    /// - `terrain_weights` (default : `{}`) : weights of the terrain types.
+    /// Terrain types without a weight can not be walked through (the default
+    /// terrain `-1` always has a weight of `1.0`).
+    /// - `cell_size` (default : `Vector2(1, 1)`) : size of a cell in the
+    /// world. Positions are the grid positions multiplied by `cell_size`.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 6, 3))
+    /// for x in range(1, 5):
+    ///     dijkstra_map.disable_point(grid[Vector2(x, 1)])
+    /// var path = dijkstra_map.get_any_angle_path(grid[Vector2(1, 0)], grid[Vector2(1, 2)], grid)
+    /// assert_eq(Array(path), [Vector2(1, 0), Vector2(0, 0), Vector2(0, 2), Vector2(1, 2)])
+    /// ```
+    #[method]
+    pub fn get_any_angle_path(
+        &self,
+        source: i32,
+        target: i32,
+        grid: Dictionary,
+        #[opt] terrain_weights: Option<Dictionary>,
+        #[opt] cell_size: Option<Vector2>,
+    ) -> Vector2Array {
+        let grid = dictionary_to_grid(&grid);
+        let terrain_weights = dictionary_to_terrain_weights(&terrain_weights.unwrap_or_default());
+        match self.dijkstra.find_any_angle_path(
+            PointId(source),
+            PointId(target),
+            &grid,
+            &terrain_weights,
+        ) {
+            Some(path) => path_to_world_positions(&path, &grid, cell_size.unwrap_or(Vector2::ONE)),
+            None => Vector2Array::new(),
+        }
+    }
+
+    /// Adds a square grid of connected points.
+    ///
+    /// # Parameters
+    ///
+    /// - `bounds` : Dimensions of the grid. At the moment, only
+    /// [Rect2] is supported.
+    /// - `terrain_type` (default : `-1`) : Terrain to use for all
+    /// points of the grid.
+    /// - `orthogonal_cost` (default : `1.0`) : specifies cost of
+    /// orthogonal connections (up, down, right and left). \
+    ///   If `orthogonal_cost` is [INF] or [NAN], orthogonal
+    /// connections are disabled.
+    /// - `diagonal_cost` (default : [INF]) : specifies cost of
+    /// diagonal connections. \
+    ///   If `diagonal_cost` is [INF] or [NAN], diagonal connections
+    /// are disabled.
+    /// - `wrap_x` (default : [false]) : if [true], the left and right
+    /// edges of the grid are connected.
+    /// - `wrap_y` (default : [false]) : if [true], the top and bottom
+    /// edges of the grid are connected.
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] where keys are coordinates
+    /// of points ([Vector2]) and values are their corresponding point
+    /// IDs.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 5, 5), -1, 1.0, INF, true)
+    /// assert_true(dijkstra_map.has_connection(grid[Vector2(0, 2)], grid[Vector2(4, 2)]))
+    /// assert_false(dijkstra_map.has_connection(grid[Vector2(2, 0)], grid[Vector2(2, 4)]))
+    /// ```
+    #[method]
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_square_grid(
+        &mut self,
+        #[base] base: &Reference,
+        bounds: Variant,
+        #[opt] terrain_type: Option<i32>,
+        #[opt] orthogonal_cost: Option<f32>,
+        #[opt] diagonal_cost: Option<f32>,
+        #[opt] wrap_x: Option<bool>,
+        #[opt] wrap_y: Option<bool>,
+    ) -> Dictionary {
+        let (x_offset, y_offset, width, height) =
+            variant_to_width_and_height(bounds).expect("couldn't use bounds variant");
+        let grid = self.dijkstra.add_wrapping_square_grid(
+            width,
+            height,
+            Some((x_offset, y_offset).into()),
+            GridWrap {
+                x: wrap_x.unwrap_or(false),
+                y: wrap_y.unwrap_or(false),
+            },
+            terrain_type.unwrap_or(-1).into(),
+            orthogonal_cost.map(Weight),
+            diagonal_cost.map(Weight),
+        );
+        self.graph_changed(base);
+        grid_to_dictionary(&grid)
+    }
+
+    /// Adds a hexagonal grid of connected points.
+    ///
+    /// # Parameters
+    ///
+    /// - `bounds` : Dimensions of the grid.
+    /// - `terrain_type` (default : `-1`) : specifies terrain to be used.
+    /// - `weight` (default : `1.0`) : specifies cost of connections.
+    /// - `wrap_x` (default : [false]) : if [true], the left and right
+    /// edges of the grid are connected. The width of the grid must then be
+    /// even, otherwise the map is not modified and the dictionary is empty.
+    /// - `wrap_y` (default : [false]) : if [true], the top and bottom
+    /// edges of the grid are connected.
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] where keys are
+    /// coordinates of points ([Vector2]) and values are their
+    /// corresponding point IDs.
+    ///
+    /// # Note
+    ///
+    /// Hexgrid is in the "pointy" orientation by default (see example
+    /// below).
+    ///
+    /// For backward compatibility, the neighbours of a point are picked by
+    /// the parity of its x coordinate, and not by the parity of its row as
+    /// the example below suggests.
+    ///
+    /// For other orientations and coordinate systems, see
+    /// [add_hexagonal_grid_with_layout](#func-add_hexagonal_grid_with_layout).
+    ///
+    /// # Example
+    ///
+    /// This is what `dijkstra_map.add_hexagonal_grid(Rect2(1, 4, 2, 3), ...)` would produce:
+    ///
+    ///```text
     ///    / \     / \
     ///  /     \ /     \
     /// |  1,4  |  2,4  |
@@ -1170,35 +2746,1174 @@ impl Interface {
     #[method]
     pub fn add_hexagonal_grid(
         &mut self,
+        #[base] base: &Reference,
         bounds: Variant,
         #[opt] terrain_type: Option<i32>,
         #[opt] weight: Option<f32>,
+        #[opt] wrap_x: Option<bool>,
+        #[opt] wrap_y: Option<bool>,
     ) -> Dictionary {
         let (x_offset, y_offset, width, height) =
             variant_to_width_and_height(bounds).expect("couldn't use bounds variant");
-        let dict = Dictionary::new();
-        for (&k, &v) in self
-            .dijkstra
-            .add_hexagonal_grid(
-                width,
-                height,
-                Some((x_offset, y_offset).into()),
-                terrain_type.unwrap_or(-1).into(),
-                weight.map(Weight),
-            )
+        let wrap = GridWrap {
+            x: wrap_x.unwrap_or(false),
+            y: wrap_y.unwrap_or(false),
+        };
+        let grid = match self.dijkstra.add_wrapping_hexagonal_grid(
+            width,
+            height,
+            Some((x_offset, y_offset).into()),
+            wrap,
+            terrain_type.unwrap_or(-1).into(),
+            weight.map(Weight),
+        ) {
+            Ok(grid) => grid,
+            Err(OddWrappingWidth) => {
+                godot_error!("Hexagonal grids can only wrap horizontally with an even width");
+                return Dictionary::new().into_shared();
+            }
+        };
+        self.graph_changed(base);
+        grid_to_dictionary(&grid)
+    }
+
+    /// Adds a square grid of connected points, whose IDs are computed from
+    /// their coordinates instead of being picked among the available IDs.
+    ///
+    /// The point at `(x, y)` has the ID
+    /// `base_id + (y - bounds.position.y) * bounds.size.x + (x - bounds.position.x)`.
+    /// IDs are then the same on every machine and in every session, which
+    /// is useful for networking and save files. Use
+    /// [grid_cells_to_ids](#func-grid_cells_to_ids) and
+    /// [grid_ids_to_cells](#func-grid_ids_to_cells) to convert between
+    /// coordinates and IDs.
+    ///
+    /// The other parameters are the same as
+    /// [add_square_grid](#func-add_square_grid).
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] where keys are coordinates
+    /// of points ([Vector2]) and values are their corresponding point
+    /// IDs. If one of the IDs is already taken, or if the IDs overflow the
+    /// range of integers, the map is not modified and the dictionary is
+    /// empty.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid_with_ids(Rect2(0, 0, 10, 5), 100)
+    /// assert_eq(grid[Vector2(3, 2)], 123)
+    /// assert_true(dijkstra_map.add_square_grid_with_ids(Rect2(0, 0, 10, 5), 100).empty())
+    /// ```
+    #[method]
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_square_grid_with_ids(
+        &mut self,
+        #[base] base: &Reference,
+        bounds: Variant,
+        base_id: i32,
+        #[opt] terrain_type: Option<i32>,
+        #[opt] orthogonal_cost: Option<f32>,
+        #[opt] diagonal_cost: Option<f32>,
+        #[opt] wrap_x: Option<bool>,
+        #[opt] wrap_y: Option<bool>,
+    ) -> Dictionary {
+        let ids = variant_to_grid_ids(bounds, base_id).expect("couldn't use bounds variant");
+        let grid = match self.dijkstra.add_square_grid_with_ids(
+            ids,
+            GridWrap {
+                x: wrap_x.unwrap_or(false),
+                y: wrap_y.unwrap_or(false),
+            },
+            terrain_type.unwrap_or(-1).into(),
+            orthogonal_cost.map(Weight),
+            diagonal_cost.map(Weight),
+        ) {
+            Ok(grid) => grid,
+            Err(error) => {
+                godot_error!("{}", grid_ids_error_message(error));
+                return Dictionary::new().into_shared();
+            }
+        };
+        self.graph_changed(base);
+        grid_to_dictionary(&grid)
+    }
+
+    /// Adds a hexagonal grid of connected points, whose IDs are computed
+    /// from their coordinates (see
+    /// [add_square_grid_with_ids](#func-add_square_grid_with_ids)).
+    ///
+    /// The other parameters are the same as
+    /// [add_hexagonal_grid](#func-add_hexagonal_grid).
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] where keys are coordinates
+    /// of points ([Vector2]) and values are their corresponding point
+    /// IDs. If one of the IDs is already taken, if the IDs overflow the
+    /// range of integers, or if `wrap_x` is [true] with an odd width, the
+    /// map is not modified and the dictionary is empty.
+    #[method]
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_hexagonal_grid_with_ids(
+        &mut self,
+        #[base] base: &Reference,
+        bounds: Variant,
+        base_id: i32,
+        #[opt] terrain_type: Option<i32>,
+        #[opt] weight: Option<f32>,
+        #[opt] wrap_x: Option<bool>,
+        #[opt] wrap_y: Option<bool>,
+    ) -> Dictionary {
+        let ids = variant_to_grid_ids(bounds, base_id).expect("couldn't use bounds variant");
+        let wrap = GridWrap {
+            x: wrap_x.unwrap_or(false),
+            y: wrap_y.unwrap_or(false),
+        };
+        let grid = match self.dijkstra.add_hexagonal_grid_with_ids(
+            ids,
+            wrap,
+            terrain_type.unwrap_or(-1).into(),
+            weight.map(Weight),
+        ) {
+            Ok(grid) => grid,
+            Err(error) => {
+                godot_error!("{}", grid_ids_error_message(error));
+                return Dictionary::new().into_shared();
+            }
+        };
+        self.graph_changed(base);
+        grid_to_dictionary(&grid)
+    }
+
+    /// Returns the IDs of `cells` in a grid created by
+    /// [add_square_grid_with_ids](#func-add_square_grid_with_ids) or
+    /// [add_hexagonal_grid_with_ids](#func-add_hexagonal_grid_with_ids) with
+    /// the same `bounds` and `base_id`.
+    ///
+    /// Cells outside of `bounds` have the ID `null`. The map is not used :
+    /// the IDs are computed from the coordinates.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var ids = dijkstra_map.grid_cells_to_ids(Rect2(0, 0, 10, 5), 100, PoolVector2Array([Vector2(3, 2), Vector2(20, 0)]))
+    /// assert_eq(ids, [123, null])
+    /// ```
+    #[method]
+    pub fn grid_cells_to_ids(
+        &self,
+        bounds: Variant,
+        base_id: i32,
+        cells: Vector2Array,
+    ) -> VariantArray {
+        let grid_ids = variant_to_grid_ids(bounds, base_id).expect("couldn't use bounds variant");
+        let cells: Vec<Vector2D<i32, i32>> = cells
+            .read()
             .iter()
-        {
-            dict.insert(
-                Vector2::new(k.x as f32, k.y as f32).to_variant(),
-                i32::from(v),
-            );
+            .map(|cell| Vector2D::new(cell.x as i32, cell.y as i32))
+            .collect();
+        let ids = VariantArray::new();
+        for id in grid_ids.ids_of(&cells) {
+            match id {
+                Some(id) => ids.push(i32::from(id)),
+                None => ids.push(Variant::nil()),
+            }
         }
-        dict.into_shared()
+        ids.into_shared()
+    }
+
+    /// Returns the cells ([Vector2]) of `ids` in a grid created by
+    /// [add_square_grid_with_ids](#func-add_square_grid_with_ids) or
+    /// [add_hexagonal_grid_with_ids](#func-add_hexagonal_grid_with_ids) with
+    /// the same `bounds` and `base_id`.
+    ///
+    /// IDs that are not in the grid have the cell `null`. The map is not
+    /// used : the cells are computed from the IDs.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var cells = dijkstra_map.grid_ids_to_cells(Rect2(0, 0, 10, 5), 100, PoolIntArray([123, 7]))
+    /// assert_eq(cells, [Vector2(3, 2), null])
+    /// ```
+    #[method]
+    pub fn grid_ids_to_cells(
+        &self,
+        bounds: Variant,
+        base_id: i32,
+        ids: Int32Array,
+    ) -> VariantArray {
+        let grid_ids = variant_to_grid_ids(bounds, base_id).expect("couldn't use bounds variant");
+        let ids: Vec<PointId> = ids.read().iter().map(|&id| PointId(id)).collect();
+        let cells = VariantArray::new();
+        for cell in grid_ids.cells_of(&ids) {
+            match cell {
+                Some(cell) => cells.push(Vector2::new(cell.x as f32, cell.y as f32)),
+                None => cells.push(Variant::nil()),
+            }
+        }
+        cells.into_shared()
+    }
+
+    /// Adds a hexagonal grid of connected points, with the given orientation
+    /// and coordinate system.
+    ///
+    /// # Parameters
+    ///
+    /// - `bounds` : Dimensions of the grid.
+    /// - `orientation` : `"pointy_top"` (hexagons form horizontal rows) or
+    /// `"flat_top"` (hexagons form vertical columns).
+    /// - `coordinates` : coordinate system of the keys of the returned
+    /// [Dictionary] :
+    ///   - `"odd_offset"` : `(column, row)`, where odd rows (pointy top) or
+    /// odd columns (flat top) are shifted by half a hexagon. The grid is a
+    /// rectangle.
+    ///   - `"even_offset"` : same, but even rows or columns are shifted.
+    ///   - `"axial"` : `(q, r)` along the axes of the hexagons. The grid is a
+    /// parallelogram.
+    ///   - `"cube"` : `(q, r, -q - r)`, as [Vector3]. The grid is the same as
+    /// with `"axial"`.
+    /// - `terrain_type` (default : `-1`) : specifies terrain to be used.
+    /// - `weight` (default : `1.0`) : specifies cost of connections.
+    ///
+    /// The position of each point (see
+    /// [get_point_position](#func-get_point_position)) is the center of its
+    /// hexagon, with a distance of `1.0` between neighbours.
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] where keys are coordinates of
+    /// points ([Vector2], or [Vector3] for cube coordinates) and values are
+    /// their corresponding point IDs. If `orientation` or `coordinates` is
+    /// invalid, the map is not modified and the dictionary is empty.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_hexagonal_grid_with_layout(Rect2(0, 0, 3, 3), "flat_top", "odd_offset")
+    /// assert_true(dijkstra_map.has_connection(grid[Vector2(1, 1)], grid[Vector2(0, 2)]))
+    /// assert_false(dijkstra_map.has_connection(grid[Vector2(1, 1)], grid[Vector2(0, 0)]))
+    /// var cube = dijkstra_map.add_hexagonal_grid_with_layout(Rect2(0, 0, 3, 3), "pointy_top", "cube")
+    /// assert_true(dijkstra_map.has_connection(cube[Vector3(1, 1, -2)], cube[Vector3(2, 0, -2)]))
+    /// ```
+    #[method]
+    pub fn add_hexagonal_grid_with_layout(
+        &mut self,
+        #[base] base: &Reference,
+        bounds: Variant,
+        orientation: String,
+        coordinates: String,
+        #[opt] terrain_type: Option<i32>,
+        #[opt] weight: Option<f32>,
+    ) -> Dictionary {
+        let (x_offset, y_offset, width, height) =
+            variant_to_width_and_height(bounds).expect("couldn't use bounds variant");
+        let orientation = match orientation.as_str() {
+            "pointy_top" => HexOrientation::PointyTop,
+            "flat_top" => HexOrientation::FlatTop,
+            _ => {
+                godot_error!("Invalid orientation `{}`", orientation);
+                return Dictionary::new().into_shared();
+            }
+        };
+        let coordinates = match coordinates.as_str() {
+            "odd_offset" => Some(HexCoordinates::OddOffset),
+            "even_offset" => Some(HexCoordinates::EvenOffset),
+            "axial" => Some(HexCoordinates::Axial),
+            "cube" => None,
+            _ => {
+                godot_error!("Invalid coordinates `{}`", coordinates);
+                return Dictionary::new().into_shared();
+            }
+        };
+        let terrain_type = terrain_type.unwrap_or(-1).into();
+        let weight = weight.map(Weight);
+        let dict = match coordinates {
+            Some(coordinates) => {
+                let grid = self.dijkstra.add_hexagonal_grid_with_layout(
+                    width,
+                    height,
+                    Some((x_offset, y_offset).into()),
+                    (orientation, coordinates),
+                    terrain_type,
+                    weight,
+                );
+                grid_to_dictionary(&grid)
+            }
+            None => {
+                let grid = self.dijkstra.add_cube_hexagonal_grid(
+                    width,
+                    height,
+                    Some((x_offset, y_offset).into()),
+                    orientation,
+                    terrain_type,
+                    weight,
+                );
+                let dict = Dictionary::new();
+                for (&k, &v) in grid.iter() {
+                    dict.insert(
+                        Vector3::new(k.x as f32, k.y as f32, k.z as f32).to_variant(),
+                        i32::from(v),
+                    );
+                }
+                dict.into_shared()
+            }
+        };
+        self.graph_changed(base);
+        dict
+    }
+
+    /// Adds an isometric grid of connected points, in the diamond layout of
+    /// an isometric [TileMap].
+    ///
+    /// This is a square grid (see [add_square_grid](#func-add_square_grid),
+    /// which has the same parameters and keys) seen at an angle : the x axis
+    /// goes down-right, and the y axis down-left. The position of each point
+    /// (see [get_point_position](#func-get_point_position)) is the center of
+    /// its tile on screen, for tiles of width `1.0` and height `0.5`.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_isometric_grid(Rect2(0, 0, 3, 3))
+    /// assert_true(dijkstra_map.has_connection(grid[Vector2(1, 1)], grid[Vector2(1, 0)]))
+    /// assert_eq(dijkstra_map.get_point_position(grid[Vector2(1, 0)]), Vector2(0.5, 0.25))
+    /// ```
+    #[method]
+    pub fn add_isometric_grid(
+        &mut self,
+        #[base] base: &Reference,
+        bounds: Variant,
+        #[opt] terrain_type: Option<i32>,
+        #[opt] orthogonal_cost: Option<f32>,
+        #[opt] diagonal_cost: Option<f32>,
+    ) -> Dictionary {
+        let (x_offset, y_offset, width, height) =
+            variant_to_width_and_height(bounds).expect("couldn't use bounds variant");
+        let grid = self.dijkstra.add_isometric_grid(
+            width,
+            height,
+            Some((x_offset, y_offset).into()),
+            terrain_type.unwrap_or(-1).into(),
+            orthogonal_cost.map(Weight),
+            diagonal_cost.map(Weight),
+        );
+        self.graph_changed(base);
+        grid_to_dictionary(&grid)
+    }
+
+    /// Adds an isometric grid of connected points, in the staggered layout
+    /// of an isometric [TileMap] with `cell_half_offset` set to
+    /// `HALF_OFFSET_X` : tiles form horizontal rows, and odd rows are shifted
+    /// right by half a tile.
+    ///
+    /// # Parameters
+    ///
+    /// - `bounds` : Dimensions of the grid.
+    /// - `terrain_type` (default : `-1`) : Terrain to use for all
+    /// points of the grid.
+    /// - `orthogonal_cost` (default : `1.0`) : specifies cost of connections
+    /// between tiles sharing a side (up-left, up-right, down-left and
+    /// down-right). \
+    ///   If `orthogonal_cost` is [INF] or [NAN], these connections are
+    /// disabled.
+    /// - `diagonal_cost` (default : [INF]) : specifies cost of connections
+    /// between tiles sharing a corner (left, right, up and down). \
+    ///   If `diagonal_cost` is [INF] or [NAN], these connections are
+    /// disabled.
+    ///
+    /// The position of each point (see
+    /// [get_point_position](#func-get_point_position)) is the center of its
+    /// tile on screen, for tiles of width `1.0` and height `0.5`.
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] where keys are the [TileMap]
+    /// coordinates of points ([Vector2]) and values are their corresponding
+    /// point IDs.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_staggered_isometric_grid(Rect2(0, 0, 3, 3))
+    /// assert_true(dijkstra_map.has_connection(grid[Vector2(1, 1)], grid[Vector2(2, 0)]))
+    /// assert_false(dijkstra_map.has_connection(grid[Vector2(1, 1)], grid[Vector2(0, 0)]))
+    /// ```
+    #[method]
+    pub fn add_staggered_isometric_grid(
+        &mut self,
+        #[base] base: &Reference,
+        bounds: Variant,
+        #[opt] terrain_type: Option<i32>,
+        #[opt] orthogonal_cost: Option<f32>,
+        #[opt] diagonal_cost: Option<f32>,
+    ) -> Dictionary {
+        let (x_offset, y_offset, width, height) =
+            variant_to_width_and_height(bounds).expect("couldn't use bounds variant");
+        let grid = self.dijkstra.add_staggered_isometric_grid(
+            width,
+            height,
+            Some((x_offset, y_offset).into()),
+            terrain_type.unwrap_or(-1).into(),
+            orthogonal_cost.map(Weight),
+            diagonal_cost.map(Weight),
+        );
+        self.graph_changed(base);
+        grid_to_dictionary(&grid)
+    }
+
+    /// Extends the persistent square grid of the map with a rectangle of
+    /// connected points, connecting its borders to the neighbouring cells
+    /// that already exist.
+    ///
+    /// Unlike [add_square_grid](#func-add_square_grid), which creates
+    /// independent grids, the grid built by successive calls is remembered
+    /// by the map : use [get_grid_point](#func-get_grid_point) and
+    /// [get_grid_cell](#func-get_grid_cell) to convert between cells and
+    /// points, and [remove_grid_chunk](#func-remove_grid_chunk) to unload a
+    /// part of it. This is useful for worlds streamed in chunks. Grids
+    /// created otherwise can be extended too, once added to the persistent
+    /// grid with [register_grid_cells](#func-register_grid_cells).
+    ///
+    /// # Parameters
+    ///
+    /// - `bounds` : [Rect2] of the new cells. Its position may be negative.
+    /// - `terrain_type` (default : `-1`) : Terrain to use for all
+    /// new points.
+    /// - `orthogonal_cost` (default : `1.0`) : specifies cost of
+    /// orthogonal connections (up, down, right and left). \
+    ///   If `orthogonal_cost` is [INF] or [NAN], orthogonal
+    /// connections are disabled.
+    /// - `diagonal_cost` (default : [INF]) : specifies cost of
+    /// diagonal connections. \
+    ///   If `diagonal_cost` is [INF] or [NAN], diagonal connections
+    /// are disabled.
+    ///
+    /// Cells of `bounds` that are already in the grid are left untouched.
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] where keys are coordinates of
+    /// the new cells ([Vector2]) and values are their point IDs.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.extend_square_grid(Rect2(0, 0, 4, 4))
+    /// var chunk = dijkstra_map.extend_square_grid(Rect2(-4, 0, 4, 4))
+    /// var point = dijkstra_map.get_grid_point(Vector2(0, 2))
+    /// assert_true(dijkstra_map.has_connection(point, chunk[Vector2(-1, 2)]))
+    /// assert_true(dijkstra_map.has_connection(chunk[Vector2(-1, 2)], point))
+    /// ```
+    #[method]
+    pub fn extend_square_grid(
+        &mut self,
+        #[base] base: &Reference,
+        bounds: Variant,
+        #[opt] terrain_type: Option<i32>,
+        #[opt] orthogonal_cost: Option<f32>,
+        #[opt] diagonal_cost: Option<f32>,
+    ) -> Dictionary {
+        let (offset, width, height) = variant_to_rect(bounds).expect("couldn't use bounds variant");
+        let grid = self.dijkstra.extend_square_grid(
+            width,
+            height,
+            offset,
+            terrain_type.unwrap_or(-1).into(),
+            orthogonal_cost.map(Weight),
+            diagonal_cost.map(Weight),
+        );
+        self.graph_changed(base);
+        grid_to_dictionary(&grid)
+    }
+
+    /// Adds the points of an existing grid to the persistent grid (see
+    /// [extend_square_grid](#func-extend_square_grid)), so that new chunks
+    /// are connected to it.
+    ///
+    /// `grid` is a [Dictionary] from coordinates ([Vector2]) to point IDs,
+    /// like the ones returned by [add_square_grid](#func-add_square_grid).
+    /// The connections of the points are left as they are. Cells and points
+    /// that are already in the persistent grid are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [FAILED] without modifying the map if one of the points does
+    /// not exist, else [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 4, 4))
+    /// assert_eq(dijkstra_map.register_grid_cells(grid), OK)
+    /// var chunk = dijkstra_map.extend_square_grid(Rect2(4, 0, 4, 4))
+    /// assert_true(dijkstra_map.has_connection(grid[Vector2(3, 2)], chunk[Vector2(4, 2)]))
+    /// assert_true(dijkstra_map.has_connection(chunk[Vector2(4, 2)], grid[Vector2(3, 2)]))
+    /// ```
+    #[method]
+    pub fn register_grid_cells(&mut self, grid: Dictionary) -> i64 {
+        result_to_int(
+            self.dijkstra
+                .register_grid_cells(&dictionary_to_grid(&grid)),
+        )
+    }
+
+    /// Removes the points of the persistent grid (see
+    /// [extend_square_grid](#func-extend_square_grid)) in `bounds`, along
+    /// with their connections.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the removed points, sorted by ID.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.extend_square_grid(Rect2(0, 0, 8, 4))
+    /// var removed = dijkstra_map.remove_grid_chunk(Rect2(4, 0, 4, 4))
+    /// assert_eq(removed.size(), 16)
+    /// assert_eq(dijkstra_map.get_grid_point(Vector2(4, 0)), -1)
+    /// ```
+    #[method]
+    pub fn remove_grid_chunk(&mut self, #[base] base: &Reference, bounds: Variant) -> Int32Array {
+        let (offset, width, height) = variant_to_rect(bounds).expect("couldn't use bounds variant");
+        let removed = self.dijkstra.remove_grid_chunk(width, height, offset);
+        self.graph_changed(base);
+        Int32Array::from_vec(removed.into_iter().map(i32::from).collect())
+    }
+
+    /// Returns the point of `cell` in the persistent grid (see
+    /// [extend_square_grid](#func-extend_square_grid)), or `-1` if there is
+    /// none.
+    #[method]
+    pub fn get_grid_point(&self, cell: Vector2) -> i32 {
+        self.dijkstra
+            .get_grid_point(Vector2D::new(cell.x as i32, cell.y as i32))
+            .map_or(-1, i32::from)
+    }
+
+    /// Returns the cell ([Vector2]) of `point_id` in the persistent grid
+    /// (see [extend_square_grid](#func-extend_square_grid)), or `null` if it
+    /// is not in the grid.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var chunk = dijkstra_map.extend_square_grid(Rect2(2, 3, 2, 2))
+    /// assert_eq(dijkstra_map.get_grid_cell(chunk[Vector2(3, 4)]), Vector2(3, 4))
+    /// assert_eq(dijkstra_map.get_grid_cell(1000), null)
+    /// ```
+    #[method]
+    pub fn get_grid_cell(&self, point_id: i32) -> Variant {
+        match self.dijkstra.get_grid_cell(PointId(point_id)) {
+            Some(cell) => Vector2::new(cell.x as f32, cell.y as f32).to_variant(),
+            None => Variant::nil(),
+        }
+    }
+
+    /// Adds the graph of a navigation mesh made of convex polygons, for
+    /// levels that are not tile-based.
+    ///
+    /// # Parameters
+    ///
+    /// - `vertices` : positions of the vertices of the mesh.
+    /// - `polygons` : [Array] of [PoolIntArray], containing the indices in
+    /// `vertices` of each polygon's vertices. Two polygons are neighbours if
+    /// they share a side.
+    /// - `terrain_type` (default : `-1`) : terrain of the new points.
+    /// - `add_portals` (default : [false]) : if [true], a point is also added
+    /// in the middle of every shared side, and paths go from side to side
+    /// instead of through the center of the polygons.
+    ///
+    /// Each polygon becomes a point, whose position (see
+    /// [get_point_position](#func-get_point_position)) is the average of its
+    /// vertices. Connections are bidirectional, and weighted by the distance
+    /// between the positions of their points.
+    ///
+    /// With a [NavigationPolygon], `vertices` is `get_vertices()` and
+    /// `polygons` contains `get_polygon(i)` for each polygon.
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] with the following keys :
+    /// - `"polygons"` : [PoolIntArray] of the point of each polygon.
+    /// - `"portals"` : [PoolIntArray] of the points added in the middle of
+    /// shared sides, if any.
+    ///
+    /// If a polygon has less than 3 vertices or refers to a vertex that does
+    /// not exist, the map is not modified and the dictionary is empty.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var vertices = PoolVector2Array([Vector2(0, 0), Vector2(2, 0), Vector2(2, 2), Vector2(0, 2), Vector2(4, 0), Vector2(4, 2)])
+    /// var polygons = [PoolIntArray([0, 1, 2, 3]), PoolIntArray([1, 4, 5, 2])]
+    /// var points = dijkstra_map.add_navigation_polygons(vertices, polygons)
+    /// assert_true(dijkstra_map.has_connection(points["polygons"][0], points["polygons"][1]))
+    /// assert_eq(dijkstra_map.get_point_position(points["polygons"][1]), Vector2(3, 1))
+    /// ```
+    #[method]
+    pub fn add_navigation_polygons(
+        &mut self,
+        #[base] base: &Reference,
+        vertices: Vector2Array,
+        polygons: VariantArray,
+        #[opt] terrain_type: Option<i32>,
+        #[opt] add_portals: Option<bool>,
+    ) -> Dictionary {
+        let vertices: Vec<Position> = vertices
+            .read()
+            .iter()
+            .map(|vertex| Position::new(vertex.x, vertex.y, 0.0))
+            .collect();
+        let mut indices = Vec::new();
+        for polygon in polygons.iter() {
+            match polygon.try_to::<Int32Array>() {
+                Ok(polygon) => indices.push(
+                    polygon
+                        .read()
+                        .iter()
+                        // negative indices are out of bounds too
+                        .map(|&index| usize::try_from(index).unwrap_or(usize::MAX))
+                        .collect(),
+                ),
+                Err(_) => {
+                    type_warning(
+                        "element of 'polygons'",
+                        VariantType::Int32Array,
+                        polygon.get_type(),
+                        line!(),
+                    );
+                    return Dictionary::new().into_shared();
+                }
+            }
+        }
+        let points = match self.dijkstra.add_navigation_polygons(
+            &vertices,
+            &indices,
+            terrain_type.unwrap_or(-1).into(),
+            add_portals.unwrap_or(false),
+        ) {
+            Ok(points) => points,
+            Err(_) => {
+                godot_error!("Invalid polygon : less than 3 vertices, or unknown vertex");
+                return Dictionary::new().into_shared();
+            }
+        };
+        let mut portals: Vec<PointId> = points.portals.values().copied().collect();
+        portals.sort_unstable();
+        self.graph_changed(base);
+        let dict = Dictionary::new();
+        dict.insert(
+            "polygons",
+            Int32Array::from_vec(points.polygons.iter().map(|&point| point.into()).collect()),
+        );
+        dict.insert(
+            "portals",
+            Int32Array::from_vec(portals.into_iter().map(i32::from).collect()),
+        );
+        dict.into_shared()
+    }
+
+    /// Returns the weakly connected components of the map.
+    ///
+    /// Two enabled points are in the same component if they are linked by a
+    /// chain of connections, regardless of their direction.
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] where keys are points' IDs and
+    /// values are the IDs of their component. Disabled points are not present
+    /// in the dictionary.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.add_point(2)
+    /// dijkstra_map.connect_points(0, 1, 1.0, false)
+    /// var components = dijkstra_map.get_weakly_connected_components()
+    /// assert_eq(components[0], components[1])
+    /// assert_ne(components[0], components[2])
+    /// ```
+    #[method]
+    pub fn get_weakly_connected_components(&self) -> Dictionary {
+        components_to_dictionary(&self.dijkstra.weakly_connected_components())
+    }
+
+    /// Returns the strongly connected components of the map.
+    ///
+    /// Two enabled points are in the same component if each one can be
+    /// reached from the other, following the direction of connections.
+    ///
+    /// # Returns
+    ///
+    /// This function returns a [Dictionary] where keys are points' IDs and
+    /// values are the IDs of their component. Disabled points are not present
+    /// in the dictionary.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.add_point(2)
+    /// dijkstra_map.connect_points(0, 1)
+    /// dijkstra_map.connect_points(1, 2, 1.0, false)
+    /// var components = dijkstra_map.get_strongly_connected_components()
+    /// assert_eq(components[0], components[1])
+    /// assert_ne(components[1], components[2])
+    /// ```
+    #[method]
+    pub fn get_strongly_connected_components(&self) -> Dictionary {
+        components_to_dictionary(&self.dijkstra.strongly_connected_components())
+    }
+
+    /// Splits the map into regions, and builds the graph of these regions.
+    ///
+    /// A region is a set of enabled points with the same terrain type, linked
+    /// by connections (ignoring their direction). On a grid where walls are
+    /// disabled points, giving rooms and corridors different terrain types
+    /// makes each room and corridor a separate region.
+    ///
+    /// Returns a dictionary with the following keys :
+    /// - `"map"` : a new `DijkstraMap`, with one point per region. Regions
+    ///   are numbered from `0`, in increasing order of their smallest point
+    ///   ID. Each region has the terrain type of its points, and is connected
+    ///   to the regions reachable from it. The weight of a connection is the
+    ///   cost of going from the center of the first region to the center of
+    ///   the second one, through the cheapest connection between them, with
+    ///   the weights of the original map. Terrain weights are applied when
+    ///   this map is recalculated, so its costs approximate the costs of
+    ///   paths in the original map.
+    /// - `"region_of"` : a dictionary mapping each enabled point to its
+    ///   region.
+    /// - `"centers"` : a [PoolIntArray] of the central point of each region.
+    ///   If some points of a region have a position, this is the one nearest
+    ///   to the average of their positions.
+    /// - `"doorways"` : an array of the connections between two different
+    ///   regions, as `[source, target]` point ID pairs.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// # room (0, 1) -- corridor (2) -- room (3, 4)
+    /// for i in range(5):
+    ///     dijkstra_map.add_point(i, 1 if i == 2 else -1)
+    ///     if i > 0:
+    ///         dijkstra_map.connect_points(i - 1, i)
+    /// var regions = dijkstra_map.extract_regions()
+    /// assert_eq(regions["map"].get_available_point_id(), 3)
+    /// assert_eq(regions["region_of"][4], 2)
+    /// assert_true(regions["map"].has_connection(1, 2))
+    /// assert_eq(regions["doorways"].size(), 4)
+    /// assert_eq(regions["centers"][0], 0)
+    /// # from 0 to 1 in the room, then to 2
+    /// regions["map"].recalculate(1, {"terrain_weights": {1: 1.0}})
+    /// assert_eq(regions["map"].get_cost_at_point(0), 2.0)
+    /// ```
+    #[method]
+    pub fn extract_regions(&self) -> Dictionary {
+        let regions = self.dijkstra.extract_regions();
+        let region_of = Dictionary::new();
+        for (&point, &region) in regions.region_map().iter() {
+            region_of.insert(i32::from(point), i32::from(region));
+        }
+        let doorways = VariantArray::new();
+        for (source, target) in regions.iter_doorways() {
+            doorways.push(Int32Array::from_vec(vec![
+                i32::from(source),
+                i32::from(target),
+            ]));
+        }
+        let centers = (0..regions.count() as i32)
+            .filter_map(|region| regions.center(PointId(region)))
+            .map(i32::from)
+            .collect();
+        let dict = Dictionary::new();
+        dict.insert("centers", Int32Array::from_vec(centers));
+        dict.insert(
+            "map",
+            Instance::emplace(Interface::from_dijkstra_map(regions.into_map())),
+        );
+        dict.insert("region_of", region_of.into_shared());
+        dict.insert("doorways", doorways.into_shared());
+        dict.into_shared()
+    }
+
+    /// Returns [true] if there is a path from `source` to `target` going
+    /// only through enabled points.
+    ///
+    /// The first call precomputes reachability for every pair of points,
+    /// after which calls are answered in constant time, until the graph is
+    /// modified.
+    ///
+    /// Returns [false] if one of the points is disabled or does not exist.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.add_point(2)
+    /// dijkstra_map.connect_points(0, 1, 1.0, false)
+    /// assert_true(dijkstra_map.is_reachable(0, 1))
+    /// assert_false(dijkstra_map.is_reachable(1, 0))
+    /// assert_false(dijkstra_map.is_reachable(0, 2))
+    /// dijkstra_map.connect_points(1, 2)
+    /// assert_true(dijkstra_map.is_reachable(0, 2))
+    /// ```
+    #[method]
+    pub fn is_reachable(&mut self, source: i32, target: i32) -> bool {
+        let revision = self.dijkstra.revision();
+        match &self.reachability {
+            Some((computed_revision, _)) if *computed_revision == revision => {}
+            _ => self.reachability = Some((revision, self.dijkstra.compute_reachability())),
+        }
+        let (_, reachability) = self.reachability.as_ref().unwrap();
+        reachability.is_reachable(source.into(), target.into())
+    }
+
+    /// Precomputes the shortest paths between every pair of points.
+    ///
+    /// The results can then be queried with
+    /// [get_distance](#func-get_distance) and
+    /// [get_next_hop](#func-get_next_hop). They are discarded as soon as the
+    /// graph is modified.
+    ///
+    /// This takes a time proportional to the cube of the number of points,
+    /// so it should only be used on small graphs (a few hundred points).
+    ///
+    /// # Parameters
+    ///
+    /// - `terrain_weights` (default : empty) : Specifies weights of terrain
+    /// types, in the same way as the `"terrain_weights"` parameter of
+    /// [recalculate](#func-recalculate).
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.add_point(2)
+    /// dijkstra_map.connect_points(0, 1)
+    /// dijkstra_map.connect_points(1, 2)
+    /// dijkstra_map.recalculate_all_pairs()
+    /// assert_eq(dijkstra_map.get_distance(0, 2), 2.0)
+    /// assert_eq(dijkstra_map.get_distance(2, 0), 2.0)
+    /// assert_eq(dijkstra_map.get_next_hop(0, 2), 1)
+    /// ```
+    #[method]
+    pub fn recalculate_all_pairs(&mut self, #[opt] terrain_weights: Option<Dictionary>) {
+        let terrain_weights = terrain_weights
+            .map(|dict| dictionary_to_terrain_weights(&dict))
+            .unwrap_or_default();
+        self.dijkstra.recalculate_all_pairs(terrain_weights);
+    }
+
+    /// Returns the cost of the shortest path from `source` to `target`,
+    /// as computed by [recalculate_all_pairs](#func-recalculate_all_pairs).
+    ///
+    /// If there is no path, the paths were not computed, or the graph was
+    /// modified since, the cost is [INF].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.add_point(2)
+    /// dijkstra_map.connect_points(0, 1, 1.0, false)
+    /// dijkstra_map.recalculate_all_pairs()
+    /// assert_eq(dijkstra_map.get_distance(0, 1), 1.0)
+    /// assert_eq(dijkstra_map.get_distance(1, 0), INF)
+    /// dijkstra_map.connect_points(1, 2)
+    /// assert_eq(dijkstra_map.get_distance(0, 1), INF, "paths must be recalculated")
+    /// ```
+    #[method]
+    pub fn get_distance(&self, source: i32, target: i32) -> f32 {
+        self.dijkstra
+            .distance(source.into(), target.into())
+            .unwrap_or_else(Cost::infinity)
+            .into()
+    }
+
+    /// Returns the next point on the shortest path from `source` to
+    /// `target`, as computed by
+    /// [recalculate_all_pairs](#func-recalculate_all_pairs).
+    ///
+    /// # Errors
+    ///
+    /// This function returns `-1` if there is no path, the paths were not
+    /// computed, or the graph was modified since.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.add_point(2)
+    /// dijkstra_map.connect_points(0, 1)
+    /// dijkstra_map.recalculate_all_pairs()
+    /// assert_eq(dijkstra_map.get_next_hop(0, 1), 1)
+    /// assert_eq(dijkstra_map.get_next_hop(0, 2), -1)
+    /// ```
+    #[method]
+    pub fn get_next_hop(&self, source: i32, target: i32) -> i32 {
+        self.dijkstra
+            .next_hop(source.into(), target.into())
+            .unwrap_or(PointId(-1))
+            .into()
+    }
+
+    /// Returns the current revision of the graph.
+    ///
+    /// The revision increases every time points, connections, terrains or
+    /// disabled points are modified. It is also sent with the
+    /// `graph_changed` signal.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// watch_signals(dijkstra_map)
+    /// # handlers can call methods of the map
+    /// dijkstra_map.connect("graph_changed", dijkstra_map, "has_point")
+    /// var revision = dijkstra_map.get_revision()
+    /// dijkstra_map.add_point(0)
+    /// assert_gt(dijkstra_map.get_revision(), revision)
+    /// yield(get_tree(), "idle_frame")
+    /// assert_signal_emitted_with_parameters(dijkstra_map, "graph_changed", [dijkstra_map.get_revision()])
+    /// ```
+    #[method]
+    pub fn get_revision(&self) -> i64 {
+        self.dijkstra.revision() as i64
+    }
+
+    /// Returns [true] if the graph was modified since the last call to
+    /// [recalculate](#func-recalculate).
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// assert_true(dijkstra_map.is_stale())
+    /// dijkstra_map.recalculate(0)
+    /// assert_false(dijkstra_map.is_stale())
+    /// dijkstra_map.set_terrain_for_point(0, 2)
+    /// assert_true(dijkstra_map.is_stale())
+    /// ```
+    #[method]
+    pub fn is_stale(&self) -> bool {
+        self.dijkstra.is_stale()
+    }
+
+    /// Returns the IDs of the points that were added, removed or modified
+    /// since the given `revision`, sorted in increasing order.
+    ///
+    /// A point is modified when its terrain, enabled state or connections
+    /// change. Removed points are forgotten after `65536` revisions, so
+    /// they may be missing if `revision` is older than that.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.add_point(2)
+    /// var revision = dijkstra_map.get_revision()
+    /// dijkstra_map.connect_points(0, 2)
+    /// assert_eq(Array(dijkstra_map.get_changed_points_since(revision)), [0, 2])
+    /// ```
+    #[method]
+    pub fn get_changed_points_since(&self, revision: i64) -> Int32Array {
+        Int32Array::from_vec(
+            self.dijkstra
+                .changed_points_since(revision.max(0) as u64)
+                .into_iter()
+                .map(i32::from)
+                .collect(),
+        )
+    }
+
+    /// Starts recording modifications of the graph in a transaction.
+    ///
+    /// The transaction is closed by either
+    /// [commit_transaction](#func-commit_transaction), after which it can be
+    /// reverted with [undo](#func-undo), or
+    /// [rollback_transaction](#func-rollback_transaction), which reverts it
+    /// immediately.
+    ///
+    /// Modifications made outside of transactions cannot be undone, and clear
+    /// the [undo](#func-undo) and [redo](#func-redo) history.
+    ///
+    /// # Errors
+    ///
+    /// Returns [FAILED] if a transaction is already open, else [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// assert_eq(dijkstra_map.begin_transaction(), OK)
+    /// assert_eq(dijkstra_map.begin_transaction(), FAILED)
+    /// ```
+    #[method]
+    pub fn begin_transaction(&mut self) -> i64 {
+        result_to_int(self.dijkstra.begin_transaction())
+    }
+
+    /// Closes the open transaction, so that it can be reverted with
+    /// [undo](#func-undo).
+    ///
+    /// # Errors
+    ///
+    /// Returns [FAILED] if no transaction is open, else [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.begin_transaction()
+    /// dijkstra_map.add_point(0)
+    /// assert_eq(dijkstra_map.commit_transaction(), OK)
+    /// assert_eq(dijkstra_map.commit_transaction(), FAILED)
+    /// assert_true(dijkstra_map.can_undo())
+    /// ```
+    #[method]
+    pub fn commit_transaction(&mut self) -> i64 {
+        result_to_int(self.dijkstra.commit_transaction())
+    }
+
+    /// Reverts every modification made since
+    /// [begin_transaction](#func-begin_transaction), and closes the
+    /// transaction.
+    ///
+    /// This is useful to try out modifications, for example to check if
+    /// blocking a point would cut a path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [FAILED] if no transaction is open, else [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.connect_points(0, 1)
+    /// dijkstra_map.begin_transaction()
+    /// dijkstra_map.disable_point(1)
+    /// assert_false(dijkstra_map.is_reachable(0, 1))
+    /// assert_eq(dijkstra_map.rollback_transaction(), OK)
+    /// assert_true(dijkstra_map.is_reachable(0, 1))
+    /// ```
+    #[method]
+    pub fn rollback_transaction(&mut self, #[base] base: &Reference) -> i64 {
+        let res = self.dijkstra.rollback_transaction();
+        self.graph_changed(base);
+        result_to_int(res)
+    }
+
+    /// Reverts the last committed transaction.
+    ///
+    /// Returns [false] if there was nothing to undo, or if a transaction is
+    /// open.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.begin_transaction()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.commit_transaction()
+    /// assert_true(dijkstra_map.undo())
+    /// assert_false(dijkstra_map.has_point(0))
+    /// assert_false(dijkstra_map.undo())
+    /// ```
+    #[method]
+    pub fn undo(&mut self, #[base] base: &Reference) -> bool {
+        let res = self.dijkstra.undo();
+        self.graph_changed(base);
+        res
+    }
+
+    /// Re-applies the last transaction reverted by [undo](#func-undo).
+    ///
+    /// Returns [false] if there was nothing to redo, or if a transaction is
+    /// open.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.begin_transaction()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.commit_transaction()
+    /// dijkstra_map.undo()
+    /// assert_true(dijkstra_map.redo())
+    /// assert_true(dijkstra_map.has_point(0))
+    /// assert_false(dijkstra_map.redo())
+    /// ```
+    #[method]
+    pub fn redo(&mut self, #[base] base: &Reference) -> bool {
+        let res = self.dijkstra.redo();
+        self.graph_changed(base);
+        res
+    }
+
+    /// Returns [true] if [undo](#func-undo) would succeed.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// assert_false(dijkstra_map.can_undo())
+    /// dijkstra_map.begin_transaction()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.commit_transaction()
+    /// assert_true(dijkstra_map.can_undo())
+    /// ```
+    #[method]
+    pub fn can_undo(&self) -> bool {
+        self.dijkstra.can_undo()
+    }
+
+    /// Returns [true] if [redo](#func-redo) would succeed.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.begin_transaction()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.commit_transaction()
+    /// assert_false(dijkstra_map.can_redo())
+    /// dijkstra_map.undo()
+    /// assert_true(dijkstra_map.can_redo())
+    /// ```
+    #[method]
+    pub fn can_redo(&self) -> bool {
+        self.dijkstra.can_redo()
+    }
+
+    /// Discards the [undo](#func-undo) and [redo](#func-redo) history.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.begin_transaction()
+    /// dijkstra_map.add_point(0)
+    /// dijkstra_map.commit_transaction()
+    /// dijkstra_map.clear_history()
+    /// assert_false(dijkstra_map.can_undo())
+    /// ```
+    #[method]
+    pub fn clear_history(&mut self) {
+        self.dijkstra.clear_history()
     }
 }
 
 fn init(handle: gdnative::prelude::InitHandle) {
     handle.add_class::<Interface>();
+    handle.add_class::<debug_draw::DebugDraw>();
+    handle.add_class::<resource::GraphResource>();
 }
 godot_gdnative_init!();
 godot_nativescript_init!(init);
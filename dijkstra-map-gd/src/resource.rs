@@ -0,0 +1,249 @@
+use super::{variant_to_interface, variant_to_position, Interface, FAILED, OK};
+use dijkstra_map::{DijkstraMap, PointId, TerrainType, Weight};
+use gdnative::prelude::*;
+
+/// Resource storing a graph (points, terrains, connections, disabled points
+/// and positions), to save it as a `.tres` or `.res` file, edit it in the
+/// inspector and [preload] it.
+///
+/// # Usage
+/// 1. Fill the resource with [store](#func-store), for example from an
+/// editor tool that builds the graph of a level, or edit its properties in
+/// the inspector.
+/// 2. Save it with [ResourceSaver].
+/// 3. At runtime, create the `DijkstraMap` of the level with
+/// [instantiate](#func-instantiate).
+///
+/// # Properties
+/// - `point_ids` : [PoolIntArray] of the IDs of the points.
+/// - `terrains` : [PoolIntArray] of the terrain of each point. If empty,
+/// every point has the default terrain (`-1`).
+/// - `connection_sources`, `connection_targets` : [PoolIntArray] of the
+/// source and target of each connection. Connections are directed.
+/// - `connection_weights` : [PoolRealArray] of the weight of each
+/// connection. If empty, every connection has a weight of `1.0`.
+/// - `disabled_points` : [PoolIntArray] of the disabled points.
+/// - `positions` : [Dictionary] where keys are point IDs and values their
+/// position ([Vector2] or [Vector3]).
+#[derive(NativeClass)]
+#[inherit(Resource)]
+pub struct GraphResource {
+    #[property]
+    point_ids: Int32Array,
+    #[property]
+    terrains: Int32Array,
+    #[property]
+    connection_sources: Int32Array,
+    #[property]
+    connection_targets: Int32Array,
+    #[property]
+    connection_weights: Float32Array,
+    #[property]
+    disabled_points: Int32Array,
+    #[property]
+    positions: Dictionary,
+}
+
+#[methods]
+impl GraphResource {
+    fn new(_base: &Resource) -> Self {
+        GraphResource {
+            point_ids: Int32Array::new(),
+            terrains: Int32Array::new(),
+            connection_sources: Int32Array::new(),
+            connection_targets: Int32Array::new(),
+            connection_weights: Float32Array::new(),
+            disabled_points: Int32Array::new(),
+            positions: Dictionary::new().into_shared(),
+        }
+    }
+
+    /// Replaces the content of the resource by the graph of `dijkstra_map`.
+    ///
+    /// Points and connections are sorted by ID, so that storing the same
+    /// graph twice gives the same file.
+    ///
+    /// # Errors
+    ///
+    /// This function returns [FAILED] if `dijkstra_map` is not a
+    /// `DijkstraMap`, in which case the resource is not modified. Else it
+    /// returns [OK].
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// dijkstra_map.add_point(0, 2)
+    /// dijkstra_map.add_point(1)
+    /// dijkstra_map.connect_points(0, 1, 2.5, false)
+    /// var resource = DijkstraMapResource.new()
+    /// assert_eq(resource.store(dijkstra_map), OK)
+    /// assert_eq(Array(resource.point_ids), [0, 1])
+    /// assert_eq(Array(resource.terrains), [2, -1])
+    /// assert_eq(Array(resource.connection_weights), [2.5])
+    /// ```
+    #[method]
+    pub fn store(&mut self, #[base] base: &Resource, dijkstra_map: Variant) -> i64 {
+        let instance = match variant_to_interface(&dijkstra_map) {
+            Some(instance) => instance,
+            None => {
+                godot_error!("Failed to convert Variant to DijkstraMap.");
+                return FAILED;
+            }
+        };
+        let instance = unsafe { instance.assume_safe() };
+        let stored = instance.map(|interface, _| {
+            let dijkstra = &interface.dijkstra;
+            let mut points: Vec<PointId> = dijkstra.points().collect();
+            points.sort_unstable();
+            let mut connections = Vec::new();
+            let positions = Dictionary::new();
+            for &point in points.iter() {
+                let mut outgoing: Vec<(PointId, Weight)> = dijkstra.connections_of(point).collect();
+                outgoing.sort_unstable_by_key(|&(target, _)| target);
+                connections.extend(
+                    outgoing
+                        .into_iter()
+                        .map(|(target, weight)| (point, target, weight)),
+                );
+                let position = interface.get_point_position(point.into());
+                if !position.is_nil() {
+                    positions.insert(i32::from(point), position);
+                }
+            }
+            let mut disabled_points: Vec<PointId> = dijkstra.disabled_points().collect();
+            disabled_points.sort_unstable();
+
+            self.point_ids =
+                Int32Array::from_vec(points.iter().map(|&point| point.into()).collect());
+            self.terrains = Int32Array::from_vec(
+                points
+                    .iter()
+                    .map(|&point| {
+                        dijkstra
+                            .get_terrain_for_point(point)
+                            .unwrap_or(TerrainType::DefaultTerrain)
+                            .into()
+                    })
+                    .collect(),
+            );
+            self.connection_sources =
+                Int32Array::from_vec(connections.iter().map(|c| c.0.into()).collect());
+            self.connection_targets =
+                Int32Array::from_vec(connections.iter().map(|c| c.1.into()).collect());
+            self.connection_weights =
+                Float32Array::from_vec(connections.iter().map(|c| (c.2).0).collect());
+            self.disabled_points =
+                Int32Array::from_vec(disabled_points.iter().map(|&point| point.into()).collect());
+            self.positions = positions.into_shared();
+        });
+        match stored {
+            Ok(()) => {
+                base.emit_changed();
+                OK
+            }
+            Err(_) => FAILED,
+        }
+    }
+
+    /// Creates a new `DijkstraMap` with the graph stored in the resource.
+    ///
+    /// The new map has no computed paths, and an empty undo/redo history.
+    ///
+    /// # Errors
+    ///
+    /// This function returns `null` if the resource is inconsistent : arrays
+    /// of different sizes, duplicate point IDs, or connections, disabled
+    /// points or positions referring to points that do not exist.
+    ///
+    /// # Example
+    /// ```gdscript
+    /// var dijkstra_map = DijkstraMap.new()
+    /// var grid = dijkstra_map.add_square_grid(Rect2(0, 0, 3, 3))
+    /// dijkstra_map.disable_point(grid[Vector2(1, 1)])
+    /// var resource = DijkstraMapResource.new()
+    /// resource.store(dijkstra_map)
+    /// var copy = resource.instantiate()
+    /// assert_true(copy.has_connection(grid[Vector2(0, 0)], grid[Vector2(1, 0)]))
+    /// assert_true(copy.is_point_disabled(grid[Vector2(1, 1)]))
+    /// assert_eq(copy.get_point_position(grid[Vector2(2, 1)]), Vector2(2, 1))
+    /// resource.point_ids = PoolIntArray([0])
+    /// assert_eq(resource.instantiate(), null)
+    /// ```
+    #[method]
+    pub fn instantiate(&self) -> Variant {
+        match self.build() {
+            Ok(interface) => Instance::emplace(interface).owned_to_variant(),
+            Err(message) => {
+                godot_error!("Invalid DijkstraMapResource : {}", message);
+                Variant::nil()
+            }
+        }
+    }
+}
+
+impl GraphResource {
+    /// Builds the `DijkstraMap` stored in the resource.
+    fn build(&self) -> Result<Interface, &'static str> {
+        let point_ids = self.point_ids.read();
+        let terrains = self.terrains.read();
+        if !terrains.is_empty() && terrains.len() != point_ids.len() {
+            return Err("'terrains' must be empty or have the same size as 'point_ids'");
+        }
+        let points: Vec<(PointId, TerrainType)> = point_ids
+            .iter()
+            .enumerate()
+            .map(|(index, &id)| {
+                let terrain = terrains.get(index).copied().unwrap_or(-1);
+                (PointId(id), TerrainType::from(terrain))
+            })
+            .collect();
+        let mut dijkstra = DijkstraMap::new();
+        dijkstra
+            .add_points(&points)
+            .map_err(|_| "'point_ids' contains duplicate IDs")?;
+
+        let sources = self.connection_sources.read();
+        let targets = self.connection_targets.read();
+        let weights = self.connection_weights.read();
+        if sources.len() != targets.len() || (!weights.is_empty() && weights.len() != sources.len())
+        {
+            return Err("the connection arrays must have the same size");
+        }
+        let connections: Vec<(PointId, PointId, Weight)> = sources
+            .iter()
+            .zip(targets.iter())
+            .enumerate()
+            .map(|(index, (&source, &target))| {
+                let weight = weights.get(index).copied().unwrap_or(1.0);
+                (PointId(source), PointId(target), Weight(weight))
+            })
+            .collect();
+        dijkstra
+            .add_connections(&connections, Some(false))
+            .map_err(|_| "a connection refers to a point that does not exist")?;
+
+        for &point in self.disabled_points.read().iter() {
+            dijkstra
+                .disable_point(PointId(point))
+                .map_err(|_| "a disabled point does not exist")?;
+        }
+
+        for (point, position) in self.positions.iter() {
+            let point = point
+                .try_to::<i32>()
+                .map(PointId)
+                .map_err(|_| "keys of 'positions' must be point IDs")?;
+            let (position, is_3d) = variant_to_position(&position)
+                .ok_or("values of 'positions' must be Vector2 or Vector3")?;
+            let set = if is_3d {
+                dijkstra.set_point_position_3d(point, position)
+            } else {
+                dijkstra.set_point_position(point, Some(position))
+            };
+            set.map_err(|_| "a position refers to a point that does not exist")?;
+        }
+
+        dijkstra.clear_history();
+        Ok(Interface::from_dijkstra_map(dijkstra))
+    }
+}
@@ -0,0 +1,261 @@
+use super::{Cost, DijkstraMap, FnvHashMap, PointId, TerrainType, Weight};
+
+/// Marks the absence of a next hop in `AllPairsPaths::next_hops`.
+const NO_HOP: u32 = u32::MAX;
+
+/// Shortest paths between every pair of points of a [`DijkstraMap`].
+///
+/// This is created via
+/// [`recalculate_all_pairs`](DijkstraMap::recalculate_all_pairs), and stored
+/// in the map until the graph is modified.
+///
+/// Costs are the same as the ones computed by
+/// [`recalculate`](DijkstraMap::recalculate) with
+/// [`InputIsOrigin`](crate::Read::InputIsOrigin).
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllPairsPaths {
+    /// Map a point to its row/column in the matrices.
+    index_of: FnvHashMap<PointId, usize>,
+    /// Inverse of `index_of`.
+    ids: Vec<PointId>,
+    /// `costs[source * n + target]` is the cost of the shortest path from
+    /// `source` to `target`.
+    costs: Vec<f32>,
+    /// `next_hops[source * n + target]` is the index of the point after
+    /// `source` on the shortest path to `target`.
+    next_hops: Vec<u32>,
+}
+
+impl AllPairsPaths {
+    /// Returns the cost of the shortest path from `source` to `target`.
+    ///
+    /// If there is no path, the cost is [`INFINITY`](Cost::infinity).
+    ///
+    /// Returns [`None`] if one of the points was not in the map when the
+    /// paths were computed.
+    pub fn distance(&self, source: PointId, target: PointId) -> Option<Cost> {
+        let cell = self.cell(source, target)?;
+        Some(Cost(self.costs[cell]))
+    }
+
+    /// Returns the point after `source` on the shortest path to `target`.
+    ///
+    /// If `source` and `target` are the same point, returns `target`. If there
+    /// is no path, or one of the points was not in the map when the paths
+    /// were computed, returns [`None`].
+    pub fn next_hop(&self, source: PointId, target: PointId) -> Option<PointId> {
+        let cell = self.cell(source, target)?;
+        match self.next_hops[cell] {
+            NO_HOP => None,
+            hop => Some(self.ids[hop as usize]),
+        }
+    }
+
+    /// Index of the `(source, target)` pair in the matrices.
+    fn cell(&self, source: PointId, target: PointId) -> Option<usize> {
+        let source = self.index_of.get(&source)?;
+        let target = self.index_of.get(&target)?;
+        Some(source * self.ids.len() + target)
+    }
+}
+
+impl DijkstraMap {
+    /// Computes shortest paths between every pair of points, using the
+    /// [Floyd–Warshall algorithm](https://en.wikipedia.org/wiki/Floyd–Warshall_algorithm).
+    ///
+    /// The result can then be queried with [`distance`](DijkstraMap::distance)
+    /// and [`next_hop`](DijkstraMap::next_hop), until the graph is modified
+    /// by [`connect_points`](DijkstraMap::connect_points),
+    /// [`remove_point`](DijkstraMap::remove_point),
+    /// [`disable_point`](DijkstraMap::disable_point)...
+    ///
+    /// This takes `O(n³)` time and `O(n²)` memory, so it is only meant for
+    /// small graphs (up to a few hundred points).
+    ///
+    /// # Parameters
+    ///
+    /// - `terrain_weights` : Specifies weights for terrain types, in the same
+    ///   way as in [`recalculate`](DijkstraMap::recalculate).
+    pub fn recalculate_all_pairs(&mut self, terrain_weights: FnvHashMap<TerrainType, Weight>) {
+        let mut ids: Vec<PointId> = self.points.keys().copied().collect();
+        ids.sort_unstable();
+        let n = ids.len();
+        let index_of: FnvHashMap<PointId, usize> =
+            ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+
+        let mut costs = vec![f32::INFINITY; n * n];
+        let mut next_hops = vec![NO_HOP; n * n];
+        for (source, id) in ids.iter().enumerate() {
+            costs[source * n + source] = 0.0;
+            next_hops[source * n + source] = source as u32;
+
+            let info = &self.points[id];
+            // same weights as in `recalculate`
+            let weight_of_source = match info.terrain_type {
+                TerrainType::DefaultTerrain => Weight(1.0),
+                x => *terrain_weights.get(&x).unwrap_or(&Weight::infinity()),
+            };
+            for (nbr, &weight) in info.connections.iter() {
+                if self.disabled_points.contains(nbr) || nbr == id {
+                    continue;
+                }
+                let weight_of_target = *terrain_weights
+                    .get(&self.points[nbr].terrain_type)
+                    .unwrap_or(&Weight(1.0));
+                let Weight(cost) = weight * Weight(0.5) * (weight_of_source + weight_of_target);
+                let target = index_of[nbr];
+                if cost < costs[source * n + target] {
+                    costs[source * n + target] = cost;
+                    next_hops[source * n + target] = target as u32;
+                }
+            }
+        }
+
+        for k in 0..n {
+            for i in 0..n {
+                let cost_to_k = costs[i * n + k];
+                if cost_to_k == f32::INFINITY {
+                    continue;
+                }
+                for j in 0..n {
+                    let cost = cost_to_k + costs[k * n + j];
+                    if cost < costs[i * n + j] {
+                        costs[i * n + j] = cost;
+                        next_hops[i * n + j] = next_hops[i * n + k];
+                    }
+                }
+            }
+        }
+
+        self.all_pairs = Some(AllPairsPaths {
+            index_of,
+            ids,
+            costs,
+            next_hops,
+        });
+    }
+
+    /// Returns the paths computed by
+    /// [`recalculate_all_pairs`](DijkstraMap::recalculate_all_pairs), or
+    /// [`None`] if they were invalidated by a modification of the graph.
+    pub fn get_all_pairs_paths(&self) -> Option<&AllPairsPaths> {
+        self.all_pairs.as_ref()
+    }
+
+    /// Returns the cost of the shortest path from `source` to `target`, as
+    /// computed by [`recalculate_all_pairs`](DijkstraMap::recalculate_all_pairs).
+    ///
+    /// See [`AllPairsPaths::distance`]. Also returns [`None`] if the paths
+    /// are not computed, or were invalidated.
+    pub fn distance(&self, source: PointId, target: PointId) -> Option<Cost> {
+        self.all_pairs.as_ref()?.distance(source, target)
+    }
+
+    /// Returns the next point on the shortest path from `source` to `target`,
+    /// as computed by
+    /// [`recalculate_all_pairs`](DijkstraMap::recalculate_all_pairs).
+    ///
+    /// See [`AllPairsPaths::next_hop`]. Also returns [`None`] if the paths
+    /// are not computed, or were invalidated.
+    pub fn next_hop(&self, source: PointId, target: PointId) -> Option<PointId> {
+        self.all_pairs.as_ref()?.next_hop(source, target)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Read;
+    use fnv::FnvHashSet;
+
+    const TERRAIN: TerrainType = TerrainType::DefaultTerrain;
+
+    /// Creates a new `DijkstraMap` with the connections :
+    ///
+    /// ```text
+    /// 0 -->₁ 1 -->₁ 2
+    ///  \           ^
+    ///   ---->₅ ---/
+    /// ```
+    fn setup() -> DijkstraMap {
+        let mut d = DijkstraMap::new();
+        for i in 0..3 {
+            d.add_point(PointId(i), TERRAIN).unwrap();
+        }
+        d.connect_points(PointId(0), PointId(1), None, Some(false))
+            .unwrap();
+        d.connect_points(PointId(1), PointId(2), None, Some(false))
+            .unwrap();
+        d.connect_points(PointId(0), PointId(2), Some(Weight(5.0)), Some(false))
+            .unwrap();
+        d.recalculate_all_pairs(FnvHashMap::default());
+        d
+    }
+
+    #[test]
+    fn distances_and_next_hops() {
+        let d = setup();
+        assert_eq!(d.distance(PointId(0), PointId(2)), Some(Cost(2.0)));
+        assert_eq!(d.next_hop(PointId(0), PointId(2)), Some(PointId(1)));
+        assert_eq!(d.distance(PointId(1), PointId(1)), Some(Cost(0.0)));
+        assert_eq!(d.next_hop(PointId(1), PointId(1)), Some(PointId(1)));
+        assert_eq!(d.distance(PointId(2), PointId(0)), Some(Cost::infinity()));
+        assert_eq!(d.next_hop(PointId(2), PointId(0)), None);
+        assert_eq!(d.distance(PointId(0), PointId(3)), None);
+    }
+
+    #[test]
+    fn modifications_invalidate_paths() {
+        let mut d = setup();
+        d.disable_point(PointId(1)).unwrap();
+        assert_eq!(d.get_all_pairs_paths(), None);
+        d.recalculate_all_pairs(FnvHashMap::default());
+        assert_eq!(d.distance(PointId(0), PointId(2)), Some(Cost(5.0)));
+        assert_eq!(d.next_hop(PointId(0), PointId(2)), Some(PointId(2)));
+
+        let mut d = setup();
+        d.remove_connection(PointId(1), PointId(2), None).unwrap();
+        assert_eq!(d.distance(PointId(0), PointId(2)), None);
+
+        let mut d = setup();
+        d.remove_point(PointId(1));
+        assert_eq!(d.distance(PointId(0), PointId(2)), None);
+
+        let mut d = setup();
+        d.connect_points(PointId(2), PointId(0), None, None)
+            .unwrap();
+        assert_eq!(d.distance(PointId(0), PointId(2)), None);
+    }
+
+    #[test]
+    fn same_costs_as_recalculate() {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_square_grid(4, 3, None, TerrainType::Terrain(1), None, Some(Weight(1.5)));
+        for (i, id) in grid.values().enumerate() {
+            if i % 3 == 0 {
+                d.set_terrain_for_point(*id, TerrainType::Terrain(2))
+                    .unwrap();
+            }
+        }
+        let mut terrain_weights = FnvHashMap::default();
+        terrain_weights.insert(TerrainType::Terrain(1), Weight(1.0));
+        terrain_weights.insert(TerrainType::Terrain(2), Weight(3.0));
+        d.recalculate_all_pairs(terrain_weights.clone());
+        let paths = d.get_all_pairs_paths().unwrap().clone();
+        for &source in grid.values() {
+            d.recalculate(
+                &[source],
+                Some(Read::InputIsOrigin),
+                None,
+                Vec::new(),
+                terrain_weights.clone(),
+                FnvHashSet::default(),
+            );
+            for &target in grid.values() {
+                let expected = d.get_cost_at_point(target);
+                let Cost(cost) = paths.distance(source, target).unwrap();
+                assert!((cost - expected.0).abs() < 1e-4);
+            }
+        }
+    }
+}
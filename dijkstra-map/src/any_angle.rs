@@ -0,0 +1,453 @@
+use super::{Cost, DijkstraMap, FnvHashMap, PointId, QueuePriority, TerrainType, Weight};
+use euclid::Vector2D;
+use fnv::FnvHashSet;
+use priority_queue::PriorityQueue;
+
+/// Position of a point on a square grid.
+type GridPosition = Vector2D<i32, i32>;
+
+/// Geometry of a square grid created by
+/// [`add_square_grid`](DijkstraMap::add_square_grid), used to test line of
+/// sight between its points.
+struct Grid<'a> {
+    map: &'a DijkstraMap,
+    /// Map positions to points, as returned by `add_square_grid`.
+    grid: &'a FnvHashMap<GridPosition, PointId>,
+    /// Map points to positions (the reverse of `grid`).
+    positions: FnvHashMap<PointId, GridPosition>,
+    terrain_weights: &'a FnvHashMap<TerrainType, Weight>,
+}
+
+impl<'a> Grid<'a> {
+    fn new(
+        map: &'a DijkstraMap,
+        grid: &'a FnvHashMap<GridPosition, PointId>,
+        terrain_weights: &'a FnvHashMap<TerrainType, Weight>,
+    ) -> Self {
+        Self {
+            map,
+            grid,
+            positions: grid.iter().map(|(&pos, &id)| (id, pos)).collect(),
+            terrain_weights,
+        }
+    }
+
+    /// Weight of the cell at `position`, or [`None`] if it can not be walked
+    /// on (no point, disabled point or infinite weight).
+    ///
+    /// Like in [`recalculate`](DijkstraMap::recalculate), the default
+    /// terrain has a weight of `1.0`, and terrains missing from
+    /// `terrain_weights` are impassable.
+    fn cell_weight(&self, position: GridPosition) -> Option<Weight> {
+        let point = *self.grid.get(&position)?;
+        if self.map.disabled_points.contains(&point) {
+            return None;
+        }
+        let weight = match self.map.get_terrain_for_point(point)? {
+            TerrainType::DefaultTerrain => Weight(1.0),
+            terrain => *self.terrain_weights.get(&terrain)?,
+        };
+        if weight.0.is_finite() {
+            Some(weight)
+        } else {
+            None
+        }
+    }
+
+    /// Cost of walking in a straight line from `source` to `target` : its
+    /// length times the largest weight of the cells it crosses.
+    ///
+    /// Returns [`None`] if there is no line of sight between them.
+    fn segment_cost(&self, source: PointId, target: PointId) -> Option<Cost> {
+        let source = *self.positions.get(&source)?;
+        let target = *self.positions.get(&target)?;
+        let mut max_weight = Weight(0.0);
+        for cell in supercover(source, target) {
+            let weight = self.cell_weight(cell)?;
+            if weight.0 > max_weight.0 {
+                max_weight = weight;
+            }
+        }
+        let delta = (target - source).to_f32();
+        Some(Cost(delta.length()) * max_weight)
+    }
+
+    /// Lower bound of the cost from `point` to `target`, for A*.
+    fn heuristic(&self, point: PointId, target: PointId, min_weight: Weight) -> Cost {
+        match (self.positions.get(&point), self.positions.get(&target)) {
+            (Some(&point), Some(&target)) => Cost((target - point).to_f32().length()) * min_weight,
+            _ => Cost(0.0),
+        }
+    }
+}
+
+/// Returns the cells crossed by the segment between the centers of `source`
+/// and `target`, in order.
+///
+/// If the segment goes exactly through the corner of a cell, both cells
+/// sharing this corner are included, so that paths never cut corners.
+fn supercover(source: GridPosition, target: GridPosition) -> Vec<GridPosition> {
+    let (nx, ny) = ((target.x - source.x).abs(), (target.y - source.y).abs());
+    let step_x = Vector2D::new((target.x - source.x).signum(), 0);
+    let step_y = Vector2D::new(0, (target.y - source.y).signum());
+    let mut cell = source;
+    let mut cells = vec![cell];
+    let (mut ix, mut iy) = (0, 0);
+    while ix < nx || iy < ny {
+        // compare the distances to the next vertical and horizontal edges
+        let decision = (1 + 2 * ix) * ny - (1 + 2 * iy) * nx;
+        if decision == 0 {
+            cells.push(cell + step_x);
+            cells.push(cell + step_y);
+            cell += step_x + step_y;
+            ix += 1;
+            iy += 1;
+        } else if decision < 0 {
+            cell += step_x;
+            ix += 1;
+        } else {
+            cell += step_y;
+            iy += 1;
+        }
+        cells.push(cell);
+    }
+    cells
+}
+
+impl DijkstraMap {
+    /// Returns [`true`] if a straight line between the centers of the cells
+    /// `source` and `target` only crosses walkable cells of `grid`.
+    ///
+    /// `grid` is the square grid returned by
+    /// [`add_square_grid`](DijkstraMap::add_square_grid). A cell is walkable
+    /// if its point is enabled and its terrain has a finite weight in
+    /// `terrain_weights` (the default terrain has a weight of `1.0`).
+    pub fn has_line_of_sight(
+        &self,
+        grid: &FnvHashMap<Vector2D<i32, i32>, PointId>,
+        source: Vector2D<i32, i32>,
+        target: Vector2D<i32, i32>,
+        terrain_weights: &FnvHashMap<TerrainType, Weight>,
+    ) -> bool {
+        let grid = Grid::new(self, grid, terrain_weights);
+        supercover(source, target)
+            .into_iter()
+            .all(|cell| grid.cell_weight(cell).is_some())
+    }
+
+    /// Removes the intermediate points of `path` that can be skipped by
+    /// walking in a straight line (also called _string pulling_).
+    ///
+    /// `path` is a sequence of points of the square `grid` returned by
+    /// [`add_square_grid`](DijkstraMap::add_square_grid), for example a
+    /// shortest path from
+    /// [`get_shortest_path_from_point`](DijkstraMap::get_shortest_path_from_point)
+    /// with its starting point. A point is removed if there is a
+    /// [line of sight](DijkstraMap::has_line_of_sight) between the points
+    /// around it. Terrain weights only matter to decide which cells are
+    /// walkable : the smoothed path may go through more expensive cells than
+    /// the original one.
+    ///
+    /// The first and last points of `path` are always kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, PointId, TerrainType};
+    /// use euclid::Vector2D;
+    /// use fnv::FnvHashMap;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let grid = dijkstra_map.add_square_grid(4, 4, None, TerrainType::DefaultTerrain, None, None);
+    /// let cell = |x, y| grid[&Vector2D::from((x, y))];
+    /// // staircase path from (0, 0) to (3, 3)
+    /// let path = [
+    ///     cell(0, 0), cell(1, 0), cell(1, 1), cell(2, 1),
+    ///     cell(2, 2), cell(3, 2), cell(3, 3),
+    /// ];
+    /// let smoothed = dijkstra_map.smooth_path(&path, &grid, &FnvHashMap::default());
+    /// assert_eq!(smoothed, [cell(0, 0), cell(3, 3)]);
+    /// ```
+    pub fn smooth_path(
+        &self,
+        path: &[PointId],
+        grid: &FnvHashMap<Vector2D<i32, i32>, PointId>,
+        terrain_weights: &FnvHashMap<TerrainType, Weight>,
+    ) -> Vec<PointId> {
+        let grid = Grid::new(self, grid, terrain_weights);
+        let mut smoothed = Vec::new();
+        let mut anchor = match path.first() {
+            Some(&first) => first,
+            None => return smoothed,
+        };
+        smoothed.push(anchor);
+        for window in path.windows(2).skip(1) {
+            let (previous, point) = (window[0], window[1]);
+            if grid.segment_cost(anchor, point).is_none() {
+                anchor = previous;
+                smoothed.push(anchor);
+            }
+        }
+        if path.len() > 1 {
+            smoothed.push(path[path.len() - 1]);
+        }
+        smoothed
+    }
+
+    /// Finds an any-angle path from `source` to `target` on a square grid,
+    /// using the [Theta*](https://en.wikipedia.org/wiki/Theta*) algorithm.
+    ///
+    /// Like A*, the search follows the connections of the map, but a point
+    /// can be reached in a straight line from any point with a
+    /// [line of sight](DijkstraMap::has_line_of_sight) to it, instead of
+    /// only from its neighbours. The cost of a straight line is its length
+    /// times the largest weight of the cells it crosses : the weights of the
+    /// connections are ignored.
+    ///
+    /// Returns the turning points of the path, starting with `source` and
+    /// ending with `target`, or [`None`] if there is no path. Points of
+    /// `grid` that are no longer in the map are ignored, and there is no
+    /// path if `source` or `target` is one of them, or is not in `grid`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, PointId, TerrainType};
+    /// use euclid::Vector2D;
+    /// use fnv::FnvHashMap;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let grid = dijkstra_map.add_square_grid(5, 3, None, TerrainType::DefaultTerrain, None, None);
+    /// let cell = |x, y| grid[&Vector2D::from((x, y))];
+    /// // wall in the middle of the grid, leaving a gap at the bottom
+    /// dijkstra_map.disable_point(cell(2, 0)).unwrap();
+    /// dijkstra_map.disable_point(cell(2, 1)).unwrap();
+    /// let path = dijkstra_map
+    ///     .find_any_angle_path(cell(0, 0), cell(4, 0), &grid, &FnvHashMap::default())
+    ///     .unwrap();
+    /// assert_eq!(path.first(), Some(&cell(0, 0)));
+    /// assert_eq!(path.last(), Some(&cell(4, 0)));
+    /// assert!(path.len() < 5);
+    /// ```
+    pub fn find_any_angle_path(
+        &self,
+        source: PointId,
+        target: PointId,
+        grid: &FnvHashMap<Vector2D<i32, i32>, PointId>,
+        terrain_weights: &FnvHashMap<TerrainType, Weight>,
+    ) -> Option<Vec<PointId>> {
+        let grid = Grid::new(self, grid, terrain_weights);
+        grid.positions.get(&source)?;
+        grid.positions.get(&target)?;
+        if !self.has_point(source) || !self.has_point(target) {
+            return None;
+        }
+        let min_weight = terrain_weights
+            .values()
+            .copied()
+            .fold(
+                Weight(1.0),
+                |min, weight| {
+                    if weight.0 < min.0 {
+                        weight
+                    } else {
+                        min
+                    }
+                },
+            );
+
+        let mut costs = FnvHashMap::<PointId, Cost>::default();
+        let mut parents = FnvHashMap::<PointId, PointId>::default();
+        let mut closed = FnvHashSet::<PointId>::default();
+        let mut open_queue = PriorityQueue::<PointId, QueuePriority>::new();
+        costs.insert(source, Cost(0.0));
+        parents.insert(source, source);
+        open_queue.push(
+            source,
+            QueuePriority {
+                id: source,
+                cost: grid.heuristic(source, target, min_weight),
+            },
+        );
+
+        while let Some((point, _)) = open_queue.pop() {
+            if point == target {
+                let mut path = vec![target];
+                let mut point = target;
+                while point != source {
+                    point = parents[&point];
+                    path.push(point);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            closed.insert(point);
+            let parent = parents[&point];
+            let connections = match self.points.get(&point) {
+                Some(info) => &info.connections,
+                None => continue,
+            };
+            for &neighbour in connections.keys() {
+                if closed.contains(&neighbour)
+                    || self.disabled_points.contains(&neighbour)
+                    || !self.has_point(neighbour)
+                {
+                    continue;
+                }
+                // try to skip `point` by going straight from its parent
+                let candidate = match grid.segment_cost(parent, neighbour) {
+                    Some(cost) => Some((parent, costs[&parent] + cost)),
+                    None => grid
+                        .segment_cost(point, neighbour)
+                        .map(|cost| (point, costs[&point] + cost)),
+                };
+                let (new_parent, cost) = match candidate {
+                    Some(candidate) => candidate,
+                    None => continue,
+                };
+                if cost
+                    < costs
+                        .get(&neighbour)
+                        .copied()
+                        .unwrap_or_else(Cost::infinity)
+                {
+                    costs.insert(neighbour, cost);
+                    parents.insert(neighbour, new_parent);
+                    open_queue.push_increase(
+                        neighbour,
+                        QueuePriority {
+                            id: neighbour,
+                            cost: cost + grid.heuristic(neighbour, target, min_weight),
+                        },
+                    );
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pos(x: i32, y: i32) -> GridPosition {
+        Vector2D::from((x, y))
+    }
+
+    #[test]
+    fn supercover_includes_both_cells_at_corners() {
+        assert_eq!(
+            supercover(pos(0, 0), pos(1, 1)),
+            [pos(0, 0), pos(1, 0), pos(0, 1), pos(1, 1)]
+        );
+        assert_eq!(supercover(pos(2, 0), pos(0, 1)).len(), 4);
+        assert_eq!(supercover(pos(0, 0), pos(0, 0)), [pos(0, 0)]);
+    }
+
+    #[test]
+    fn line_of_sight_is_blocked_by_walls_and_impassable_terrain() {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_square_grid(5, 1, None, TerrainType::DefaultTerrain, None, None);
+        let terrain_weights = FnvHashMap::default();
+        assert!(d.has_line_of_sight(&grid, pos(0, 0), pos(4, 0), &terrain_weights));
+        d.set_terrain_for_point(grid[&pos(2, 0)], TerrainType::Terrain(1))
+            .unwrap();
+        assert!(!d.has_line_of_sight(&grid, pos(0, 0), pos(4, 0), &terrain_weights));
+        let mut terrain_weights = FnvHashMap::default();
+        terrain_weights.insert(TerrainType::Terrain(1), Weight(2.0));
+        assert!(d.has_line_of_sight(&grid, pos(0, 0), pos(4, 0), &terrain_weights));
+        d.disable_point(grid[&pos(2, 0)]).unwrap();
+        assert!(!d.has_line_of_sight(&grid, pos(0, 0), pos(4, 0), &terrain_weights));
+    }
+
+    #[test]
+    fn smoothing_keeps_corners_around_walls() {
+        // #: disabled
+        //
+        // ...
+        // .#.
+        // ...
+        let mut d = DijkstraMap::new();
+        let grid = d.add_square_grid(3, 3, None, TerrainType::DefaultTerrain, None, None);
+        d.disable_point(grid[&pos(1, 1)]).unwrap();
+        let path: Vec<_> = [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]
+            .iter()
+            .map(|&(x, y)| grid[&pos(x, y)])
+            .collect();
+        let smoothed = d.smooth_path(&path, &grid, &FnvHashMap::default());
+        assert_eq!(smoothed, [path[0], path[2], path[4]]);
+        assert!(d.smooth_path(&[], &grid, &FnvHashMap::default()).is_empty());
+    }
+
+    #[test]
+    fn any_angle_path_goes_around_walls() {
+        // ......
+        // .####.
+        // ......
+        let mut d = DijkstraMap::new();
+        let grid = d.add_square_grid(6, 3, None, TerrainType::DefaultTerrain, None, None);
+        for x in 1..5 {
+            d.disable_point(grid[&pos(x, 1)]).unwrap();
+        }
+        let terrain_weights = FnvHashMap::default();
+        let path = d
+            .find_any_angle_path(grid[&pos(1, 0)], grid[&pos(1, 2)], &grid, &terrain_weights)
+            .unwrap();
+        assert_eq!(
+            path,
+            [
+                grid[&pos(1, 0)],
+                grid[&pos(0, 0)],
+                grid[&pos(0, 2)],
+                grid[&pos(1, 2)]
+            ]
+        );
+
+        d.disable_point(grid[&pos(0, 1)]).unwrap();
+        d.disable_point(grid[&pos(5, 1)]).unwrap();
+        assert_eq!(
+            d.find_any_angle_path(grid[&pos(1, 0)], grid[&pos(1, 2)], &grid, &terrain_weights),
+            None
+        );
+    }
+
+    #[test]
+    fn any_angle_path_ignores_removed_points() {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_square_grid(3, 1, None, TerrainType::DefaultTerrain, None, None);
+        let terrain_weights = FnvHashMap::default();
+        d.remove_point(grid[&pos(2, 0)]);
+        assert_eq!(
+            d.find_any_angle_path(grid[&pos(0, 0)], grid[&pos(2, 0)], &grid, &terrain_weights),
+            None
+        );
+        assert_eq!(
+            d.find_any_angle_path(grid[&pos(2, 0)], grid[&pos(0, 0)], &grid, &terrain_weights),
+            None
+        );
+        assert_eq!(
+            d.find_any_angle_path(grid[&pos(0, 0)], grid[&pos(1, 0)], &grid, &terrain_weights),
+            Some(vec![grid[&pos(0, 0)], grid[&pos(1, 0)]])
+        );
+    }
+
+    #[test]
+    fn any_angle_path_needs_points_of_the_grid() {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_square_grid(3, 1, None, TerrainType::DefaultTerrain, None, None);
+        let terrain_weights = FnvHashMap::default();
+        let outside = d.get_available_id(None);
+        d.add_point(outside, TerrainType::DefaultTerrain).unwrap();
+        d.connect_points(grid[&pos(2, 0)], outside, None, None)
+            .unwrap();
+        assert_eq!(
+            d.find_any_angle_path(grid[&pos(0, 0)], outside, &grid, &terrain_weights),
+            None
+        );
+        assert_eq!(
+            d.find_any_angle_path(outside, grid[&pos(0, 0)], &grid, &terrain_weights),
+            None
+        );
+    }
+}
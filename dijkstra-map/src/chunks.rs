@@ -0,0 +1,384 @@
+use super::history::Edit;
+use super::setters::PointNotFound;
+use super::{DijkstraMap, FnvHashMap, PointId, Position, SharedMap, TerrainType, Weight};
+use euclid::Vector2D;
+use std::sync::Arc;
+
+/// Coordinates of a cell of the persistent grid.
+type Cell = Vector2D<i32, i32>;
+
+/// Orthogonal neighbours of a cell.
+const ORTHOGONAL: [Cell; 4] = [
+    Cell::new(1, 0),
+    Cell::new(-1, 0),
+    Cell::new(0, 1),
+    Cell::new(0, -1),
+];
+
+/// Diagonal neighbours of a cell.
+const DIAGONAL: [Cell; 4] = [
+    Cell::new(1, 1),
+    Cell::new(-1, 1),
+    Cell::new(1, -1),
+    Cell::new(-1, -1),
+];
+
+/// Persistent square grid of a [`DijkstraMap`], built chunk by chunk with
+/// [`extend_square_grid`](DijkstraMap::extend_square_grid).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct GridCells {
+    /// Point of each cell.
+    points: SharedMap<Cell, PointId>,
+    /// Cell of each point.
+    cells: SharedMap<PointId, Cell>,
+}
+
+impl GridCells {
+    /// Sets or removes the cell of `point`, returning its previous one.
+    ///
+    /// `cell` must not already belong to another point.
+    pub(crate) fn set(&mut self, point: PointId, cell: Option<Cell>) -> Option<Cell> {
+        let previous = self.cells.remove(&point);
+        if let Some(previous) = previous {
+            self.points.remove(&previous);
+        }
+        if let Some(cell) = cell {
+            self.cells.insert(point, cell);
+            self.points.insert(cell, point);
+        }
+        previous
+    }
+}
+
+impl DijkstraMap {
+    /// Sets the cell of `point` in the persistent grid, recording the edit.
+    fn set_grid_cell(&mut self, point: PointId, cell: Option<Cell>) {
+        let previous = Arc::make_mut(&mut self.grid_cells).set(point, cell);
+        self.record_edit(|| Edit::SetGridCell {
+            point,
+            cell,
+            previous,
+        });
+    }
+
+    /// Extends the persistent square grid of the map with a rectangle of
+    /// connected points, connecting its borders to the neighbouring cells
+    /// that already exist.
+    ///
+    /// Unlike [`add_square_grid`](DijkstraMap::add_square_grid), which
+    /// creates independent grids, the grid built by successive calls is
+    /// remembered by the map : use
+    /// [`get_grid_point`](DijkstraMap::get_grid_point) and
+    /// [`get_grid_cell`](DijkstraMap::get_grid_cell) to convert between
+    /// cells and points, and
+    /// [`remove_grid_chunk`](DijkstraMap::remove_grid_chunk) to unload a
+    /// part of it. Grids created otherwise can be extended too, once added
+    /// to the persistent grid with
+    /// [`register_grid_cells`](DijkstraMap::register_grid_cells).
+    ///
+    /// # Parameters
+    ///
+    /// - `width` : Width of the rectangle.
+    /// - `height` : Height of the rectangle.
+    /// - `offset` : coordinates of the top-left cell of the rectangle.
+    /// - `default_terrain` : [`TerrainType`] of the new points.
+    /// - `orthogonal_cost` (default : `1.0`) : specifies cost of orthogonal
+    ///   connections (up, down, right and left). \
+    ///   If `orthogonal_cost` is [`INFINITY`] or [`NAN`], orthogonal
+    ///   connections are disabled.
+    /// - `diagonal_cost` (default : [`INFINITY`]) : specifies cost of
+    ///   diagonal connections. \
+    ///   If `diagonal_cost` is [`INFINITY`] or [`NAN`], diagonal connections
+    ///   are disabled.
+    ///
+    /// Cells of the rectangle that are already in the grid are left
+    /// untouched. The [position](DijkstraMap::get_point_position) of the new
+    /// points is set to their coordinates.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`HashMap`] where keys are the coordinates of the new cells
+    /// and values are their point IDs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// dijkstra_map.extend_square_grid(4, 4, Vector2D::new(0, 0), TerrainType::DefaultTerrain, None, None);
+    /// let chunk =
+    ///     dijkstra_map.extend_square_grid(4, 4, Vector2D::new(4, 0), TerrainType::DefaultTerrain, None, None);
+    /// // the new chunk is connected to the first one
+    /// let left = dijkstra_map.get_grid_point(Vector2D::new(3, 2)).unwrap();
+    /// assert!(dijkstra_map.has_connection(left, chunk[&Vector2D::new(4, 2)]));
+    /// assert!(dijkstra_map.has_connection(chunk[&Vector2D::new(4, 2)], left));
+    /// ```
+    ///
+    /// [`HashMap`]: FnvHashMap
+    /// [`INFINITY`]: Weight::infinity
+    /// [`NAN`]: f32::NAN
+    pub fn extend_square_grid(
+        &mut self,
+        width: usize,
+        height: usize,
+        offset: Vector2D<i32, i32>,
+        default_terrain: TerrainType,
+        orthogonal_cost: Option<Weight>,
+        diagonal_cost: Option<Weight>,
+    ) -> FnvHashMap<Cell, PointId> {
+        let mut id = self.get_available_id(None);
+        let mut new_cells = FnvHashMap::<Cell, PointId>::default();
+        for x in offset.x..offset.x + width as i32 {
+            for y in offset.y..offset.y + height as i32 {
+                let cell = Cell::new(x, y);
+                if self.grid_cells.points.contains_key(&cell) {
+                    continue;
+                }
+                id = self.get_available_id(Some(id));
+                self.add_point_replace(id, default_terrain);
+                let _ = self.set_point_position(id, Some(Position::new(x as f32, y as f32, 0.0)));
+                self.set_grid_cell(id, Some(cell));
+                new_cells.insert(cell, id);
+            }
+        }
+
+        let costs = [
+            (orthogonal_cost.unwrap_or(Weight(1.0)), &ORTHOGONAL),
+            (diagonal_cost.unwrap_or(Weight::infinity()), &DIAGONAL),
+        ];
+        for (&cell, &id_1) in new_cells.iter() {
+            for &(cost, offsets) in costs.iter().filter(|(cost, _)| *cost < Weight::infinity()) {
+                for &offs in offsets.iter() {
+                    let neighbour = cell + offs;
+                    if let Some(&id_2) = self.grid_cells.points.get(&neighbour) {
+                        // new neighbours connect back themselves
+                        let bidirectional = !new_cells.contains_key(&neighbour);
+                        // ignore error, we know it succeeded
+                        let _ = self.connect_points(id_1, id_2, Some(cost), Some(bidirectional));
+                    }
+                }
+            }
+        }
+        new_cells
+    }
+
+    /// Adds the points of an existing grid to the persistent grid, so that
+    /// [`extend_square_grid`](DijkstraMap::extend_square_grid) connects the
+    /// new chunks to it.
+    ///
+    /// `cells` maps coordinates to point IDs, like the grids returned by
+    /// [`add_square_grid`](DijkstraMap::add_square_grid) or
+    /// [`add_square_grid_with_ids`](DijkstraMap::add_square_grid_with_ids).
+    /// The connections of the points are left as they are. Cells and points
+    /// that are already in the persistent grid are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] without modifying the map if one of the points does
+    /// not exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let grid = dijkstra_map.add_square_grid(4, 4, None, TerrainType::DefaultTerrain, None, None);
+    /// dijkstra_map.register_grid_cells(&grid).unwrap();
+    /// let chunk =
+    ///     dijkstra_map.extend_square_grid(4, 4, Vector2D::new(4, 0), TerrainType::DefaultTerrain, None, None);
+    /// // the new chunk is connected to the existing grid
+    /// assert!(dijkstra_map.has_connection(grid[&Vector2D::new(3, 2)], chunk[&Vector2D::new(4, 2)]));
+    /// assert!(dijkstra_map.has_connection(chunk[&Vector2D::new(4, 2)], grid[&Vector2D::new(3, 2)]));
+    /// ```
+    pub fn register_grid_cells(
+        &mut self,
+        cells: &FnvHashMap<Cell, PointId>,
+    ) -> Result<(), PointNotFound> {
+        if cells.values().any(|&point| !self.has_point(point)) {
+            return Err(PointNotFound);
+        }
+        for (&cell, &point) in cells.iter() {
+            if self.grid_cells.points.contains_key(&cell)
+                || self.grid_cells.cells.contains_key(&point)
+            {
+                continue;
+            }
+            self.set_grid_cell(point, Some(cell));
+        }
+        Ok(())
+    }
+
+    /// Removes the points of the persistent grid in a rectangle, along with
+    /// their connections.
+    ///
+    /// # Parameters
+    ///
+    /// - `width` : Width of the rectangle.
+    /// - `height` : Height of the rectangle.
+    /// - `offset` : coordinates of the top-left cell of the rectangle.
+    ///
+    /// # Returns
+    ///
+    /// Returns the removed points, sorted by ID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// dijkstra_map.extend_square_grid(8, 4, Vector2D::new(0, 0), TerrainType::DefaultTerrain, None, None);
+    /// let removed = dijkstra_map.remove_grid_chunk(4, 4, Vector2D::new(4, 0));
+    /// assert_eq!(removed.len(), 16);
+    /// assert_eq!(dijkstra_map.get_grid_point(Vector2D::new(4, 0)), None);
+    /// assert!(dijkstra_map.get_grid_point(Vector2D::new(3, 0)).is_some());
+    /// ```
+    pub fn remove_grid_chunk(
+        &mut self,
+        width: usize,
+        height: usize,
+        offset: Vector2D<i32, i32>,
+    ) -> Vec<PointId> {
+        let mut removed = Vec::new();
+        for x in offset.x..offset.x + width as i32 {
+            for y in offset.y..offset.y + height as i32 {
+                if let Some(&point) = self.grid_cells.points.get(&Cell::new(x, y)) {
+                    // also removes the cell
+                    self.remove_point(point);
+                    removed.push(point);
+                }
+            }
+        }
+        removed.sort_unstable();
+        removed
+    }
+
+    /// Returns the point of `cell` in the persistent grid built with
+    /// [`extend_square_grid`](DijkstraMap::extend_square_grid), if any.
+    pub fn get_grid_point(&self, cell: Vector2D<i32, i32>) -> Option<PointId> {
+        self.grid_cells.points.get(&cell).copied()
+    }
+
+    /// Returns the cell of `point` in the persistent grid built with
+    /// [`extend_square_grid`](DijkstraMap::extend_square_grid), if any.
+    pub fn get_grid_cell(&self, point: PointId) -> Option<Vector2D<i32, i32>> {
+        self.grid_cells.cells.get(&point).copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TERRAIN: TerrainType = TerrainType::DefaultTerrain;
+
+    fn weight(d: &DijkstraMap, source: PointId, target: PointId) -> Option<Weight> {
+        d.points[&source].connections.get(&target).copied()
+    }
+
+    #[test]
+    fn chunks_are_stitched_together() {
+        let mut d = DijkstraMap::new();
+        let first = d.extend_square_grid(3, 3, Cell::new(-3, 0), TERRAIN, None, Some(Weight(1.5)));
+        let second = d.extend_square_grid(3, 3, Cell::new(0, 1), TERRAIN, None, Some(Weight(1.5)));
+        // no ID is reused
+        assert!(first
+            .values()
+            .all(|id| !second.values().any(|other| id == other)));
+        let a = first[&Cell::new(-1, 1)];
+        let b = second[&Cell::new(0, 1)];
+        let c = second[&Cell::new(0, 2)];
+        assert_eq!(weight(&d, a, b), Some(Weight(1.0)));
+        assert_eq!(weight(&d, b, a), Some(Weight(1.0)));
+        assert_eq!(weight(&d, c, a), Some(Weight(1.5)));
+        assert_eq!(d.get_grid_cell(c), Some(Cell::new(0, 2)));
+    }
+
+    #[test]
+    fn existing_cells_are_kept() {
+        let mut d = DijkstraMap::new();
+        let first = d.extend_square_grid(2, 2, Cell::new(0, 0), TERRAIN, None, None);
+        let second = d.extend_square_grid(3, 2, Cell::new(0, 0), TERRAIN, None, None);
+        assert_eq!(second.len(), 2);
+        assert_eq!(
+            d.get_grid_point(Cell::new(1, 1)),
+            Some(first[&Cell::new(1, 1)])
+        );
+        assert!(d.has_connection(first[&Cell::new(1, 1)], second[&Cell::new(2, 1)]));
+    }
+
+    #[test]
+    fn removed_chunks_can_be_reloaded() {
+        let mut d = DijkstraMap::new();
+        d.extend_square_grid(4, 2, Cell::new(0, 0), TERRAIN, None, None);
+        let removed = d.remove_grid_chunk(2, 2, Cell::new(2, 0));
+        assert_eq!(removed.len(), 4);
+        let edge = d.get_grid_point(Cell::new(1, 0)).unwrap();
+        assert!(removed.iter().all(|&point| !d.has_point(point)));
+        assert!(removed
+            .iter()
+            .all(|&point| d.get_grid_cell(point).is_none()));
+        assert!(!d.has_connection(edge, removed[0]));
+
+        let reloaded = d.extend_square_grid(2, 2, Cell::new(2, 0), TERRAIN, None, None);
+        assert!(d.has_connection(edge, reloaded[&Cell::new(2, 0)]));
+        assert!(d.has_connection(reloaded[&Cell::new(2, 0)], edge));
+    }
+
+    #[test]
+    fn registered_grids_are_extended() {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_square_grid(2, 2, Some((1, 1).into()), TERRAIN, None, None);
+        let chunk = d.extend_square_grid(1, 1, Cell::new(0, 0), TERRAIN, None, None);
+        assert_eq!(
+            d.register_grid_cells(&grid.iter().map(|(&cell, _)| (cell, PointId(100))).collect()),
+            Err(PointNotFound)
+        );
+        assert_eq!(d.get_grid_point(Cell::new(1, 1)), None);
+
+        // the cell (0, 0) is already taken by the chunk
+        let mut cells = grid.clone();
+        cells.insert(Cell::new(0, 0), grid[&Cell::new(1, 1)]);
+        d.register_grid_cells(&cells).unwrap();
+        assert_eq!(
+            d.get_grid_point(Cell::new(0, 0)),
+            Some(chunk[&Cell::new(0, 0)])
+        );
+        assert_eq!(
+            d.get_grid_cell(grid[&Cell::new(2, 2)]),
+            Some(Cell::new(2, 2))
+        );
+
+        let remote = grid[&Cell::new(2, 1)];
+        let new = d.extend_square_grid(1, 2, Cell::new(3, 1), TERRAIN, None, None);
+        assert!(d.has_connection(remote, new[&Cell::new(3, 1)]));
+        assert!(d.has_connection(new[&Cell::new(3, 1)], remote));
+        assert_eq!(d.remove_grid_chunk(2, 2, Cell::new(1, 1)).len(), 4);
+        assert!(!d.has_point(remote));
+    }
+
+    #[test]
+    fn grid_cells_are_undone() {
+        let mut d = DijkstraMap::new();
+        d.extend_square_grid(2, 2, Cell::new(0, 0), TERRAIN, None, None);
+        let original = d.clone();
+
+        d.begin_transaction().unwrap();
+        d.remove_grid_chunk(1, 2, Cell::new(1, 0));
+        d.extend_square_grid(2, 2, Cell::new(2, 0), TERRAIN, None, None);
+        d.commit_transaction().unwrap();
+        let modified = d.clone();
+
+        assert!(d.undo());
+        assert_eq!(d.grid_cells, original.grid_cells);
+        assert_eq!(d.points, original.points);
+        assert!(d.redo());
+        assert_eq!(d.grid_cells, modified.grid_cells);
+        assert_eq!(d.points, modified.points);
+    }
+}
@@ -0,0 +1,407 @@
+use super::{DijkstraMap, FnvHashMap, PointId, PointInfo};
+use std::collections::hash_map::Entry;
+
+/// Partition of the enabled points of a [`DijkstraMap`] into connected
+/// components.
+///
+/// This is created via the
+/// [`weakly_connected_components`](DijkstraMap::weakly_connected_components)
+/// and
+/// [`strongly_connected_components`](DijkstraMap::strongly_connected_components)
+/// methods.
+///
+/// Components are numbered from `0` to [`count`](Components::count) (excluded).
+/// Disabled points do not belong to any component.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Components {
+    /// Map a point to the ID of its component.
+    component_of: FnvHashMap<PointId, usize>,
+    /// Number of components.
+    count: usize,
+}
+
+impl Components {
+    /// Returns the number of components.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the ID of the component containing `point`, or [`None`] if
+    /// `point` is disabled or does not exist.
+    pub fn component_of(&self, point: PointId) -> Option<usize> {
+        self.component_of.get(&point).copied()
+    }
+
+    /// Returns an iterator over every point and the ID of its component.
+    pub fn iter(&self) -> impl Iterator<Item = (PointId, usize)> + '_ {
+        self.component_of
+            .iter()
+            .map(|(&point, &component)| (point, component))
+    }
+}
+
+/// Precomputed reachability between all the points of a [`DijkstraMap`].
+///
+/// This is created via the
+/// [`compute_reachability`](DijkstraMap::compute_reachability) method, and
+/// answers [`is_reachable`](Reachability::is_reachable) queries in constant
+/// time.
+///
+/// # Note
+///
+/// Memory usage is quadratic in the number of strongly connected components.
+/// For graphs where most connections are bidirectional, this is a small
+/// fraction of the number of points.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Reachability {
+    /// Strongly connected components of the map.
+    components: Components,
+    /// Number of [`u64`] in a row of `reachable`.
+    row_length: usize,
+    /// For each component `c`, bitset of the components reachable from `c`.
+    reachable: Vec<u64>,
+}
+
+impl Reachability {
+    /// Returns [`true`] if there is a path from `source` to `target` that only
+    /// goes through enabled points.
+    ///
+    /// Returns [`false`] if one of the points is disabled or does not exist.
+    pub fn is_reachable(&self, source: PointId, target: PointId) -> bool {
+        match (
+            self.components.component_of(source),
+            self.components.component_of(target),
+        ) {
+            (Some(source), Some(target)) => {
+                let word = self.reachable[source * self.row_length + target / 64];
+                word & (1 << (target % 64)) != 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the strongly connected components used for the computation.
+    pub fn strongly_connected_components(&self) -> &Components {
+        &self.components
+    }
+}
+
+impl DijkstraMap {
+    /// Enabled points reachable in a single step from `point`.
+    ///
+    /// If `reverse` is [`true`], `reverse_connections` are used instead.
+    pub(crate) fn enabled_neighbours(&self, point: PointId, reverse: bool) -> Vec<PointId> {
+        match self.points.get(&point) {
+            Some(PointInfo {
+                connections,
+                reverse_connections,
+                ..
+            }) => {
+                let neighbours = if reverse {
+                    reverse_connections
+                } else {
+                    connections
+                };
+                neighbours
+                    .keys()
+                    .filter(|nbr| !self.disabled_points.contains(nbr))
+                    .copied()
+                    .collect()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Enabled points, sorted by ID so that components are numbered
+    /// deterministically.
+    pub(crate) fn sorted_enabled_points(&self) -> Vec<PointId> {
+        let mut points: Vec<PointId> = self
+            .points
+            .keys()
+            .filter(|point| !self.disabled_points.contains(point))
+            .copied()
+            .collect();
+        points.sort_unstable();
+        points
+    }
+
+    /// Computes the weakly connected components of the map.
+    ///
+    /// Two enabled points are in the same component if they are linked by a
+    /// chain of connections, ignoring their direction. Disabled points are
+    /// skipped.
+    pub fn weakly_connected_components(&self) -> Components {
+        let mut components = Components::default();
+        let mut stack = Vec::new();
+        for root in self.sorted_enabled_points() {
+            if components.component_of.contains_key(&root) {
+                continue;
+            }
+            let component = components.count;
+            components.count += 1;
+            components.component_of.insert(root, component);
+            stack.push(root);
+            while let Some(point) = stack.pop() {
+                let outgoing = self.enabled_neighbours(point, false);
+                let incoming = self.enabled_neighbours(point, true);
+                for nbr in outgoing.into_iter().chain(incoming) {
+                    if let Entry::Vacant(entry) = components.component_of.entry(nbr) {
+                        entry.insert(component);
+                        stack.push(nbr);
+                    }
+                }
+            }
+        }
+        components
+    }
+
+    /// Computes the strongly connected components of the map.
+    ///
+    /// Two enabled points are in the same component if each one can be
+    /// reached from the other, following the direction of connections and
+    /// only going through enabled points.
+    ///
+    /// Components are numbered in reverse topological order : if a point in
+    /// component `b` can be reached from component `a`, then `b <= a`.
+    pub fn strongly_connected_components(&self) -> Components {
+        self.tarjan().0
+    }
+
+    /// Computes the [`Reachability`] between every pair of points.
+    ///
+    /// The result is not updated when the map is modified.
+    pub fn compute_reachability(&self) -> Reachability {
+        let (components, members) = self.tarjan();
+        let row_length = components.count.div_ceil(64);
+        let mut reachable = vec![0u64; row_length * components.count];
+        // components are in reverse topological order, so every successor of
+        // `component` has already been processed.
+        for (component, points) in members.iter().enumerate() {
+            reachable[component * row_length + component / 64] |= 1 << (component % 64);
+            for &point in points {
+                for nbr in self.enabled_neighbours(point, false) {
+                    let successor = components.component_of[&nbr];
+                    if successor != component {
+                        for word in 0..row_length {
+                            reachable[component * row_length + word] |=
+                                reachable[successor * row_length + word];
+                        }
+                    }
+                }
+            }
+        }
+        Reachability {
+            components,
+            row_length,
+            reachable,
+        }
+    }
+
+    /// Iterative version of Tarjan's strongly connected components
+    /// algorithm.
+    ///
+    /// Returns the components, along with the points of each component.
+    fn tarjan(&self) -> (Components, Vec<Vec<PointId>>) {
+        /// Per-point state of the algorithm.
+        struct Visit {
+            index: usize,
+            lowlink: usize,
+            on_stack: bool,
+        }
+
+        let mut components = Components::default();
+        let mut members = Vec::new();
+        let mut visits = FnvHashMap::<PointId, Visit>::default();
+        let mut stack = Vec::new();
+        // (point, its neighbours, index of the next neighbour to visit)
+        let mut call_stack: Vec<(PointId, Vec<PointId>, usize)> = Vec::new();
+
+        for root in self.sorted_enabled_points() {
+            if visits.contains_key(&root) {
+                continue;
+            }
+            call_stack.push((root, self.enabled_neighbours(root, false), 0));
+            let index = visits.len();
+            visits.insert(
+                root,
+                Visit {
+                    index,
+                    lowlink: index,
+                    on_stack: true,
+                },
+            );
+            stack.push(root);
+
+            while let Some((point, neighbours, next)) = call_stack.last_mut() {
+                let point = *point;
+                if let Some(&nbr) = neighbours.get(*next) {
+                    *next += 1;
+                    match visits.get(&nbr) {
+                        None => {
+                            let index = visits.len();
+                            visits.insert(
+                                nbr,
+                                Visit {
+                                    index,
+                                    lowlink: index,
+                                    on_stack: true,
+                                },
+                            );
+                            stack.push(nbr);
+                            call_stack.push((nbr, self.enabled_neighbours(nbr, false), 0));
+                        }
+                        Some(visit) if visit.on_stack => {
+                            let nbr_index = visit.index;
+                            let visit = visits.get_mut(&point).unwrap();
+                            visit.lowlink = visit.lowlink.min(nbr_index);
+                        }
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+
+                // all neighbours have been visited
+                call_stack.pop();
+                let Visit { index, lowlink, .. } = visits[&point];
+                if let Some((parent, _, _)) = call_stack.last() {
+                    let visit = visits.get_mut(parent).unwrap();
+                    visit.lowlink = visit.lowlink.min(lowlink);
+                }
+                if index == lowlink {
+                    let component = components.count;
+                    components.count += 1;
+                    let mut points = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        visits.get_mut(&member).unwrap().on_stack = false;
+                        components.component_of.insert(member, component);
+                        points.push(member);
+                        if member == point {
+                            break;
+                        }
+                    }
+                    members.push(points);
+                }
+            }
+        }
+        (components, members)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TerrainType;
+
+    const TERRAIN: TerrainType = TerrainType::DefaultTerrain;
+
+    /// Creates a new `DijkstraMap` with the connections :
+    ///
+    /// 0 <--> 1 --> 2 <--> 3    4
+    fn setup_two_islands() -> DijkstraMap {
+        let mut d = DijkstraMap::new();
+        for i in 0..5 {
+            d.add_point(PointId(i), TERRAIN).unwrap();
+        }
+        d.connect_points(PointId(0), PointId(1), None, None)
+            .unwrap();
+        d.connect_points(PointId(1), PointId(2), None, Some(false))
+            .unwrap();
+        d.connect_points(PointId(2), PointId(3), None, None)
+            .unwrap();
+        d
+    }
+
+    #[test]
+    fn weak_components_ignore_direction() {
+        let d = setup_two_islands();
+        let components = d.weakly_connected_components();
+        assert_eq!(components.count(), 2);
+        for i in 1..4 {
+            assert_eq!(
+                components.component_of(PointId(i)),
+                components.component_of(PointId(0))
+            );
+        }
+        assert_ne!(
+            components.component_of(PointId(4)),
+            components.component_of(PointId(0))
+        );
+    }
+
+    #[test]
+    fn strong_components_follow_direction() {
+        let d = setup_two_islands();
+        let components = d.strongly_connected_components();
+        assert_eq!(components.count(), 3);
+        assert_eq!(
+            components.component_of(PointId(0)),
+            components.component_of(PointId(1))
+        );
+        assert_eq!(
+            components.component_of(PointId(2)),
+            components.component_of(PointId(3))
+        );
+        // reverse topological order
+        assert!(components.component_of(PointId(2)) < components.component_of(PointId(0)));
+    }
+
+    #[test]
+    fn disabled_points_split_components() {
+        let mut d = setup_two_islands();
+        d.disable_point(PointId(1)).unwrap();
+        let components = d.weakly_connected_components();
+        assert_eq!(components.component_of(PointId(1)), None);
+        assert_ne!(
+            components.component_of(PointId(0)),
+            components.component_of(PointId(2))
+        );
+    }
+
+    #[test]
+    fn reachability_works() {
+        let mut d = setup_two_islands();
+        let reachability = d.compute_reachability();
+        assert!(reachability.is_reachable(PointId(0), PointId(3)));
+        assert!(reachability.is_reachable(PointId(1), PointId(0)));
+        assert!(!reachability.is_reachable(PointId(3), PointId(0)));
+        assert!(!reachability.is_reachable(PointId(0), PointId(4)));
+        assert!(reachability.is_reachable(PointId(4), PointId(4)));
+        assert!(!reachability.is_reachable(PointId(0), PointId(5)));
+
+        d.disable_point(PointId(2)).unwrap();
+        let reachability = d.compute_reachability();
+        assert!(!reachability.is_reachable(PointId(0), PointId(3)));
+        assert!(!reachability.is_reachable(PointId(0), PointId(2)));
+    }
+
+    #[test]
+    fn reachability_on_long_chain() {
+        // more than 64 components, and deep enough to need an iterative DFS
+        let mut d = DijkstraMap::new();
+        for i in 0..10_000 {
+            d.add_point(PointId(i), TERRAIN).unwrap();
+            if i > 0 {
+                d.connect_points(PointId(i - 1), PointId(i), None, Some(false))
+                    .unwrap();
+            }
+        }
+        let components = d.strongly_connected_components();
+        assert_eq!(components.count(), 10_000);
+        let d = {
+            let mut small = DijkstraMap::new();
+            for i in 0..200 {
+                small.add_point(PointId(i), TERRAIN).unwrap();
+                if i > 0 {
+                    small
+                        .connect_points(PointId(i - 1), PointId(i), None, Some(false))
+                        .unwrap();
+                }
+            }
+            small
+        };
+        let reachability = d.compute_reachability();
+        assert!(reachability.is_reachable(PointId(0), PointId(199)));
+        assert!(reachability.is_reachable(PointId(70), PointId(130)));
+        assert!(!reachability.is_reachable(PointId(130), PointId(70)));
+    }
+}
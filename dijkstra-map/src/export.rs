@@ -0,0 +1,270 @@
+use super::{DijkstraMap, PointComputedInfo, PointId, Weight};
+use std::fmt::Write;
+
+/// Informations written for a point.
+struct PointExport {
+    id: PointId,
+    terrain: i32,
+    disabled: bool,
+    computed: Option<PointComputedInfo>,
+}
+
+impl DijkstraMap {
+    /// Points and connections of the map, sorted by ID.
+    ///
+    /// If `include_paths` is [`true`], the cost and direction of the points
+    /// reached by the last [`recalculate`](DijkstraMap::recalculate) are
+    /// included.
+    fn export(&self, include_paths: bool) -> (Vec<PointExport>, Vec<(PointId, PointId, Weight)>) {
+        let computed_info = self.get_path_results().get_direction_and_cost_map();
+        let mut points: Vec<PointExport> = self
+            .points
+            .iter()
+            .map(|(&id, info)| PointExport {
+                id,
+                terrain: info.terrain_type.into(),
+                disabled: self.disabled_points.contains(&id),
+                computed: if include_paths {
+                    computed_info.get(&id).copied()
+                } else {
+                    None
+                },
+            })
+            .collect();
+        points.sort_unstable_by_key(|point| point.id);
+        let mut connections: Vec<(PointId, PointId, Weight)> = self
+            .points
+            .iter()
+            .flat_map(|(&source, info)| {
+                info.connections
+                    .iter()
+                    .map(move |(&target, &weight)| (source, target, weight))
+            })
+            .collect();
+        connections.sort_unstable_by_key(|&(source, target, _)| (source, target));
+        (points, connections)
+    }
+
+    /// Writes the graph in the [DOT](https://graphviz.org/doc/info/lang.html)
+    /// language of Graphviz, to inspect small graphs visually.
+    ///
+    /// Each point is a node labelled with its ID and terrain, with the
+    /// custom attributes `terrain` and `disabled`. Disabled points are
+    /// dashed. Each connection is a directed edge labelled with its weight.
+    ///
+    /// If `include_paths` is [`true`], the nodes reached by the last
+    /// [`recalculate`](DijkstraMap::recalculate) also have their `cost` and
+    /// `direction`, and the edges along the shortest paths are bold.
+    ///
+    /// Points and connections are sorted by ID, so the output of identical
+    /// graphs is identical.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, PointId, TerrainType, Weight};
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// dijkstra_map.add_point(PointId(0), TerrainType::DefaultTerrain).unwrap();
+    /// dijkstra_map.add_point(PointId(1), TerrainType::Terrain(2)).unwrap();
+    /// dijkstra_map.connect_points(PointId(0), PointId(1), Some(Weight(1.5)), Some(false)).unwrap();
+    /// let dot = dijkstra_map.to_dot(false);
+    /// assert!(dot.starts_with("digraph dijkstra_map {"));
+    /// assert!(dot.contains("    0 -> 1 [label=\"1.5\"];"));
+    /// ```
+    pub fn to_dot(&self, include_paths: bool) -> String {
+        let (points, connections) = self.export(include_paths);
+        let mut out = String::from("digraph dijkstra_map {\n");
+        for point in points.iter() {
+            let id = i32::from(point.id);
+            let mut label = format!("{}\\nterrain {}", id, point.terrain);
+            let mut attributes = format!("terrain={}, disabled={}", point.terrain, point.disabled);
+            if point.disabled {
+                attributes.push_str(", style=dashed");
+            }
+            if let Some(computed) = point.computed {
+                let _ = write!(label, "\\ncost {}", computed.cost.0);
+                let _ = write!(
+                    attributes,
+                    ", cost={}, direction={}",
+                    computed.cost.0,
+                    i32::from(computed.direction)
+                );
+            }
+            let _ = writeln!(out, "    {} [label=\"{}\", {}];", id, label, attributes);
+        }
+        let directions: Vec<(PointId, PointId)> = points
+            .iter()
+            .filter_map(|point| {
+                point
+                    .computed
+                    .map(|computed| (point.id, computed.direction))
+            })
+            .collect();
+        for &(source, target, weight) in connections.iter() {
+            let along_path = directions.binary_search(&(source, target)).is_ok()
+                || directions.binary_search(&(target, source)).is_ok();
+            let style = if along_path { ", style=bold" } else { "" };
+            let _ = writeln!(
+                out,
+                "    {} -> {} [label=\"{}\"{}];",
+                i32::from(source),
+                i32::from(target),
+                weight.0,
+                style
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Writes the graph in the [GraphML](http://graphml.graphdrawing.org/)
+    /// format, read by graph editors such as yEd, Gephi or NetworkX.
+    ///
+    /// Nodes have the `terrain` and `disabled` data, and edges the `weight`
+    /// data. If `include_paths` is [`true`], the nodes reached by the last
+    /// [`recalculate`](DijkstraMap::recalculate) also have their `cost` and
+    /// `direction`.
+    ///
+    /// Node IDs are the point IDs prefixed by `n`, and points and
+    /// connections are sorted by ID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, PointId, TerrainType, Weight};
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// dijkstra_map.add_point(PointId(0), TerrainType::DefaultTerrain).unwrap();
+    /// dijkstra_map.add_point(PointId(1), TerrainType::Terrain(2)).unwrap();
+    /// dijkstra_map.connect_points(PointId(0), PointId(1), Some(Weight(1.5)), Some(false)).unwrap();
+    /// let graphml = dijkstra_map.to_graphml(false);
+    /// assert!(graphml.contains("<node id=\"n1\"><data key=\"terrain\">2</data></node>"));
+    /// assert!(graphml.contains(
+    ///     "<edge source=\"n0\" target=\"n1\"><data key=\"weight\">1.5</data></edge>"
+    /// ));
+    /// ```
+    pub fn to_graphml(&self, include_paths: bool) -> String {
+        let (points, connections) = self.export(include_paths);
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"terrain\" for=\"node\" attr.name=\"terrain\" attr.type=\"int\"/>\n",
+            "  <key id=\"disabled\" for=\"node\" attr.name=\"disabled\" attr.type=\"boolean\">",
+            "<default>false</default></key>\n",
+        ));
+        if include_paths {
+            out.push_str(concat!(
+                "  <key id=\"cost\" for=\"node\" attr.name=\"cost\" attr.type=\"double\"/>\n",
+                "  <key id=\"direction\" for=\"node\" attr.name=\"direction\" attr.type=\"int\"/>\n",
+            ));
+        }
+        out.push_str(concat!(
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n",
+            "  <graph id=\"dijkstra_map\" edgedefault=\"directed\">\n",
+        ));
+        for point in points.iter() {
+            let _ = write!(
+                out,
+                "    <node id=\"n{}\"><data key=\"terrain\">{}</data>",
+                i32::from(point.id),
+                point.terrain
+            );
+            if point.disabled {
+                out.push_str("<data key=\"disabled\">true</data>");
+            }
+            if let Some(computed) = point.computed {
+                let _ = write!(
+                    out,
+                    "<data key=\"cost\">{}</data><data key=\"direction\">{}</data>",
+                    computed.cost.0,
+                    i32::from(computed.direction)
+                );
+            }
+            out.push_str("</node>\n");
+        }
+        for &(source, target, weight) in connections.iter() {
+            let _ = writeln!(
+                out,
+                "    <edge source=\"n{}\" target=\"n{}\"><data key=\"weight\">{}</data></edge>",
+                i32::from(source),
+                i32::from(target),
+                weight.0
+            );
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Read, TerrainType};
+
+    /// Creates a new `DijkstraMap` with the connections :
+    ///
+    /// 0 <--> 1 --> 2, where 2 is disabled
+    fn setup() -> DijkstraMap {
+        let mut d = DijkstraMap::new();
+        d.add_point(PointId(0), TerrainType::DefaultTerrain)
+            .unwrap();
+        d.add_point(PointId(1), TerrainType::Terrain(3)).unwrap();
+        d.add_point(PointId(2), TerrainType::DefaultTerrain)
+            .unwrap();
+        d.connect_points(PointId(0), PointId(1), Some(Weight(2.0)), None)
+            .unwrap();
+        d.connect_points(PointId(1), PointId(2), None, Some(false))
+            .unwrap();
+        d.disable_point(PointId(2)).unwrap();
+        d
+    }
+
+    #[test]
+    fn dot_lists_points_and_connections() {
+        let d = setup();
+        assert_eq!(
+            d.to_dot(false),
+            concat!(
+                "digraph dijkstra_map {\n",
+                "    0 [label=\"0\\nterrain -1\", terrain=-1, disabled=false];\n",
+                "    1 [label=\"1\\nterrain 3\", terrain=3, disabled=false];\n",
+                "    2 [label=\"2\\nterrain -1\", terrain=-1, disabled=true, style=dashed];\n",
+                "    0 -> 1 [label=\"2\"];\n",
+                "    1 -> 0 [label=\"2\"];\n",
+                "    1 -> 2 [label=\"1\"];\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn exports_include_paths() {
+        let mut d = setup();
+        d.recalculate(
+            &[PointId(0)],
+            Some(Read::InputIsOrigin),
+            None,
+            Vec::new(),
+            [(TerrainType::Terrain(3), Weight(1.0))]
+                .iter()
+                .copied()
+                .collect(),
+            Default::default(),
+        );
+        let dot = d.to_dot(true);
+        assert!(dot.contains("    1 [label=\"1\\nterrain 3\\ncost 2\", terrain=3, disabled=false, cost=2, direction=0];\n"));
+        assert!(dot.contains("    0 -> 1 [label=\"2\", style=bold];\n"));
+        // the disabled point is not reached
+        assert!(dot.contains("    1 -> 2 [label=\"1\"];\n"));
+
+        let graphml = d.to_graphml(true);
+        assert!(graphml.contains("<key id=\"cost\""));
+        assert!(graphml.contains(
+            "    <node id=\"n1\"><data key=\"terrain\">3</data><data key=\"cost\">2</data><data key=\"direction\">0</data></node>\n"
+        ));
+        assert!(graphml
+            .contains("    <node id=\"n2\"><data key=\"terrain\">-1</data><data key=\"disabled\">true</data></node>\n"));
+        assert!(!d.to_graphml(false).contains("<key id=\"cost\""));
+    }
+}
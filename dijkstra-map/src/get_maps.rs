@@ -3,31 +3,14 @@ use fnv::FnvHashMap;
 
 impl DijkstraMap {
     /// Returns the entire Dijkstra map of directions and costs.
-    pub fn get_direction_and_cost_map(&mut self) -> &FnvHashMap<PointId, PointComputedInfo> {
-        &self.computed_info
+    pub fn get_direction_and_cost_map(&self) -> &FnvHashMap<PointId, PointComputedInfo> {
+        self.get_path_results().get_direction_and_cost_map()
     }
 
     /// Returns a slice of all points with costs between `min_cost` and `max_cost` (inclusive), sorted by cost.
     pub fn get_all_points_with_cost_between(&self, min_cost: Cost, max_cost: Cost) -> &[PointId] {
-        let start_point = match self.sorted_points.binary_search_by(|a| {
-            if self.get_cost_at_point(*a) < min_cost {
-                std::cmp::Ordering::Less
-            } else {
-                std::cmp::Ordering::Greater
-            }
-        }) {
-            Ok(a) | Err(a) => a,
-        };
-        let end_point = match self.sorted_points.binary_search_by(|a| {
-            if self.get_cost_at_point(*a) > max_cost {
-                std::cmp::Ordering::Greater
-            } else {
-                std::cmp::Ordering::Less
-            }
-        }) {
-            Ok(a) | Err(a) => a,
-        };
-        &self.sorted_points[start_point..end_point]
+        self.get_path_results()
+            .get_all_points_with_cost_between(min_cost, max_cost)
     }
 }
 
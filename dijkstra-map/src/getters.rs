@@ -1,4 +1,7 @@
-use super::{Cost, DijkstraMap, PointComputedInfo, PointId, PointInfo, TerrainType};
+use super::{
+    Cost, DijkstraMap, PathResults, PathSearch, PointId, PointInfo, ShortestPathIterator,
+    TerrainType, Weight,
+};
 
 impl DijkstraMap {
     /// Gives the smallest [`PointId`] not yet used.
@@ -38,8 +41,43 @@ impl DijkstraMap {
             .map(|PointInfo { terrain_type, .. }| *terrain_type)
     }
 
+    /// Returns an iterator over the IDs of all the points of the map, in no
+    /// particular order.
+    pub fn points(&self) -> impl Iterator<Item = PointId> + '_ {
+        self.points.keys().copied()
+    }
+
+    /// Returns an iterator over the connections from `point`, as
+    /// `(target, weight)`, in no particular order.
+    ///
+    /// The iterator is empty if `point` does not exist.
+    pub fn connections_of(&self, point: PointId) -> impl Iterator<Item = (PointId, Weight)> + '_ {
+        self.points
+            .get(&point)
+            .into_iter()
+            .flat_map(|info| info.connections.iter())
+            .map(|(&target, &weight)| (target, weight))
+    }
+
+    /// Returns an iterator over the connections to `point`, as
+    /// `(source, weight)`, in no particular order.
+    ///
+    /// The iterator is empty if `point` does not exist.
+    pub fn incoming_of(&self, point: PointId) -> impl Iterator<Item = (PointId, Weight)> + '_ {
+        self.points
+            .get(&point)
+            .into_iter()
+            .flat_map(|info| info.reverse_connections.iter())
+            .map(|(&source, &weight)| (source, weight))
+    }
+
+    /// Returns an iterator over the disabled points, in no particular order.
+    pub fn disabled_points(&self) -> impl Iterator<Item = PointId> + '_ {
+        self.disabled_points.iter().copied()
+    }
+
     /// Returns [`true`] if `point` exists and is disabled.
-    pub fn is_point_disabled(&mut self, point: PointId) -> bool {
+    pub fn is_point_disabled(&self, point: PointId) -> bool {
         self.disabled_points.contains(&point)
     }
 
@@ -48,19 +86,14 @@ impl DijkstraMap {
     ///
     /// If there is no path, returns [`None`].
     pub fn get_direction_at_point(&self, point: PointId) -> Option<PointId> {
-        self.computed_info
-            .get(&point)
-            .map(|PointComputedInfo { direction, .. }| *direction)
+        self.get_path_results().get_direction_at_point(point)
     }
 
     /// Returns the cost of the shortest path computed with [`recalculate`](DijkstraMap::recalculate).
     ///
     /// If there is no path, the cost is [`INFINITY`](Cost::infinity).
     pub fn get_cost_at_point(&self, point: PointId) -> Cost {
-        self.computed_info
-            .get(&point)
-            .map(|PointComputedInfo { cost, .. }| *cost)
-            .unwrap_or_else(Cost::infinity)
+        self.get_path_results().get_cost_at_point(point)
     }
 
     /// Returns an iterator over the components of the shortest path from the
@@ -68,37 +101,19 @@ impl DijkstraMap {
     ///
     /// If `point` is a target or is inaccessible, the iterator will be empty.
     pub fn get_shortest_path_from_point(&self, point: PointId) -> ShortestPathIterator {
-        ShortestPathIterator {
-            dijkstra_map: self,
-            next_point: self.get_direction_at_point(point),
-        }
+        self.get_path_results().get_shortest_path_from_point(point)
     }
-}
-
-/// Iterator over the components of a shortest path in a [`DijkstraMap`].
-///
-/// This is created via the
-/// [`get_shortest_path_from_point`](DijkstraMap::get_shortest_path_from_point)
-/// function.
-pub struct ShortestPathIterator<'a> {
-    /// Reference to the dijkstra map
-    dijkstra_map: &'a DijkstraMap,
-    /// next point to return
-    next_point: Option<PointId>,
-}
-
-impl<'a> Iterator for ShortestPathIterator<'a> {
-    type Item = PointId;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let current_point = self.next_point?;
-        self.next_point = self.dijkstra_map.get_direction_at_point(current_point);
-        if let Some(point) = self.next_point {
-            if point == current_point {
-                self.next_point = None;
-            }
-        }
-        Some(current_point)
+    /// Returns the paths computed by the last call to
+    /// [`recalculate`](DijkstraMap::recalculate).
+    ///
+    /// During a time-sliced recalculation (see
+    /// [`start_recalculation`](DijkstraMap::start_recalculation)), returns
+    /// the paths computed so far instead.
+    pub fn get_path_results(&self) -> &PathResults {
+        self.search
+            .as_ref()
+            .map_or(&self.results, PathSearch::results)
     }
 }
 
@@ -153,6 +168,33 @@ mod test {
         d.add_point(id, TERRAIN).unwrap();
     }
 
+    #[test]
+    fn graph_can_be_read_back() {
+        let mut d = DijkstraMap::new();
+        for i in 0..3 {
+            d.add_point(PointId(i), TERRAIN).unwrap();
+        }
+        d.connect_points(PointId(0), PointId(1), Some(Weight(2.0)), None)
+            .unwrap();
+        d.connect_points(PointId(2), PointId(1), None, Some(false))
+            .unwrap();
+        d.disable_point(PointId(2)).unwrap();
+
+        let mut points: Vec<PointId> = d.points().collect();
+        points.sort_unstable();
+        assert_eq!(points, vec![PointId(0), PointId(1), PointId(2)]);
+        let connections: Vec<_> = d.connections_of(PointId(0)).collect();
+        assert_eq!(connections, vec![(PointId(1), Weight(2.0))]);
+        let mut incoming: Vec<_> = d.incoming_of(PointId(1)).collect();
+        incoming.sort_unstable_by_key(|&(source, _)| source);
+        assert_eq!(
+            incoming,
+            vec![(PointId(0), Weight(2.0)), (PointId(2), Weight(1.0))]
+        );
+        assert_eq!(d.connections_of(PointId(5)).count(), 0);
+        assert_eq!(d.disabled_points().collect::<Vec<_>>(), vec![PointId(2)]);
+    }
+
     #[test]
     fn iterate_on_shortest_path() {
         use crate::Read;
@@ -182,5 +224,8 @@ mod test {
         assert_eq!(path_iterator.next(), Some(PointId(0)));
         assert_eq!(path_iterator.next(), None);
         assert_eq!(path_iterator.next(), None);
+
+        // the path from a target is empty
+        assert_eq!(d.get_shortest_path_from_point(PointId(0)).next(), None);
     }
 }
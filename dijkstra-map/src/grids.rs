@@ -1,5 +1,210 @@
-use super::{DijkstraMap, FnvHashMap, PointId, TerrainType, Weight};
-use euclid::Vector2D;
+use super::{DijkstraMap, FnvHashMap, PointId, Position, TerrainType, Weight};
+use euclid::{Vector2D, Vector3D};
+use std::convert::TryFrom;
+use std::hash::Hash;
+
+/// Axes along which a grid wraps around : the points on one edge are
+/// connected to the points on the opposite edge.
+///
+/// Wrapping on one axis gives a cylinder, and on both axes a torus.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GridWrap {
+    /// Connect the left and right edges.
+    pub x: bool,
+    /// Connect the top and bottom edges.
+    pub y: bool,
+}
+
+impl GridWrap {
+    /// Returns the neighbour of `pos` at `offset`, in a grid starting at
+    /// `origin` with the given `size`, wrapping around on the axes of `self`.
+    fn neighbour(
+        self,
+        pos: Vector2D<i32, i32>,
+        offset: Vector2D<i32, i32>,
+        origin: Vector2D<i32, i32>,
+        size: Vector2D<i32, i32>,
+    ) -> Vector2D<i32, i32> {
+        let mut neighbour = pos + offset;
+        if self.x {
+            neighbour.x = origin.x + (neighbour.x - origin.x).rem_euclid(size.x);
+        }
+        if self.y {
+            neighbour.y = origin.y + (neighbour.y - origin.y).rem_euclid(size.y);
+        }
+        neighbour
+    }
+}
+
+/// Deterministic IDs of the points of a rectangular grid, for
+/// [`add_square_grid_with_ids`](DijkstraMap::add_square_grid_with_ids) and
+/// [`add_hexagonal_grid_with_ids`](DijkstraMap::add_hexagonal_grid_with_ids).
+///
+/// The point at `(x, y)` has the ID
+/// `base + (y - offset.y) * width + (x - offset.x)` : IDs only depend on
+/// coordinates, and not on the points already in the map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GridIds {
+    /// ID of the top-left cell.
+    pub base: PointId,
+    /// Coordinates of the top-left cell.
+    pub offset: Vector2D<i32, i32>,
+    /// Width of the grid.
+    pub width: usize,
+    /// Height of the grid.
+    pub height: usize,
+}
+
+/// Error returned by
+/// [`add_square_grid_with_ids`](DijkstraMap::add_square_grid_with_ids) and
+/// [`add_hexagonal_grid_with_ids`](DijkstraMap::add_hexagonal_grid_with_ids).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GridIdsError {
+    /// One of the IDs of the grid is already taken.
+    PointAlreadyExists,
+    /// The IDs of the grid overflow the range of [`PointId`].
+    IdOverflow,
+    /// The hexagonal grid wraps horizontally, but its width is odd.
+    OddWrappingWidth,
+}
+
+/// Error returned by
+/// [`add_wrapping_hexagonal_grid`](DijkstraMap::add_wrapping_hexagonal_grid)
+/// when the grid wraps horizontally but its width is odd : the first and
+/// last columns would have the same parity, so they could not be
+/// neighbours.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OddWrappingWidth;
+
+impl GridIds {
+    fn size(&self) -> Vector2D<i32, i32> {
+        Vector2D::new(self.width as i32, self.height as i32)
+    }
+
+    /// Iterates over the cells of the grid, row by row.
+    fn cells(self) -> impl Iterator<Item = Vector2D<i32, i32>> {
+        (0..self.height as i32).flat_map(move |y| {
+            (0..self.width as i32).map(move |x| self.offset + Vector2D::new(x, y))
+        })
+    }
+
+    /// Returns the ID of `cell`, or [`None`] if it is outside of the grid
+    /// (or if its ID would overflow).
+    pub fn id_of(&self, cell: Vector2D<i32, i32>) -> Option<PointId> {
+        let x = i64::from(cell.x) - i64::from(self.offset.x);
+        let y = i64::from(cell.y) - i64::from(self.offset.y);
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return None;
+        }
+        let id = i64::from(i32::from(self.base)) + y * self.width as i64 + x;
+        i32::try_from(id).ok().map(PointId)
+    }
+
+    /// Returns the cell of `id`, or [`None`] if it is not an ID of the
+    /// grid.
+    pub fn cell_of(&self, id: PointId) -> Option<Vector2D<i32, i32>> {
+        let index = i64::from(i32::from(id)) - i64::from(i32::from(self.base));
+        if self.width == 0 || index < 0 || index >= self.width as i64 * self.height as i64 {
+            return None;
+        }
+        let x = index % self.width as i64;
+        let y = index / self.width as i64;
+        Some(self.offset + Vector2D::new(x as i32, y as i32))
+    }
+
+    /// Returns the IDs of `cells`, see [`id_of`](GridIds::id_of).
+    pub fn ids_of(&self, cells: &[Vector2D<i32, i32>]) -> Vec<Option<PointId>> {
+        cells.iter().map(|&cell| self.id_of(cell)).collect()
+    }
+
+    /// Returns the cells of `ids`, see [`cell_of`](GridIds::cell_of).
+    pub fn cells_of(&self, ids: &[PointId]) -> Vec<Option<Vector2D<i32, i32>>> {
+        ids.iter().map(|&id| self.cell_of(id)).collect()
+    }
+}
+
+/// Orientation of the hexagons of a hexagonal grid.
+///
+/// See <https://www.redblobgames.com/grids/hexagons/> for illustrations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HexOrientation {
+    /// Hexagons have a vertex at the top, and form horizontal rows.
+    PointyTop,
+    /// Hexagons have a side at the top, and form vertical columns.
+    FlatTop,
+}
+
+/// Coordinate system of a hexagonal grid created by
+/// [`add_hexagonal_grid_with_layout`](DijkstraMap::add_hexagonal_grid_with_layout).
+///
+/// See <https://www.redblobgames.com/grids/hexagons/> for illustrations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HexCoordinates {
+    /// `(column, row)`, where odd rows (for [`PointyTop`](HexOrientation::PointyTop)
+    /// hexagons) or odd columns (for [`FlatTop`](HexOrientation::FlatTop)
+    /// hexagons) are shifted by half a hexagon. The grid is a rectangle.
+    OddOffset,
+    /// Same as [`OddOffset`](HexCoordinates::OddOffset), but even rows or
+    /// columns are shifted.
+    EvenOffset,
+    /// `(q, r)`, where `q` increases to the right and `r` downwards, along
+    /// the axes of the hexagons. The grid is a parallelogram.
+    Axial,
+}
+
+/// The 6 directions of an hexagonal grid, in axial coordinates.
+const AXIAL_DIRECTIONS: [Vector2D<i32, i32>; 6] = [
+    Vector2D::<i32, i32>::new(1, 0),
+    Vector2D::<i32, i32>::new(1, -1),
+    Vector2D::<i32, i32>::new(0, -1),
+    Vector2D::<i32, i32>::new(-1, 0),
+    Vector2D::<i32, i32>::new(-1, 1),
+    Vector2D::<i32, i32>::new(0, 1),
+];
+
+/// Converts offset coordinates to axial coordinates.
+///
+/// `odd` is [`true`] if odd rows or columns are shifted.
+fn offset_to_axial(
+    offset: Vector2D<i32, i32>,
+    orientation: HexOrientation,
+    odd: bool,
+) -> Vector2D<i32, i32> {
+    let shift = |line: i32| {
+        if odd {
+            (line - (line & 1)) / 2
+        } else {
+            (line + (line & 1)) / 2
+        }
+    };
+    match orientation {
+        HexOrientation::PointyTop => Vector2D::new(offset.x - shift(offset.y), offset.y),
+        HexOrientation::FlatTop => Vector2D::new(offset.x, offset.y - shift(offset.x)),
+    }
+}
+
+/// Position of the center of an hexagon, with a distance of `1.0` between
+/// neighbouring hexagons.
+fn hexagon_center(axial: Vector2D<i32, i32>, orientation: HexOrientation) -> Position {
+    let (q, r) = (axial.x as f32, axial.y as f32);
+    let half_sqrt_3 = 3f32.sqrt() / 2.0;
+    match orientation {
+        HexOrientation::PointyTop => Position::new(q + r / 2.0, r * half_sqrt_3, 0.0),
+        HexOrientation::FlatTop => Position::new(q * half_sqrt_3, r + q / 2.0, 0.0),
+    }
+}
+
+/// Position of a point of a square grid : its coordinates.
+fn cell_center(pos: Vector2D<i32, i32>) -> Position {
+    Position::new(pos.x as f32, pos.y as f32, 0.0)
+}
+
+/// Position of the center of an isometric tile of width `1.0` and height
+/// `0.5`, from its coordinates on screen measured in tile widths and tile
+/// heights.
+fn isometric_center(x: f32, y: f32) -> Position {
+    Position::new(x, y / 2.0, 0.0)
+}
 
 impl DijkstraMap {
     /// Function for common processing input of add_*grid methods.
@@ -16,6 +221,7 @@ impl DijkstraMap {
     /// - `terrain_type_default` : Terrain type of each point in the new grid.
     /// - `initial_offset` (default : `0`) : offset at which the function will
     /// try to map the points' IDs.
+    /// - `position_of` : position of the point at the given coordinates.
     ///
     /// # Return
     ///
@@ -27,6 +233,7 @@ impl DijkstraMap {
         width: usize,
         height: usize,
         terrain_type_default: TerrainType,
+        position_of: impl Fn(Vector2D<i32, i32>) -> Position,
     ) -> FnvHashMap<Vector2D<i32, i32>, PointId> {
         let mut id = self.get_available_id(None);
         let mut pos_to_id = FnvHashMap::<Vector2D<i32, i32>, PointId>::default();
@@ -36,6 +243,7 @@ impl DijkstraMap {
                 let pos = Vector2D::<i32, i32>::from((x as i32, y as i32));
                 id = self.get_available_id(Some(PointId(i32::from(id) + 1)));
                 self.add_point_replace(id, terrain_type_default);
+                let _ = self.set_point_position(id, Some(position_of(pos)));
                 pos_to_id.insert(pos, id);
             }
         }
@@ -72,55 +280,75 @@ impl DijkstraMap {
         default_terrain: TerrainType,
         orthogonal_cost: Option<Weight>,
         diagonal_cost: Option<Weight>,
+    ) -> FnvHashMap<Vector2D<i32, i32>, PointId> {
+        self.add_wrapping_square_grid(
+            width,
+            height,
+            initial_offset,
+            GridWrap::default(),
+            default_terrain,
+            orthogonal_cost,
+            diagonal_cost,
+        )
+    }
+
+    /// Adds a square grid of connected points, whose edges wrap around.
+    ///
+    /// Same as [`add_square_grid`](DijkstraMap::add_square_grid), but
+    /// neighbours across the edges of the axes of `wrap` are connected too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, GridWrap, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let wrap = GridWrap { x: true, y: false };
+    /// let grid = dijkstra_map.add_wrapping_square_grid(
+    ///     5,
+    ///     5,
+    ///     None,
+    ///     wrap,
+    ///     TerrainType::DefaultTerrain,
+    ///     None,
+    ///     None,
+    /// );
+    /// // the left and right edges are connected, the top and bottom are not
+    /// assert!(dijkstra_map.has_connection(grid[&Vector2D::new(0, 2)], grid[&Vector2D::new(4, 2)]));
+    /// assert!(!dijkstra_map.has_connection(grid[&Vector2D::new(2, 0)], grid[&Vector2D::new(2, 4)]));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_wrapping_square_grid(
+        &mut self,
+        width: usize,
+        height: usize,
+        initial_offset: Option<Vector2D<usize, usize>>,
+        wrap: GridWrap,
+        default_terrain: TerrainType,
+        orthogonal_cost: Option<Weight>,
+        diagonal_cost: Option<Weight>,
     ) -> FnvHashMap<Vector2D<i32, i32>, PointId> {
         let initial_offset = initial_offset.unwrap_or_default();
+        let origin = Vector2D::new(initial_offset.x as i32, initial_offset.y as i32);
+        let size = Vector2D::new(width as i32, height as i32);
         let pos_to_id = self.add_grid_internal(
             initial_offset.x,
             initial_offset.y,
             width,
             height,
             default_terrain,
+            cell_center,
         );
 
-        let orthogonal_cost = orthogonal_cost.unwrap_or(Weight(1.0));
-        let diagonal_cost = diagonal_cost.unwrap_or(Weight(f32::INFINITY));
-        // now connect points
-        /// Orthogonal connection
-        const ORTHOS: [Vector2D<i32, i32>; 4] = [
-            Vector2D::<i32, i32>::new(1, 0),
-            Vector2D::<i32, i32>::new(-1, 0),
-            Vector2D::<i32, i32>::new(0, 1),
-            Vector2D::<i32, i32>::new(0, -1),
-        ];
-        /// Diagonal connections
-        const DIAGS: [Vector2D<i32, i32>; 4] = [
-            Vector2D::<i32, i32>::new(1, 1),
-            Vector2D::<i32, i32>::new(-1, 1),
-            Vector2D::<i32, i32>::new(1, -1),
-            Vector2D::<i32, i32>::new(-1, -1),
-        ];
-
-        for (&pos, &id_1) in pos_to_id.iter() {
-            if orthogonal_cost < Weight(f32::INFINITY) {
-                for &offs in &ORTHOS {
-                    let sum = offs + pos;
-                    if let Some(&id_2) = pos_to_id.get(&sum) {
-                        // ignore error, we know it succeeded
-                        let _ = self.connect_points(id_1, id_2, Some(orthogonal_cost), Some(false));
-                    }
-                }
-            }
-
-            if diagonal_cost < Weight(f32::INFINITY) {
-                for &offs in &DIAGS {
-                    let sum = offs + pos;
-                    if let Some(&id_2) = pos_to_id.get(&sum) {
-                        // ignore error, we know it succeeded
-                        let _ = self.connect_points(id_1, id_2, Some(diagonal_cost), Some(false));
-                    }
-                }
-            }
-        }
+        self.connect_square_grid(
+            &pos_to_id,
+            origin,
+            size,
+            wrap,
+            orthogonal_cost,
+            diagonal_cost,
+        );
         pos_to_id
     }
 
@@ -141,8 +369,12 @@ impl DijkstraMap {
     ///
     /// Hexgrid is in the "pointy" orentation by default (see example below).
     ///
-    /// To switch to "flat" orientation, swap `width` and `height`, and switch
-    /// `x` and `y` coordinates of the keys in the returned [`HashMap`].
+    /// For backward compatibility, neighbours are picked by the parity of
+    /// the x coordinate of the points, and not by the parity of their row
+    /// as the example below suggests.
+    ///
+    /// For other orientations and coordinate systems, see
+    /// [`add_hexagonal_grid_with_layout`](DijkstraMap::add_hexagonal_grid_with_layout).
     ///
     /// # Example
     ///
@@ -171,14 +403,154 @@ impl DijkstraMap {
         default_terrain: TerrainType,
         weight: Option<Weight>,
     ) -> FnvHashMap<Vector2D<i32, i32>, PointId> {
+        // a grid that does not wrap can not fail
+        self.add_wrapping_hexagonal_grid(
+            width,
+            height,
+            initial_offset,
+            GridWrap::default(),
+            default_terrain,
+            weight,
+        )
+        .unwrap_or_default()
+    }
+
+    /// Adds a hexagonal grid of connected points, whose edges wrap around.
+    ///
+    /// Same as [`add_hexagonal_grid`](DijkstraMap::add_hexagonal_grid), but
+    /// neighbours across the edges of the axes of `wrap` are connected too.
+    ///
+    /// Neighbours are picked the same way as in `add_hexagonal_grid`, so
+    /// wrapping only adds the connections across the edges.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] without modifying the map if `wrap.x` is [`true`] and
+    /// `width` is odd.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, GridWrap, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let wrap = GridWrap { x: true, y: true };
+    /// let grid = dijkstra_map
+    ///     .add_wrapping_hexagonal_grid(4, 3, None, wrap, TerrainType::DefaultTerrain, None)
+    ///     .unwrap();
+    /// // (3, 2) touches (0, 0) across both edges
+    /// assert!(dijkstra_map.has_connection(grid[&Vector2D::new(3, 2)], grid[&Vector2D::new(0, 0)]));
+    /// assert!(dijkstra_map.has_connection(grid[&Vector2D::new(3, 2)], grid[&Vector2D::new(3, 0)]));
+    ///
+    /// // the width must be even to wrap horizontally
+    /// let res = dijkstra_map.add_wrapping_hexagonal_grid(3, 3, None, wrap, TerrainType::DefaultTerrain, None);
+    /// assert!(res.is_err());
+    /// ```
+    pub fn add_wrapping_hexagonal_grid(
+        &mut self,
+        width: usize,
+        height: usize,
+        initial_offset: Option<Vector2D<usize, usize>>,
+        wrap: GridWrap,
+        default_terrain: TerrainType,
+        weight: Option<Weight>,
+    ) -> Result<FnvHashMap<Vector2D<i32, i32>, PointId>, OddWrappingWidth> {
+        if wrap.x && width & 1 == 1 {
+            return Err(OddWrappingWidth);
+        }
         let initial_offset = initial_offset.unwrap_or_default();
+        let origin = Vector2D::new(initial_offset.x as i32, initial_offset.y as i32);
+        let size = Vector2D::new(width as i32, height as i32);
         let pos_to_id = self.add_grid_internal(
             initial_offset.x,
             initial_offset.y,
             width,
             height,
             default_terrain,
+            cell_center,
         );
+        self.connect_hexagonal_grid(&pos_to_id, origin, size, wrap, weight);
+        Ok(pos_to_id)
+    }
+
+    /// Connects the neighbouring points of a square grid, created by
+    /// [`add_grid_internal`](DijkstraMap::add_grid_internal) or
+    /// [`add_grid_with_ids_internal`](DijkstraMap::add_grid_with_ids_internal)
+    /// in the rectangle of size `size` starting at `origin`.
+    pub(crate) fn connect_square_grid(
+        &mut self,
+        pos_to_id: &FnvHashMap<Vector2D<i32, i32>, PointId>,
+        origin: Vector2D<i32, i32>,
+        size: Vector2D<i32, i32>,
+        wrap: GridWrap,
+        orthogonal_cost: Option<Weight>,
+        diagonal_cost: Option<Weight>,
+    ) {
+        let orthogonal_cost = orthogonal_cost.unwrap_or(Weight(1.0));
+        let diagonal_cost = diagonal_cost.unwrap_or(Weight(f32::INFINITY));
+        // now connect points
+        /// Orthogonal connection
+        const ORTHOS: [Vector2D<i32, i32>; 4] = [
+            Vector2D::<i32, i32>::new(1, 0),
+            Vector2D::<i32, i32>::new(-1, 0),
+            Vector2D::<i32, i32>::new(0, 1),
+            Vector2D::<i32, i32>::new(0, -1),
+        ];
+        /// Diagonal connections
+        const DIAGS: [Vector2D<i32, i32>; 4] = [
+            Vector2D::<i32, i32>::new(1, 1),
+            Vector2D::<i32, i32>::new(-1, 1),
+            Vector2D::<i32, i32>::new(1, -1),
+            Vector2D::<i32, i32>::new(-1, -1),
+        ];
+
+        for (&pos, &id_1) in pos_to_id.iter() {
+            if orthogonal_cost < Weight(f32::INFINITY) {
+                for &offs in &ORTHOS {
+                    let sum = wrap.neighbour(pos, offs, origin, size);
+                    match pos_to_id.get(&sum) {
+                        // a grid of width or height 1 wraps onto itself
+                        Some(&id_2) if id_2 != id_1 => {
+                            // ignore error, we know it succeeded
+                            let _ =
+                                self.connect_points(id_1, id_2, Some(orthogonal_cost), Some(false));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            if diagonal_cost < Weight(f32::INFINITY) {
+                for &offs in &DIAGS {
+                    let sum = wrap.neighbour(pos, offs, origin, size);
+                    match pos_to_id.get(&sum) {
+                        Some(&id_2) if id_2 != id_1 => {
+                            // ignore error, we know it succeeded
+                            let _ =
+                                self.connect_points(id_1, id_2, Some(diagonal_cost), Some(false));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Connects the neighbouring points of a hexagonal grid, see
+    /// [`connect_square_grid`](DijkstraMap::connect_square_grid).
+    ///
+    /// Neighbours are picked by the parity of the x coordinate of the
+    /// points, so the width of the grid must be even if it wraps
+    /// horizontally.
+    fn connect_hexagonal_grid(
+        &mut self,
+        pos_to_id: &FnvHashMap<Vector2D<i32, i32>, PointId>,
+        origin: Vector2D<i32, i32>,
+        size: Vector2D<i32, i32>,
+        wrap: GridWrap,
+        weight: Option<Weight>,
+    ) {
         let weight = weight.unwrap_or(Weight(1.0));
 
         /// Connections directions for an hexagonal map.
@@ -190,7 +562,7 @@ impl DijkstraMap {
                 Vector2D::<i32, i32>::new(1, 0),
                 Vector2D::<i32, i32>::new(-1, 1),
                 Vector2D::<i32, i32>::new(0, 1),
-            ], // for points with even x coordinate
+            ], // for points with an even parity
             [
                 Vector2D::<i32, i32>::new(0, -1),
                 Vector2D::<i32, i32>::new(1, -1),
@@ -198,17 +570,464 @@ impl DijkstraMap {
                 Vector2D::<i32, i32>::new(1, 0),
                 Vector2D::<i32, i32>::new(0, 1),
                 Vector2D::<i32, i32>::new(1, 1),
-            ], // for points with odd x coordinate
+            ], // for points with an odd parity
         ];
-
         // Create connections
         for (&pos, &id_1) in pos_to_id.iter() {
             if weight < Weight(std::f32::INFINITY) {
-                for &offs in CONNECTIONS[(pos.x % 2) as usize].iter() {
-                    let sum = offs + pos;
-                    if let Some(id_2) = pos_to_id.get(&sum) {
+                for &offs in CONNECTIONS[pos.x.rem_euclid(2) as usize].iter() {
+                    let sum = wrap.neighbour(pos, offs, origin, size);
+                    match pos_to_id.get(&sum) {
+                        // a grid of width or height 1 wraps onto itself
+                        Some(&id_2) if id_2 != id_1 => {
+                            // ignore error, we know it succeeded
+                            let _ = self.connect_points(id_1, id_2, Some(weight), Some(false));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the points of a grid with the IDs of `ids`, without connecting
+    /// them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] without modifying the map if one of the IDs is
+    /// already taken, or if the IDs overflow the range of [`PointId`].
+    fn add_grid_with_ids_internal(
+        &mut self,
+        ids: GridIds,
+        terrain_type_default: TerrainType,
+    ) -> Result<FnvHashMap<Vector2D<i32, i32>, PointId>, GridIdsError> {
+        let mut pos_to_id = FnvHashMap::<Vector2D<i32, i32>, PointId>::default();
+        for pos in ids.cells() {
+            let id = ids.id_of(pos).ok_or(GridIdsError::IdOverflow)?;
+            if self.has_point(id) {
+                return Err(GridIdsError::PointAlreadyExists);
+            }
+            pos_to_id.insert(pos, id);
+        }
+        for (&pos, &id) in pos_to_id.iter() {
+            // ignore error, we know it succeeded
+            let _ = self.add_point(id, terrain_type_default);
+            let _ = self.set_point_position(id, Some(cell_center(pos)));
+        }
+        Ok(pos_to_id)
+    }
+
+    /// Adds a square grid of connected points, whose IDs are computed from
+    /// their coordinates by `ids` instead of being picked among the
+    /// available IDs.
+    ///
+    /// The grid covers the rectangle of `ids`. This makes IDs predictable,
+    /// e.g. to send them over the network or to store them in save files :
+    /// use [`GridIds::id_of`] and [`GridIds::cell_of`] to convert between
+    /// coordinates and IDs without a lookup table.
+    ///
+    /// The other parameters are the same as
+    /// [`add_wrapping_square_grid`](DijkstraMap::add_wrapping_square_grid).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] without modifying the map if one of the IDs is
+    /// already taken, or if the IDs overflow the range of [`PointId`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, GridIds, GridIdsError, GridWrap, PointId, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let ids = GridIds {
+    ///     base: PointId(100),
+    ///     offset: Vector2D::new(0, 0),
+    ///     width: 10,
+    ///     height: 5,
+    /// };
+    /// let grid = dijkstra_map
+    ///     .add_square_grid_with_ids(ids, GridWrap::default(), TerrainType::DefaultTerrain, None, None)
+    ///     .unwrap();
+    /// // the ID is `base + y * width + x`
+    /// assert_eq!(grid[&Vector2D::new(3, 2)], PointId(123));
+    /// assert_eq!(ids.cell_of(PointId(123)), Some(Vector2D::new(3, 2)));
+    ///
+    /// // the IDs are now taken
+    /// let res =
+    ///     dijkstra_map.add_square_grid_with_ids(ids, GridWrap::default(), TerrainType::DefaultTerrain, None, None);
+    /// assert_eq!(res, Err(GridIdsError::PointAlreadyExists));
+    /// ```
+    pub fn add_square_grid_with_ids(
+        &mut self,
+        ids: GridIds,
+        wrap: GridWrap,
+        default_terrain: TerrainType,
+        orthogonal_cost: Option<Weight>,
+        diagonal_cost: Option<Weight>,
+    ) -> Result<FnvHashMap<Vector2D<i32, i32>, PointId>, GridIdsError> {
+        let pos_to_id = self.add_grid_with_ids_internal(ids, default_terrain)?;
+        self.connect_square_grid(
+            &pos_to_id,
+            ids.offset,
+            ids.size(),
+            wrap,
+            orthogonal_cost,
+            diagonal_cost,
+        );
+        Ok(pos_to_id)
+    }
+
+    /// Adds a hexagonal grid of connected points, whose IDs are computed
+    /// from their coordinates by `ids`, see
+    /// [`add_square_grid_with_ids`](DijkstraMap::add_square_grid_with_ids).
+    ///
+    /// The other parameters are the same as
+    /// [`add_wrapping_hexagonal_grid`](DijkstraMap::add_wrapping_hexagonal_grid).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] without modifying the map if one of the IDs is
+    /// already taken, if the IDs overflow the range of [`PointId`], or if
+    /// `wrap.x` is [`true`] and the width of the grid is odd.
+    pub fn add_hexagonal_grid_with_ids(
+        &mut self,
+        ids: GridIds,
+        wrap: GridWrap,
+        default_terrain: TerrainType,
+        weight: Option<Weight>,
+    ) -> Result<FnvHashMap<Vector2D<i32, i32>, PointId>, GridIdsError> {
+        if wrap.x && ids.width & 1 == 1 {
+            return Err(GridIdsError::OddWrappingWidth);
+        }
+        let pos_to_id = self.add_grid_with_ids_internal(ids, default_terrain)?;
+        self.connect_hexagonal_grid(&pos_to_id, ids.offset, ids.size(), wrap, weight);
+        Ok(pos_to_id)
+    }
+}
+
+impl DijkstraMap {
+    /// Adds the hexagons of a grid, given their key and axial coordinates,
+    /// and connects neighbours.
+    fn add_hexagons<K: Copy + Eq + Hash>(
+        &mut self,
+        hexagons: Vec<(K, Vector2D<i32, i32>)>,
+        orientation: HexOrientation,
+        default_terrain: TerrainType,
+        weight: Option<Weight>,
+    ) -> FnvHashMap<K, PointId> {
+        let mut id = self.get_available_id(None);
+        let mut key_to_id = FnvHashMap::<K, PointId>::default();
+        let mut axial_to_id = FnvHashMap::<Vector2D<i32, i32>, PointId>::default();
+        for (key, axial) in hexagons {
+            id = self.get_available_id(Some(id));
+            self.add_point_replace(id, default_terrain);
+            let _ = self.set_point_position(id, Some(hexagon_center(axial, orientation)));
+            key_to_id.insert(key, id);
+            axial_to_id.insert(axial, id);
+        }
+
+        let weight = weight.unwrap_or(Weight(1.0));
+        if weight < Weight::infinity() {
+            for (&axial, &id_1) in axial_to_id.iter() {
+                for &direction in &AXIAL_DIRECTIONS {
+                    if let Some(&id_2) = axial_to_id.get(&(axial + direction)) {
                         // ignore error, we know it succeeded
-                        let _ = self.connect_points(id_1, *id_2, Some(weight), Some(false));
+                        let _ = self.connect_points(id_1, id_2, Some(weight), Some(false));
+                    }
+                }
+            }
+        }
+        key_to_id
+    }
+
+    /// Adds a hexagonal grid of connected points, with the given orientation
+    /// and coordinate system.
+    ///
+    /// # Parameters
+    ///
+    /// - `width` : Width of the grid.
+    /// - `height` : Height of the grid.
+    /// - `initial_offset` (default : `(0, 0)`) : coordinates of the top-left
+    ///   hexagon.
+    /// - `layout` : orientation of the hexagons, and coordinate system of the
+    ///   keys of the returned [`HashMap`]. With offset coordinates, the grid
+    ///   is a rectangle. With axial coordinates, it is a parallelogram.
+    /// - `default_terrain` : [`TerrainType`] to use for all points of the grid.
+    /// - `weight` (default : `1.0`) : specifies cost of connections. \
+    ///   If `weight` is [`INFINITY`] or [`NAN`], points are not connected.
+    ///
+    /// The [position](DijkstraMap::get_point_position) of each point is the
+    /// center of its hexagon, with a distance of `1.0` between neighbours.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`HashMap`] where keys are coordinates of points
+    /// ([`Vector2D`]) and values are the corresponding point IDs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, HexCoordinates, HexOrientation, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let grid = dijkstra_map.add_hexagonal_grid_with_layout(
+    ///     3,
+    ///     3,
+    ///     None,
+    ///     (HexOrientation::FlatTop, HexCoordinates::OddOffset),
+    ///     TerrainType::DefaultTerrain,
+    ///     None,
+    /// );
+    /// // in flat-top, odd-offset coordinates, odd columns are shifted down
+    /// let center = grid[&Vector2D::new(1, 1)];
+    /// assert!(dijkstra_map.has_connection(center, grid[&Vector2D::new(0, 2)]));
+    /// assert!(!dijkstra_map.has_connection(center, grid[&Vector2D::new(0, 0)]));
+    /// ```
+    ///
+    /// [`HashMap`]: FnvHashMap
+    /// [`INFINITY`]: Weight::infinity
+    /// [`NAN`]: f32::NAN
+    pub fn add_hexagonal_grid_with_layout(
+        &mut self,
+        width: usize,
+        height: usize,
+        initial_offset: Option<Vector2D<usize, usize>>,
+        layout: (HexOrientation, HexCoordinates),
+        default_terrain: TerrainType,
+        weight: Option<Weight>,
+    ) -> FnvHashMap<Vector2D<i32, i32>, PointId> {
+        let (orientation, coordinates) = layout;
+        let initial_offset = initial_offset.unwrap_or_default();
+        let mut hexagons = Vec::with_capacity(width * height);
+        for x in initial_offset.x..initial_offset.x + width {
+            for y in initial_offset.y..initial_offset.y + height {
+                let key = Vector2D::<i32, i32>::new(x as i32, y as i32);
+                let axial = match coordinates {
+                    HexCoordinates::OddOffset => offset_to_axial(key, orientation, true),
+                    HexCoordinates::EvenOffset => offset_to_axial(key, orientation, false),
+                    HexCoordinates::Axial => key,
+                };
+                hexagons.push((key, axial));
+            }
+        }
+        self.add_hexagons(hexagons, orientation, default_terrain, weight)
+    }
+
+    /// Adds a hexagonal grid of connected points, in cube coordinates.
+    ///
+    /// This is the same grid as
+    /// [`add_hexagonal_grid_with_layout`](DijkstraMap::add_hexagonal_grid_with_layout)
+    /// with [axial coordinates](HexCoordinates::Axial), but each key
+    /// `(q, r)` becomes `(q, r, -q - r)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, HexOrientation, TerrainType};
+    /// use euclid::Vector3D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let grid = dijkstra_map.add_cube_hexagonal_grid(
+    ///     3,
+    ///     3,
+    ///     None,
+    ///     HexOrientation::PointyTop,
+    ///     TerrainType::DefaultTerrain,
+    ///     None,
+    /// );
+    /// let center = grid[&Vector3D::new(1, 1, -2)];
+    /// assert!(dijkstra_map.has_connection(center, grid[&Vector3D::new(2, 0, -2)]));
+    /// assert!(!dijkstra_map.has_connection(center, grid[&Vector3D::new(2, 2, -4)]));
+    /// ```
+    pub fn add_cube_hexagonal_grid(
+        &mut self,
+        width: usize,
+        height: usize,
+        initial_offset: Option<Vector2D<usize, usize>>,
+        orientation: HexOrientation,
+        default_terrain: TerrainType,
+        weight: Option<Weight>,
+    ) -> FnvHashMap<Vector3D<i32, i32>, PointId> {
+        let initial_offset = initial_offset.unwrap_or_default();
+        let mut hexagons = Vec::with_capacity(width * height);
+        for q in initial_offset.x as i32..(initial_offset.x + width) as i32 {
+            for r in initial_offset.y as i32..(initial_offset.y + height) as i32 {
+                hexagons.push((Vector3D::new(q, r, -q - r), Vector2D::new(q, r)));
+            }
+        }
+        self.add_hexagons(hexagons, orientation, default_terrain, weight)
+    }
+
+    /// Adds an isometric grid of connected points, in the diamond layout of
+    /// Godot's isometric `TileMap`.
+    ///
+    /// This is a [square grid](DijkstraMap::add_square_grid) (with the same
+    /// parameters and keys), seen at an angle : the x axis goes down-right,
+    /// and the y axis down-left. The
+    /// [position](DijkstraMap::get_point_position) of each point is the
+    /// center of its tile on screen, for tiles of width `1.0` and height
+    /// `0.5`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, Position, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let grid = dijkstra_map.add_isometric_grid(3, 3, None, TerrainType::DefaultTerrain, None, None);
+    /// // tiles sharing a side are connected, as in a square grid
+    /// let tile = grid[&Vector2D::new(1, 1)];
+    /// assert!(dijkstra_map.has_connection(tile, grid[&Vector2D::new(2, 1)]));
+    /// assert!(!dijkstra_map.has_connection(tile, grid[&Vector2D::new(2, 2)]));
+    /// // (1, 1) is one tile height below (0, 0) on screen
+    /// assert_eq!(dijkstra_map.get_point_position(tile), Some(Position::new(0.0, 0.5, 0.0)));
+    /// ```
+    pub fn add_isometric_grid(
+        &mut self,
+        width: usize,
+        height: usize,
+        initial_offset: Option<Vector2D<usize, usize>>,
+        default_terrain: TerrainType,
+        orthogonal_cost: Option<Weight>,
+        diagonal_cost: Option<Weight>,
+    ) -> FnvHashMap<Vector2D<i32, i32>, PointId> {
+        let initial_offset = initial_offset.unwrap_or_default();
+        let origin = Vector2D::new(initial_offset.x as i32, initial_offset.y as i32);
+        let size = Vector2D::new(width as i32, height as i32);
+        let pos_to_id = self.add_grid_internal(
+            initial_offset.x,
+            initial_offset.y,
+            width,
+            height,
+            default_terrain,
+            |pos| {
+                let (x, y) = (pos.x as f32, pos.y as f32);
+                isometric_center((x - y) / 2.0, (x + y) / 2.0)
+            },
+        );
+        self.connect_square_grid(
+            &pos_to_id,
+            origin,
+            size,
+            GridWrap::default(),
+            orthogonal_cost,
+            diagonal_cost,
+        );
+        pos_to_id
+    }
+
+    /// Adds an isometric grid of connected points, in the staggered layout
+    /// of Godot's isometric `TileMap` with a half offset : tiles form
+    /// horizontal rows, and odd rows are shifted right by half a tile.
+    ///
+    /// # Parameters
+    ///
+    /// - `width` : Width of the grid.
+    /// - `height` : Height of the grid.
+    /// - `initial_offset` (default : `(0, 0)`) : specifies offset of the grid.
+    /// - `default_terrain` : [`TerrainType`] to use for all points of the grid.
+    /// - `orthogonal_cost` (default : `1.0`) : specifies cost of connections
+    ///   between tiles sharing a side (up-left, up-right, down-left and
+    ///   down-right). \
+    ///   If `orthogonal_cost` is [`INFINITY`] or [`NAN`], these connections are disabled.
+    /// - `diagonal_cost` (default : [`INFINITY`]) : specifies cost of
+    ///   connections between tiles sharing a corner (left, right, up and
+    ///   down). \
+    ///   If `diagonal_cost` is [`INFINITY`] or [`NAN`], these connections are disabled.
+    ///
+    /// The [position](DijkstraMap::get_point_position) of each point is the
+    /// center of its tile on screen, for tiles of width `1.0` and height
+    /// `0.5`.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`HashMap`] where keys are coordinates of points
+    /// ([`Vector2D`]) and values are the corresponding point IDs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let grid = dijkstra_map.add_staggered_isometric_grid(3, 3, None, TerrainType::DefaultTerrain, None, None);
+    /// // the odd row 1 is shifted right, so (1, 1) touches (1, 0) and (2, 0)
+    /// let tile = grid[&Vector2D::new(1, 1)];
+    /// assert!(dijkstra_map.has_connection(tile, grid[&Vector2D::new(2, 0)]));
+    /// assert!(!dijkstra_map.has_connection(tile, grid[&Vector2D::new(0, 0)]));
+    /// ```
+    ///
+    /// [`HashMap`]: FnvHashMap
+    /// [`INFINITY`]: Weight::infinity
+    /// [`NAN`]: f32::NAN
+    pub fn add_staggered_isometric_grid(
+        &mut self,
+        width: usize,
+        height: usize,
+        initial_offset: Option<Vector2D<usize, usize>>,
+        default_terrain: TerrainType,
+        orthogonal_cost: Option<Weight>,
+        diagonal_cost: Option<Weight>,
+    ) -> FnvHashMap<Vector2D<i32, i32>, PointId> {
+        let initial_offset = initial_offset.unwrap_or_default();
+        let pos_to_id = self.add_grid_internal(
+            initial_offset.x,
+            initial_offset.y,
+            width,
+            height,
+            default_terrain,
+            |pos| {
+                let odd_row = pos.y.rem_euclid(2) as f32;
+                isometric_center(pos.x as f32 + odd_row / 2.0, pos.y as f32 / 2.0)
+            },
+        );
+
+        let orthogonal_cost = orthogonal_cost.unwrap_or(Weight(1.0));
+        let diagonal_cost = diagonal_cost.unwrap_or(Weight(f32::INFINITY));
+        /// Connections through sides, for even and odd rows
+        const SIDES: [[Vector2D<i32, i32>; 4]; 2] = [
+            [
+                Vector2D::<i32, i32>::new(-1, -1),
+                Vector2D::<i32, i32>::new(0, -1),
+                Vector2D::<i32, i32>::new(-1, 1),
+                Vector2D::<i32, i32>::new(0, 1),
+            ],
+            [
+                Vector2D::<i32, i32>::new(0, -1),
+                Vector2D::<i32, i32>::new(1, -1),
+                Vector2D::<i32, i32>::new(0, 1),
+                Vector2D::<i32, i32>::new(1, 1),
+            ],
+        ];
+        /// Connections through corners
+        const CORNERS: [Vector2D<i32, i32>; 4] = [
+            Vector2D::<i32, i32>::new(1, 0),
+            Vector2D::<i32, i32>::new(-1, 0),
+            Vector2D::<i32, i32>::new(0, 2),
+            Vector2D::<i32, i32>::new(0, -2),
+        ];
+
+        for (&pos, &id_1) in pos_to_id.iter() {
+            let odd_row = pos.y.rem_euclid(2);
+            if orthogonal_cost < Weight(f32::INFINITY) {
+                for &offs in &SIDES[odd_row as usize] {
+                    if let Some(&id_2) = pos_to_id.get(&(pos + offs)) {
+                        // ignore error, we know it succeeded
+                        let _ = self.connect_points(id_1, id_2, Some(orthogonal_cost), Some(false));
+                    }
+                }
+            }
+            if diagonal_cost < Weight(f32::INFINITY) {
+                for &offs in &CORNERS {
+                    if let Some(&id_2) = pos_to_id.get(&(pos + offs)) {
+                        // ignore error, we know it succeeded
+                        let _ = self.connect_points(id_1, id_2, Some(diagonal_cost), Some(false));
                     }
                 }
             }
@@ -221,6 +1040,310 @@ impl DijkstraMap {
 mod test {
     use super::*;
 
+    /// Returns the number of connections from each point.
+    fn degrees<K: Copy + Eq + Hash>(
+        d: &DijkstraMap,
+        grid: &FnvHashMap<K, PointId>,
+    ) -> FnvHashMap<K, usize> {
+        grid.iter()
+            .map(|(&key, &id)| {
+                let degree = grid
+                    .values()
+                    .filter(|&&other| d.has_connection(id, other))
+                    .count();
+                (key, degree)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hexagonal_grid_picks_neighbours_by_column_parity() {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_hexagonal_grid(4, 4, None, TerrainType::DefaultTerrain, None);
+        // (1, 2) is in an odd column
+        let id = grid[&Vector2D::new(1, 2)];
+        for &(x, y) in &[(1, 1), (2, 1), (0, 2), (2, 2), (1, 3), (2, 3)] {
+            assert!(d.has_connection(id, grid[&Vector2D::new(x, y)]));
+        }
+        assert!(!d.has_connection(id, grid[&Vector2D::new(0, 1)]));
+        assert!(!d.has_connection(id, grid[&Vector2D::new(0, 3)]));
+    }
+
+    #[test]
+    fn wrapping_hexagonal_grids_only_add_seam_connections() {
+        let offset = Vector2D::new(3, 2);
+        let mut d = DijkstraMap::new();
+        let grid = d.add_hexagonal_grid(
+            6,
+            5,
+            Some(offset.to_usize()),
+            TerrainType::DefaultTerrain,
+            None,
+        );
+        for &(x, y) in &[(true, false), (false, true), (true, true)] {
+            let mut wrapped = DijkstraMap::new();
+            let wrapped_grid = wrapped
+                .add_wrapping_hexagonal_grid(
+                    6,
+                    5,
+                    Some(offset.to_usize()),
+                    GridWrap { x, y },
+                    TerrainType::DefaultTerrain,
+                    None,
+                )
+                .unwrap();
+            for (&cell_1, &id_1) in wrapped_grid.iter() {
+                for (&cell_2, &id_2) in wrapped_grid.iter() {
+                    let delta = cell_2 - cell_1;
+                    let crosses_seam = (x && delta.x.abs() == 5) || (y && delta.y.abs() == 4);
+                    if !crosses_seam {
+                        assert_eq!(
+                            wrapped.has_connection(id_1, id_2),
+                            d.has_connection(grid[&cell_1], grid[&cell_2])
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wrapping_grids_have_no_edges() {
+        let wrap = GridWrap { x: true, y: true };
+        let mut d = DijkstraMap::new();
+        let grid = d.add_wrapping_square_grid(
+            4,
+            3,
+            Some((2, 5).into()),
+            wrap,
+            TerrainType::DefaultTerrain,
+            None,
+            Some(Weight(1.5)),
+        );
+        assert!(degrees(&d, &grid).values().all(|&degree| degree == 8));
+        assert!(d.has_connection(grid[&Vector2D::new(5, 7)], grid[&Vector2D::new(2, 5)]));
+
+        let mut d = DijkstraMap::new();
+        let grid = d
+            .add_wrapping_hexagonal_grid(
+                4,
+                5,
+                Some((1, 1).into()),
+                wrap,
+                TerrainType::DefaultTerrain,
+                None,
+            )
+            .unwrap();
+        assert!(degrees(&d, &grid).values().all(|&degree| degree == 6));
+        // connections across the seams are bidirectional
+        for &id_1 in grid.values() {
+            for &id_2 in grid.values() {
+                assert_eq!(d.has_connection(id_1, id_2), d.has_connection(id_2, id_1));
+            }
+        }
+    }
+
+    #[test]
+    fn thin_wrapping_grids_do_not_connect_points_to_themselves() {
+        let mut d = DijkstraMap::new();
+        let wrap = GridWrap { x: true, y: false };
+        let grid =
+            d.add_wrapping_square_grid(1, 3, None, wrap, TerrainType::DefaultTerrain, None, None);
+        assert!(grid.values().all(|&id| !d.has_connection(id, id)));
+        assert_eq!(degrees(&d, &grid)[&Vector2D::new(0, 1)], 2);
+    }
+
+    #[test]
+    fn hexagonal_grids_with_odd_width_can_not_wrap_horizontally() {
+        let mut d = DijkstraMap::new();
+        let wrap = GridWrap { x: true, y: false };
+        let res =
+            d.add_wrapping_hexagonal_grid(3, 4, None, wrap, TerrainType::DefaultTerrain, None);
+        assert_eq!(res, Err(OddWrappingWidth));
+        let ids = GridIds {
+            base: PointId(0),
+            offset: Vector2D::new(0, 0),
+            width: 3,
+            height: 4,
+        };
+        let res = d.add_hexagonal_grid_with_ids(ids, wrap, TerrainType::DefaultTerrain, None);
+        assert_eq!(res, Err(GridIdsError::OddWrappingWidth));
+        assert_eq!(d.points().count(), 0);
+    }
+
+    #[test]
+    fn grid_ids_are_computed_from_coordinates() {
+        let ids = GridIds {
+            base: PointId(-10),
+            offset: Vector2D::new(-2, 4),
+            width: 4,
+            height: 3,
+        };
+        let mut d = DijkstraMap::new();
+        let grid = d
+            .add_hexagonal_grid_with_ids(
+                ids,
+                GridWrap::default(),
+                TerrainType::DefaultTerrain,
+                None,
+            )
+            .unwrap();
+        assert_eq!(grid.len(), 12);
+        for (&cell, &id) in grid.iter() {
+            assert_eq!(ids.id_of(cell), Some(id));
+            assert_eq!(ids.cell_of(id), Some(cell));
+        }
+        assert_eq!(grid[&Vector2D::new(-1, 5)], PointId(-5));
+        assert_eq!(ids.id_of(Vector2D::new(2, 3)), None);
+        assert_eq!(ids.cell_of(PointId(2)), None);
+        assert_eq!(
+            ids.cells_of(&[PointId(-10), PointId(-11)]),
+            vec![Some(Vector2D::new(-2, 4)), None]
+        );
+        // same connections as a grid with allocated IDs
+        let mut other = DijkstraMap::new();
+        let other_grid = other.add_hexagonal_grid(4, 3, None, TerrainType::DefaultTerrain, None);
+        let (degrees, other_degrees) = (degrees(&d, &grid), degrees(&other, &other_grid));
+        for (&cell, &degree) in other_degrees.iter() {
+            assert_eq!(degrees[&(cell + ids.offset)], degree);
+        }
+    }
+
+    #[test]
+    fn taken_grid_ids_are_an_error() {
+        let mut d = DijkstraMap::new();
+        d.add_point(PointId(7), TerrainType::DefaultTerrain)
+            .unwrap();
+        let ids = GridIds {
+            base: PointId(0),
+            offset: Vector2D::new(0, 0),
+            width: 3,
+            height: 3,
+        };
+        let res = d.add_square_grid_with_ids(
+            ids,
+            GridWrap::default(),
+            TerrainType::DefaultTerrain,
+            None,
+            None,
+        );
+        assert_eq!(res, Err(GridIdsError::PointAlreadyExists));
+        assert!(!d.has_point(PointId(0)));
+    }
+
+    #[test]
+    fn overflowing_grid_ids_are_an_error() {
+        let mut d = DijkstraMap::new();
+        let ids = GridIds {
+            base: PointId(i32::MAX - 5),
+            offset: Vector2D::new(0, 0),
+            width: 3,
+            height: 3,
+        };
+        let res = d.add_hexagonal_grid_with_ids(
+            ids,
+            GridWrap::default(),
+            TerrainType::DefaultTerrain,
+            None,
+        );
+        assert_eq!(res, Err(GridIdsError::IdOverflow));
+        assert_eq!(d.points().count(), 0);
+    }
+
+    #[test]
+    fn hexagonal_layouts_have_six_neighbours() {
+        for &orientation in &[HexOrientation::PointyTop, HexOrientation::FlatTop] {
+            for &coordinates in &[
+                HexCoordinates::OddOffset,
+                HexCoordinates::EvenOffset,
+                HexCoordinates::Axial,
+            ] {
+                let mut d = DijkstraMap::new();
+                let grid = d.add_hexagonal_grid_with_layout(
+                    5,
+                    5,
+                    Some((2, 3).into()),
+                    (orientation, coordinates),
+                    TerrainType::DefaultTerrain,
+                    None,
+                );
+                assert_eq!(grid.len(), 25);
+                let degrees = degrees(&d, &grid);
+                assert_eq!(degrees[&Vector2D::new(4, 5)], 6);
+                // neighbours are at a distance of 1
+                let center = d.get_point_position(grid[&Vector2D::new(4, 5)]).unwrap();
+                for &other in grid.values() {
+                    if d.has_connection(grid[&Vector2D::new(4, 5)], other) {
+                        let distance = (d.get_point_position(other).unwrap() - center).length();
+                        assert!((distance - 1.0).abs() < 1e-5);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn offset_coordinates_are_shifted() {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_hexagonal_grid_with_layout(
+            3,
+            3,
+            None,
+            (HexOrientation::PointyTop, HexCoordinates::EvenOffset),
+            TerrainType::DefaultTerrain,
+            None,
+        );
+        // even rows are shifted right, so (1, 1) touches (0, 0) and (1, 0)
+        let id = grid[&Vector2D::new(1, 1)];
+        assert!(d.has_connection(id, grid[&Vector2D::new(0, 0)]));
+        assert!(d.has_connection(id, grid[&Vector2D::new(1, 0)]));
+        assert!(!d.has_connection(id, grid[&Vector2D::new(2, 0)]));
+    }
+
+    #[test]
+    fn cube_coordinates_sum_to_zero() {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_cube_hexagonal_grid(
+            4,
+            4,
+            None,
+            HexOrientation::FlatTop,
+            TerrainType::DefaultTerrain,
+            None,
+        );
+        assert!(grid.keys().all(|key| key.x + key.y + key.z == 0));
+        assert_eq!(degrees(&d, &grid)[&Vector3D::new(1, 1, -2)], 6);
+    }
+
+    #[test]
+    fn isometric_grids_connect_tiles_sharing_a_side() {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_isometric_grid(3, 3, None, TerrainType::DefaultTerrain, None, None);
+        assert_eq!(degrees(&d, &grid)[&Vector2D::new(1, 1)], 4);
+        // the x axis goes down-right
+        assert_eq!(
+            d.get_point_position(grid[&Vector2D::new(1, 0)]),
+            Some(Position::new(0.5, 0.25, 0.0))
+        );
+
+        let mut d = DijkstraMap::new();
+        let grid = d.add_staggered_isometric_grid(
+            4,
+            7,
+            None,
+            TerrainType::DefaultTerrain,
+            None,
+            Some(Weight(2.0)),
+        );
+        let degrees = degrees(&d, &grid);
+        assert_eq!(degrees[&Vector2D::new(1, 2)], 8);
+        assert_eq!(degrees[&Vector2D::new(2, 3)], 8);
+        // tiles sharing a side are half a tile apart horizontally and vertically
+        let a = d.get_point_position(grid[&Vector2D::new(1, 2)]).unwrap();
+        let b = d.get_point_position(grid[&Vector2D::new(1, 3)]).unwrap();
+        assert_eq!(b - a, Position::new(0.5, 0.25, 0.0).to_vector());
+    }
+
     #[test]
     fn square_grid_works() {
         let mut d = DijkstraMap::new();
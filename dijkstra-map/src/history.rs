@@ -0,0 +1,527 @@
+use super::chunks::GridCells;
+use super::spatial::{SpatialIndex, StoredPosition};
+use super::{DijkstraMap, PointId, PointInfo, SharedMap, SharedSet, TerrainType, Weight};
+use euclid::Vector2D;
+use std::sync::Arc;
+
+/// Error returned by [`DijkstraMap::begin_transaction`] when a transaction is
+/// already open.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TransactionAlreadyOpen;
+
+/// Error returned by [`DijkstraMap::commit_transaction`] and
+/// [`DijkstraMap::rollback_transaction`] when no transaction is open.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NoOpenTransaction;
+
+/// Elementary modification of the graph, with enough information to revert
+/// it.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Edit {
+    /// [`add_point`](DijkstraMap::add_point).
+    AddPoint {
+        id: PointId,
+        terrain_type: TerrainType,
+    },
+    /// [`add_point_replace`](DijkstraMap::add_point_replace).
+    ReplacePoint {
+        id: PointId,
+        terrain_type: TerrainType,
+        previous: Option<PointInfo>,
+        previous_position: Option<StoredPosition>,
+        previous_cell: Option<Vector2D<i32, i32>>,
+    },
+    /// [`remove_point`](DijkstraMap::remove_point).
+    RemovePoint {
+        id: PointId,
+        info: PointInfo,
+        disabled: bool,
+        position: Option<StoredPosition>,
+        cell: Option<Vector2D<i32, i32>>,
+    },
+    /// Unidirectional [`connect_points`](DijkstraMap::connect_points).
+    Connect {
+        source: PointId,
+        target: PointId,
+        weight: Weight,
+        previous: Option<Weight>,
+    },
+    /// Unidirectional [`remove_connection`](DijkstraMap::remove_connection).
+    Disconnect {
+        source: PointId,
+        target: PointId,
+        previous: Option<Weight>,
+    },
+    /// [`disable_point`](DijkstraMap::disable_point) or
+    /// [`enable_point`](DijkstraMap::enable_point).
+    SetDisabled {
+        point: PointId,
+        disabled: bool,
+        previous: bool,
+    },
+    /// [`set_terrain_for_point`](DijkstraMap::set_terrain_for_point).
+    SetTerrain {
+        point: PointId,
+        terrain_type: TerrainType,
+        previous: TerrainType,
+    },
+    /// [`set_point_position`](DijkstraMap::set_point_position) or
+    /// [`set_point_position_3d`](DijkstraMap::set_point_position_3d).
+    SetPosition {
+        point: PointId,
+        position: Option<StoredPosition>,
+        previous: Option<StoredPosition>,
+    },
+    /// Cell of a point in the persistent grid, set by
+    /// [`extend_square_grid`](DijkstraMap::extend_square_grid).
+    SetGridCell {
+        point: PointId,
+        cell: Option<Vector2D<i32, i32>>,
+        previous: Option<Vector2D<i32, i32>>,
+    },
+    /// [`clear`](DijkstraMap::clear).
+    Clear {
+        points: SharedMap<PointId, PointInfo>,
+        disabled_points: SharedSet<PointId>,
+        spatial: Arc<SpatialIndex>,
+        grid_cells: Arc<GridCells>,
+    },
+}
+
+/// Log of the transactions applied to a [`DijkstraMap`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct History {
+    /// Edits of the open transaction, if any.
+    transaction: Option<Vec<Edit>>,
+    /// Committed transactions, most recent last.
+    undo: Vec<Vec<Edit>>,
+    /// Undone transactions, most recently undone last.
+    redo: Vec<Vec<Edit>>,
+}
+
+impl DijkstraMap {
+    /// Records an edit in the open transaction.
+    ///
+    /// `edit` is only evaluated if a transaction is open. Else, the undo and
+    /// redo history is discarded, since it no longer applies to the graph.
+    pub(crate) fn record_edit(&mut self, edit: impl FnOnce() -> Edit) {
+        match &mut self.history.transaction {
+            Some(edits) => edits.push(edit()),
+            None => self.clear_history(),
+        }
+    }
+
+    /// Opens a new transaction.
+    ///
+    /// Every modification of the graph is then recorded, until the
+    /// transaction is either :
+    /// - committed with [`commit_transaction`](DijkstraMap::commit_transaction),
+    ///   after which it can be reverted as a whole with
+    ///   [`undo`](DijkstraMap::undo).
+    /// - reverted with
+    ///   [`rollback_transaction`](DijkstraMap::rollback_transaction).
+    ///
+    /// Modifications made outside of transactions are not recorded, and
+    /// discard the undo and redo history.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if a transaction is already open.
+    pub fn begin_transaction(&mut self) -> Result<(), TransactionAlreadyOpen> {
+        if self.history.transaction.is_some() {
+            return Err(TransactionAlreadyOpen);
+        }
+        self.history.transaction = Some(Vec::new());
+        Ok(())
+    }
+
+    /// Closes the open transaction, and pushes it on the undo history.
+    ///
+    /// Empty transactions are not pushed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if no transaction is open.
+    pub fn commit_transaction(&mut self) -> Result<(), NoOpenTransaction> {
+        let edits = self.history.transaction.take().ok_or(NoOpenTransaction)?;
+        if !edits.is_empty() {
+            self.history.undo.push(edits);
+            self.history.redo.clear();
+        }
+        Ok(())
+    }
+
+    /// Reverts every modification made since the open transaction began, and
+    /// closes it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if no transaction is open.
+    pub fn rollback_transaction(&mut self) -> Result<(), NoOpenTransaction> {
+        let edits = self.history.transaction.take().ok_or(NoOpenTransaction)?;
+        self.revert_edits(&edits);
+        Ok(())
+    }
+
+    /// Returns [`true`] if a transaction is open.
+    pub fn is_transaction_open(&self) -> bool {
+        self.history.transaction.is_some()
+    }
+
+    /// Returns [`true`] if there is a committed transaction to
+    /// [`undo`](DijkstraMap::undo), and no open transaction.
+    pub fn can_undo(&self) -> bool {
+        self.history.transaction.is_none() && !self.history.undo.is_empty()
+    }
+
+    /// Returns [`true`] if there is an undone transaction to
+    /// [`redo`](DijkstraMap::redo), and no open transaction.
+    pub fn can_redo(&self) -> bool {
+        self.history.transaction.is_none() && !self.history.redo.is_empty()
+    }
+
+    /// Reverts the last committed transaction.
+    ///
+    /// Returns [`false`] without modifying the map if there is nothing to
+    /// undo, or if a transaction is open.
+    pub fn undo(&mut self) -> bool {
+        if !self.can_undo() {
+            return false;
+        }
+        let edits = self.history.undo.pop().unwrap();
+        self.revert_edits(&edits);
+        self.history.redo.push(edits);
+        true
+    }
+
+    /// Re-applies the last undone transaction.
+    ///
+    /// Returns [`false`] without modifying the map if there is nothing to
+    /// redo, or if a transaction is open.
+    pub fn redo(&mut self) -> bool {
+        if !self.can_redo() {
+            return false;
+        }
+        let edits = self.history.redo.pop().unwrap();
+        self.reapply_edits(&edits);
+        self.history.undo.push(edits);
+        true
+    }
+
+    /// Discards the undo and redo history.
+    ///
+    /// The open transaction, if any, is unaffected.
+    pub fn clear_history(&mut self) {
+        self.history.undo.clear();
+        self.history.redo.clear();
+    }
+
+    /// Reverts `edits`, most recent first.
+    fn revert_edits(&mut self, edits: &[Edit]) {
+        // do not record the edits made while reverting
+        let history = std::mem::take(&mut self.history);
+        for edit in edits.iter().rev() {
+            // errors are ignored : points may have been modified outside of
+            // transactions in the meantime.
+            match edit {
+                Edit::AddPoint { id, .. } => {
+                    self.remove_point(*id);
+                }
+                Edit::ReplacePoint {
+                    id,
+                    previous,
+                    previous_position,
+                    previous_cell,
+                    ..
+                } => match previous {
+                    Some(info) => {
+                        self.points.insert(*id, info.clone());
+                        // the neighbours' links may have been modified since
+                        // the point was replaced.
+                        let mut changed = vec![*id];
+                        for (&target, &weight) in info.connections.iter() {
+                            if let Some(target_info) = self.points.get_mut(&target) {
+                                target_info.reverse_connections.insert(*id, weight);
+                                changed.push(target);
+                            }
+                        }
+                        for (&source, &weight) in info.reverse_connections.iter() {
+                            if let Some(source_info) = self.points.get_mut(&source) {
+                                source_info.connections.insert(*id, weight);
+                                changed.push(source);
+                            }
+                        }
+                        Arc::make_mut(&mut self.spatial).set(*id, *previous_position);
+                        Arc::make_mut(&mut self.grid_cells).set(*id, *previous_cell);
+                        self.mark_changed(&changed);
+                    }
+                    None => {
+                        self.remove_point(*id);
+                    }
+                },
+                Edit::RemovePoint {
+                    id,
+                    info,
+                    disabled,
+                    position,
+                    cell,
+                } => {
+                    if self.add_point(*id, info.terrain_type).is_ok() {
+                        Arc::make_mut(&mut self.spatial).set(*id, *position);
+                        Arc::make_mut(&mut self.grid_cells).set(*id, *cell);
+                        for (&target, &weight) in info.connections.iter() {
+                            let _ = self.connect_points(*id, target, Some(weight), Some(false));
+                        }
+                        for (&source, &weight) in info.reverse_connections.iter() {
+                            let _ = self.connect_points(source, *id, Some(weight), Some(false));
+                        }
+                        if *disabled {
+                            let _ = self.disable_point(*id);
+                        }
+                    }
+                }
+                Edit::Connect {
+                    source,
+                    target,
+                    previous,
+                    ..
+                } => {
+                    let _ = match previous {
+                        Some(weight) => {
+                            self.connect_points(*source, *target, Some(*weight), Some(false))
+                        }
+                        None => self.remove_connection(*source, *target, Some(false)),
+                    };
+                }
+                Edit::Disconnect {
+                    source,
+                    target,
+                    previous,
+                } => {
+                    if let Some(weight) = previous {
+                        let _ = self.connect_points(*source, *target, Some(*weight), Some(false));
+                    }
+                }
+                Edit::SetDisabled {
+                    point, previous, ..
+                } => {
+                    let _ = if *previous {
+                        self.disable_point(*point)
+                    } else {
+                        self.enable_point(*point)
+                    };
+                }
+                Edit::SetTerrain {
+                    point, previous, ..
+                } => {
+                    let _ = self.set_terrain_for_point(*point, *previous);
+                }
+                Edit::SetPosition {
+                    point, previous, ..
+                } => {
+                    let _ = self.set_stored_position(*point, *previous);
+                }
+                Edit::SetGridCell {
+                    point, previous, ..
+                } => {
+                    if self.has_point(*point) {
+                        Arc::make_mut(&mut self.grid_cells).set(*point, *previous);
+                    }
+                }
+                Edit::Clear {
+                    points,
+                    disabled_points,
+                    spatial,
+                    grid_cells,
+                } => {
+                    self.points = points.clone();
+                    self.disabled_points = disabled_points.clone();
+                    self.spatial = spatial.clone();
+                    self.grid_cells = grid_cells.clone();
+                    let changed: Vec<PointId> = points.keys().copied().collect();
+                    self.mark_changed(&changed);
+                }
+            }
+        }
+        self.history = history;
+    }
+
+    /// Re-applies `edits`, least recent first.
+    fn reapply_edits(&mut self, edits: &[Edit]) {
+        // do not record the edits made while reapplying
+        let history = std::mem::take(&mut self.history);
+        for edit in edits {
+            match edit {
+                Edit::AddPoint { id, terrain_type } => {
+                    let _ = self.add_point(*id, *terrain_type);
+                }
+                Edit::ReplacePoint {
+                    id, terrain_type, ..
+                } => self.add_point_replace(*id, *terrain_type),
+                Edit::RemovePoint { id, .. } => {
+                    self.remove_point(*id);
+                }
+                Edit::Connect {
+                    source,
+                    target,
+                    weight,
+                    ..
+                } => {
+                    let _ = self.connect_points(*source, *target, Some(*weight), Some(false));
+                }
+                Edit::Disconnect { source, target, .. } => {
+                    let _ = self.remove_connection(*source, *target, Some(false));
+                }
+                Edit::SetDisabled {
+                    point, disabled, ..
+                } => {
+                    let _ = if *disabled {
+                        self.disable_point(*point)
+                    } else {
+                        self.enable_point(*point)
+                    };
+                }
+                Edit::SetTerrain {
+                    point,
+                    terrain_type,
+                    ..
+                } => {
+                    let _ = self.set_terrain_for_point(*point, *terrain_type);
+                }
+                Edit::SetPosition {
+                    point, position, ..
+                } => {
+                    let _ = self.set_stored_position(*point, *position);
+                }
+                Edit::SetGridCell { point, cell, .. } => {
+                    if self.has_point(*point) {
+                        Arc::make_mut(&mut self.grid_cells).set(*point, *cell);
+                    }
+                }
+                Edit::Clear { .. } => self.clear(),
+            }
+        }
+        self.history = history;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ID0: PointId = PointId(0);
+    const ID1: PointId = PointId(1);
+    const ID2: PointId = PointId(2);
+    const TERRAIN: TerrainType = TerrainType::DefaultTerrain;
+
+    /// Creates a new `DijkstraMap` with the connections :
+    ///
+    /// 0 <--> 1 --> 2
+    fn setup() -> DijkstraMap {
+        let mut d = DijkstraMap::new();
+        d.add_point(ID0, TERRAIN).unwrap();
+        d.add_point(ID1, TERRAIN).unwrap();
+        d.add_point(ID2, TERRAIN).unwrap();
+        d.connect_points(ID0, ID1, None, None).unwrap();
+        d.connect_points(ID1, ID2, Some(Weight(2.0)), Some(false))
+            .unwrap();
+        d
+    }
+
+    /// Compares the graphs of two maps.
+    fn assert_same_graph(a: &DijkstraMap, b: &DijkstraMap) {
+        assert_eq!(a.points, b.points);
+        assert_eq!(a.disabled_points, b.disabled_points);
+    }
+
+    #[test]
+    fn rollback_restores_graph() {
+        let mut d = setup();
+        let original = d.clone();
+        d.begin_transaction().unwrap();
+        d.remove_point(ID1);
+        d.add_point(PointId(3), TerrainType::Terrain(1)).unwrap();
+        d.connect_points(ID0, PointId(3), None, None).unwrap();
+        d.disable_point(ID2).unwrap();
+        d.set_terrain_for_point(ID0, TerrainType::Terrain(4))
+            .unwrap();
+        d.rollback_transaction().unwrap();
+        assert_same_graph(&d, &original);
+        assert!(!d.is_transaction_open());
+        assert!(!d.can_undo());
+    }
+
+    #[test]
+    fn undo_redo_step_by_step() {
+        let mut d = setup();
+        let step0 = d.clone();
+
+        d.begin_transaction().unwrap();
+        d.connect_points(ID2, ID1, Some(Weight(3.0)), Some(false))
+            .unwrap();
+        d.connect_points(ID1, ID2, Some(Weight(5.0)), Some(false))
+            .unwrap();
+        d.commit_transaction().unwrap();
+        let step1 = d.clone();
+
+        d.begin_transaction().unwrap();
+        d.remove_connection(ID0, ID1, None).unwrap();
+        d.clear();
+        d.commit_transaction().unwrap();
+        let step2 = d.clone();
+
+        assert!(d.undo());
+        assert_same_graph(&d, &step1);
+        assert!(d.undo());
+        assert_same_graph(&d, &step0);
+        assert!(!d.undo());
+        assert!(d.redo());
+        assert_same_graph(&d, &step1);
+        assert!(d.redo());
+        assert_same_graph(&d, &step2);
+        assert!(!d.redo());
+    }
+
+    #[test]
+    fn untracked_edits_discard_history() {
+        let mut d = setup();
+        d.begin_transaction().unwrap();
+        d.disable_point(ID0).unwrap();
+        d.commit_transaction().unwrap();
+        d.begin_transaction().unwrap();
+        d.disable_point(ID1).unwrap();
+        d.commit_transaction().unwrap();
+        assert!(d.undo());
+        assert!(d.can_undo());
+        assert!(d.can_redo());
+        d.enable_point(ID2).unwrap();
+        assert!(!d.can_undo());
+        assert!(!d.can_redo());
+    }
+
+    #[test]
+    fn undo_replace_point_restores_neighbours() {
+        let mut d = setup();
+        let original = d.clone();
+        d.begin_transaction().unwrap();
+        d.add_point_replace(ID1, TerrainType::Terrain(1));
+        d.connect_points(ID1, ID2, Some(Weight(5.0)), Some(false))
+            .unwrap();
+        d.commit_transaction().unwrap();
+        assert!(d.undo());
+        assert_same_graph(&d, &original);
+    }
+
+    #[test]
+    fn transactions_cannot_be_nested() {
+        let mut d = setup();
+        assert_eq!(d.commit_transaction(), Err(NoOpenTransaction));
+        assert_eq!(d.rollback_transaction(), Err(NoOpenTransaction));
+        d.begin_transaction().unwrap();
+        assert_eq!(d.begin_transaction(), Err(TransactionAlreadyOpen));
+        assert!(!d.undo());
+        d.commit_transaction().unwrap();
+        // empty transactions are not recorded
+        assert!(!d.can_undo());
+    }
+}
@@ -0,0 +1,436 @@
+use super::{DijkstraMap, FnvHashMap, GridWrap, PointId, Position, TerrainType, Weight};
+use euclid::Vector2D;
+use std::fmt;
+
+/// `(column, row)` coordinates of a cell of an ASCII-art grid.
+type Cell = Vector2D<i32, i32>;
+
+/// Error returned by the loaders of [`DijkstraMap`] (such as
+/// [`from_edge_list_csv`](DijkstraMap::from_edge_list_csv)) when the text is
+/// malformed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    /// Line of the malformed input, starting at `1`.
+    pub line: usize,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} : {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses the point ID `field` of line `line`.
+fn parse_id(field: &str, line: usize) -> Result<PointId, ParseError> {
+    field
+        .parse::<i32>()
+        .map(PointId)
+        .map_err(|_| ParseError::new(line, format!("invalid point ID `{}`", field)))
+}
+
+/// Parses the weight `field` of line `line`, which must be a non-negative
+/// number.
+fn parse_weight(field: &str, line: usize) -> Result<Weight, ParseError> {
+    match field.parse::<f32>() {
+        Ok(weight) if weight >= 0.0 => Ok(Weight(weight)),
+        _ => Err(ParseError::new(
+            line,
+            format!("invalid weight `{}`, expected a non-negative number", field),
+        )),
+    }
+}
+
+impl DijkstraMap {
+    /// Adds `id` with the default terrain if it does not exist yet.
+    fn add_point_if_missing(&mut self, id: PointId) {
+        if !self.has_point(id) {
+            // ignore error, we know it succeeded
+            let _ = self.add_point(id, TerrainType::DefaultTerrain);
+        }
+    }
+
+    /// Builds a map from an edge list in CSV format, one connection per
+    /// line : `source,target,weight[,bidirectional]`.
+    ///
+    /// - `source` and `target` are point IDs. Points are created with the
+    ///   default terrain when they first appear.
+    /// - `weight` is a non-negative number.
+    /// - `bidirectional` is `true`, `false`, `1` or `0`. It defaults to
+    ///   `default_bidirectional`.
+    ///
+    /// Fields may be surrounded by spaces or double quotes. Empty lines,
+    /// lines starting with `#` and a header line starting with `source` are
+    /// ignored. When a connection appears twice, the last one wins.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] with the line of the first malformed record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, PointId};
+    ///
+    /// let csv = "source,target,weight,bidirectional\n0,1,2.5\n1,2,1,true\n";
+    /// let dijkstra_map = DijkstraMap::from_edge_list_csv(csv, false).unwrap();
+    /// assert!(dijkstra_map.has_connection(PointId(0), PointId(1)));
+    /// assert!(!dijkstra_map.has_connection(PointId(1), PointId(0)));
+    /// assert!(dijkstra_map.has_connection(PointId(2), PointId(1)));
+    ///
+    /// let error = DijkstraMap::from_edge_list_csv("0,1,1\n0,x,1\n", false).unwrap_err();
+    /// assert_eq!(error.line, 2);
+    /// ```
+    pub fn from_edge_list_csv(
+        text: &str,
+        default_bidirectional: bool,
+    ) -> Result<DijkstraMap, ParseError> {
+        let mut dijkstra_map = DijkstraMap::new();
+        let mut first_record = true;
+        for (index, record) in text.lines().enumerate() {
+            let line = index + 1;
+            let record = record.trim();
+            if record.is_empty() || record.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = record
+                .split(',')
+                .map(|field| field.trim().trim_matches('"').trim())
+                .collect();
+            if first_record {
+                first_record = false;
+                if fields[0].eq_ignore_ascii_case("source") {
+                    continue;
+                }
+            }
+            if fields.len() != 3 && fields.len() != 4 {
+                return Err(ParseError::new(
+                    line,
+                    format!("expected 3 or 4 fields, found {}", fields.len()),
+                ));
+            }
+            let source = parse_id(fields[0], line)?;
+            let target = parse_id(fields[1], line)?;
+            let weight = parse_weight(fields[2], line)?;
+            let bidirectional = match fields.get(3).map(|field| field.to_ascii_lowercase()) {
+                None => default_bidirectional,
+                Some(field) if field == "true" || field == "1" => true,
+                Some(field) if field == "false" || field == "0" => false,
+                Some(field) => {
+                    return Err(ParseError::new(
+                        line,
+                        format!("invalid bidirectional flag `{}`", field),
+                    ))
+                }
+            };
+            dijkstra_map.add_point_if_missing(source);
+            dijkstra_map.add_point_if_missing(target);
+            // ignore error, we know it succeeded
+            let _ = dijkstra_map.connect_points(source, target, Some(weight), Some(bidirectional));
+        }
+        Ok(dijkstra_map)
+    }
+
+    /// Builds a map from a graph in the DIMACS shortest-path format (`.gr`
+    /// files of the 9th DIMACS implementation challenge).
+    ///
+    /// - `c ...` lines are comments.
+    /// - The problem line `p sp <points> <arcs>` creates the points `1` to
+    ///   `<points>`, with the default terrain. It must come before the arcs.
+    /// - Each arc line `a <source> <target> <weight>` adds a directed
+    ///   connection. Of parallel arcs, the lightest is kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] with the line of the first malformed line, or
+    /// of the problem line if the number of arcs does not match it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, PointId};
+    ///
+    /// let gr = "c tiny graph\np sp 3 2\na 1 2 4\na 2 3 1\n";
+    /// let dijkstra_map = DijkstraMap::from_dimacs(gr).unwrap();
+    /// assert!(dijkstra_map.has_point(PointId(3)));
+    /// assert!(dijkstra_map.has_connection(PointId(1), PointId(2)));
+    /// assert!(!dijkstra_map.has_connection(PointId(2), PointId(1)));
+    ///
+    /// let error = DijkstraMap::from_dimacs("p sp 3 1\na 1 4 1\n").unwrap_err();
+    /// assert_eq!(error.line, 2);
+    /// ```
+    pub fn from_dimacs(text: &str) -> Result<DijkstraMap, ParseError> {
+        let mut dijkstra_map = DijkstraMap::new();
+        // line of the problem line, point count and arc count
+        let mut problem: Option<(usize, i32, usize)> = None;
+        let mut arcs = 0;
+        for (index, record) in text.lines().enumerate() {
+            let line = index + 1;
+            let fields: Vec<&str> = record.split_whitespace().collect();
+            match fields.as_slice() {
+                [] | ["c", ..] => {}
+                ["p", kind, points, arc_count] => {
+                    if problem.is_some() {
+                        return Err(ParseError::new(line, "duplicate problem line"));
+                    }
+                    if *kind != "sp" {
+                        return Err(ParseError::new(
+                            line,
+                            format!("unsupported problem type `{}`, expected `sp`", kind),
+                        ));
+                    }
+                    let points = points
+                        .parse::<i32>()
+                        .ok()
+                        .filter(|&points| points >= 0)
+                        .ok_or_else(|| {
+                            ParseError::new(line, format!("invalid point count `{}`", points))
+                        })?;
+                    let arc_count = arc_count.parse::<usize>().map_err(|_| {
+                        ParseError::new(line, format!("invalid arc count `{}`", arc_count))
+                    })?;
+                    for id in 1..=points {
+                        // ignore error, we know it succeeded
+                        let _ = dijkstra_map.add_point(PointId(id), TerrainType::DefaultTerrain);
+                    }
+                    problem = Some((line, points, arc_count));
+                }
+                ["a", source, target, weight] => {
+                    if problem.is_none() {
+                        return Err(ParseError::new(line, "arc before the problem line"));
+                    }
+                    let source = parse_id(source, line)?;
+                    let target = parse_id(target, line)?;
+                    let weight = parse_weight(weight, line)?;
+                    for &point in [source, target].iter() {
+                        if !dijkstra_map.has_point(point) {
+                            return Err(ParseError::new(
+                                line,
+                                format!("point {} is out of range", i32::from(point)),
+                            ));
+                        }
+                    }
+                    let lighter = match dijkstra_map.points[&source].connections.get(&target) {
+                        Some(&previous) => weight < previous,
+                        None => true,
+                    };
+                    if lighter {
+                        // ignore error, we know it succeeded
+                        let _ =
+                            dijkstra_map.connect_points(source, target, Some(weight), Some(false));
+                    }
+                    arcs += 1;
+                }
+                _ => {
+                    return Err(ParseError::new(
+                        line,
+                        format!("unrecognized line `{}`", record.trim()),
+                    ))
+                }
+            }
+        }
+        match problem {
+            None => Err(ParseError::new(
+                text.lines().count().max(1),
+                "missing problem line",
+            )),
+            Some((line, _, arc_count)) if arc_count != arcs => Err(ParseError::new(
+                line,
+                format!("expected {} arcs, found {}", arc_count, arcs),
+            )),
+            Some(_) => Ok(dijkstra_map),
+        }
+    }
+
+    /// Builds a square grid from an ASCII-art map, one row per line.
+    ///
+    /// - `#` is a wall : no point is created.
+    /// - `.` is a point with the default terrain.
+    /// - A digit `0` to `9` is a point with that terrain.
+    ///
+    /// Rows may have different lengths, and trailing whitespace is ignored.
+    /// Point IDs are numbered from `0` in reading order, and the
+    /// [position](DijkstraMap::get_point_position) of each point is set to
+    /// its `(column, row)` coordinates.
+    ///
+    /// `orthogonal_cost` and `diagonal_cost` are the same as in
+    /// [`add_square_grid`](DijkstraMap::add_square_grid).
+    ///
+    /// # Returns
+    ///
+    /// Returns the map, along with a [`HashMap`] where keys are the
+    /// `(column, row)` coordinates of the points and values are their IDs.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] with the line of the first unknown
+    /// character.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let (dijkstra_map, pos_to_id) = DijkstraMap::from_ascii_grid("..#\n.2.\n", None, None).unwrap();
+    /// assert_eq!(pos_to_id.len(), 5);
+    /// let center = pos_to_id[&Vector2D::new(1, 1)];
+    /// assert_eq!(dijkstra_map.get_terrain_for_point(center), Some(TerrainType::Terrain(2)));
+    /// assert!(dijkstra_map.has_connection(pos_to_id[&Vector2D::new(1, 0)], center));
+    ///
+    /// let error = DijkstraMap::from_ascii_grid("..\n.?\n", None, None).unwrap_err();
+    /// assert_eq!(error.line, 2);
+    /// ```
+    ///
+    /// [`HashMap`]: FnvHashMap
+    pub fn from_ascii_grid(
+        text: &str,
+        orthogonal_cost: Option<Weight>,
+        diagonal_cost: Option<Weight>,
+    ) -> Result<(DijkstraMap, FnvHashMap<Cell, PointId>), ParseError> {
+        let mut dijkstra_map = DijkstraMap::new();
+        let mut pos_to_id = FnvHashMap::<Cell, PointId>::default();
+        let mut size = Cell::zero();
+        for (y, row) in text.lines().enumerate() {
+            for (x, character) in row.trim_end().chars().enumerate() {
+                let terrain = match character {
+                    '#' => continue,
+                    '.' => TerrainType::DefaultTerrain,
+                    '0'..='9' => TerrainType::Terrain(character as i32 - '0' as i32),
+                    _ => {
+                        return Err(ParseError::new(
+                            y + 1,
+                            format!("unknown character `{}` in column {}", character, x + 1),
+                        ))
+                    }
+                };
+                let pos = Cell::new(x as i32, y as i32);
+                let id = PointId(pos_to_id.len() as i32);
+                // ignore error, we know it succeeded
+                let _ = dijkstra_map.add_point(id, terrain);
+                let _ = dijkstra_map
+                    .set_point_position(id, Some(Position::new(pos.x as f32, pos.y as f32, 0.0)));
+                pos_to_id.insert(pos, id);
+                size = Cell::new(size.x.max(pos.x + 1), pos.y + 1);
+            }
+        }
+        dijkstra_map.connect_square_grid(
+            &pos_to_id,
+            Vector2D::zero(),
+            size,
+            GridWrap::default(),
+            orthogonal_cost,
+            diagonal_cost,
+        );
+        Ok((dijkstra_map, pos_to_id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn weight(d: &DijkstraMap, source: i32, target: i32) -> Option<Weight> {
+        d.points[&PointId(source)]
+            .connections
+            .get(&PointId(target))
+            .copied()
+    }
+
+    #[test]
+    fn csv_edge_lists_are_loaded() {
+        let csv =
+            "# fixture\n\"source\", \"target\", \"weight\"\n\n 3 , 5 , 1.5 \n5,7,2,1\n3,5,4\n";
+        let d = DijkstraMap::from_edge_list_csv(csv, false).unwrap();
+        assert_eq!(d.points().count(), 3);
+        assert_eq!(weight(&d, 3, 5), Some(Weight(4.0)));
+        assert_eq!(weight(&d, 5, 3), None);
+        assert_eq!(weight(&d, 7, 5), Some(Weight(2.0)));
+
+        let d = DijkstraMap::from_edge_list_csv("0,1,1\n1,2,1,false", true).unwrap();
+        assert_eq!(weight(&d, 1, 0), Some(Weight(1.0)));
+        assert_eq!(weight(&d, 2, 1), None);
+    }
+
+    #[test]
+    fn malformed_csv_reports_the_line() {
+        for &(csv, line) in [
+            ("0,1\n", 1),
+            ("0,1,1\n\n0,1,-1\n", 3),
+            ("0,1,1\n0,1,1,maybe\n", 2),
+            ("0,1,1,true,false\n", 1),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                DijkstraMap::from_edge_list_csv(csv, false)
+                    .unwrap_err()
+                    .line,
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn dimacs_graphs_are_loaded() {
+        let gr = "c header\n\np sp 4 3\nc arcs\na 1 2 7\na 1 2 3\na 2 4 1\n";
+        let d = DijkstraMap::from_dimacs(gr).unwrap();
+        assert_eq!(d.points().count(), 4);
+        assert!(d.has_point(PointId(3)));
+        assert!(!d.has_point(PointId(0)));
+        assert_eq!(weight(&d, 1, 2), Some(Weight(3.0)));
+        assert_eq!(weight(&d, 2, 4), Some(Weight(1.0)));
+        assert_eq!(weight(&d, 4, 2), None);
+    }
+
+    #[test]
+    fn malformed_dimacs_reports_the_line() {
+        for &(gr, line) in [
+            ("a 1 2 1\np sp 2 1\n", 1),
+            ("p sp 2 1\np sp 2 1\n", 2),
+            ("p max 2 1\n", 1),
+            ("p sp 2 1\nc\na 1 x 1\n", 3),
+            ("p sp 2 1\nx\n", 2),
+            ("p sp 2 2\na 1 2 1\n", 1),
+            ("c nothing\nc else\n", 2),
+        ]
+        .iter()
+        {
+            assert_eq!(DijkstraMap::from_dimacs(gr).unwrap_err().line, line);
+        }
+    }
+
+    #[test]
+    fn ascii_grids_are_loaded() {
+        let (d, pos_to_id) =
+            DijkstraMap::from_ascii_grid("#.1\n.#\n..  \n", None, Some(Weight(1.5))).unwrap();
+        assert_eq!(pos_to_id.len(), 5);
+        assert_eq!(pos_to_id[&Vector2D::new(1, 0)], PointId(0));
+        assert_eq!(pos_to_id[&Vector2D::new(1, 2)], PointId(4));
+        assert_eq!(
+            d.get_terrain_for_point(PointId(1)),
+            Some(TerrainType::Terrain(1))
+        );
+        assert_eq!(
+            d.get_point_position(PointId(4)),
+            Some(Position::new(1.0, 2.0, 0.0))
+        );
+        // walls are not crossed orthogonally, but diagonals cut corners
+        assert_eq!(weight(&d, 0, 2), Some(Weight(1.5)));
+        assert_eq!(weight(&d, 2, 3), Some(Weight(1.0)));
+        assert_eq!(weight(&d, 2, 4), Some(Weight(1.5)));
+        assert_eq!(d.connections_of(PointId(1)).count(), 1);
+    }
+}
@@ -2,9 +2,22 @@
 //!
 //! This is intended for use in Godot, via the **dijkstra-map-gd** crate.
 
+#[cfg(feature = "persistent")]
+use fnv::FnvBuildHasher;
 use fnv::FnvHashMap;
 use fnv::FnvHashSet;
+use std::sync::Arc;
 
+/// Shortest paths between all pairs of points, for small graphs.
+mod all_pairs;
+/// Line-of-sight path smoothing and any-angle search on square grids.
+mod any_angle;
+/// Persistent square grid, extended and unloaded chunk by chunk.
+mod chunks;
+/// Connected components and reachability analysis.
+mod components;
+/// Export of the graph to Graphviz DOT and GraphML.
+mod export;
 /// Contains the
 /// [`get_direction_and_cost_map`](DijkstraMap::get_direction_and_cost_map) and
 /// [`get_all_points_with_cost_between`](DijkstraMap::get_all_points_with_cost_between)
@@ -12,14 +25,66 @@ use fnv::FnvHashSet;
 mod get_maps;
 /// Various 'getter' method for [`DijkstraMap`].
 mod getters;
-/// Implementation of some default [`DijkstraMap`]s : square and hexagonal grids.
+/// Implementation of some default [`DijkstraMap`]s : square, hexagonal and
+/// isometric grids.
 mod grids;
+/// Undo/redo history of graph modifications, grouped in transactions.
+mod history;
+/// Loaders building a [`DijkstraMap`] from simple text formats.
+mod import;
+/// Graphs built from navigation meshes made of convex polygons.
+mod navmesh;
+/// Property-based tests of [`recalculate`](DijkstraMap::recalculate) against
+/// a reference solver.
+#[cfg(test)]
+mod property_tests;
+/// Extraction of a graph of regions (rooms, corridors...) from a map.
+mod regions;
+/// Owned results of Dijkstra's algorithm, independent from the map.
+mod results;
+/// Revision tracking, to know when computed informations are outdated.
+mod revisions;
+/// Resumable implementation of Dijkstra's algorithm.
+mod search;
 /// Various 'setter' method for [`DijkstraMap`].
 mod setters;
+/// Positions of the points, and nearest-point queries.
+mod spatial;
 /// contains trait that allows explicit conversion, operations, defaut values
 /// on custom struct [`Weight`], [`PointId`] and [`Cost`].
 mod trait_conversions_ops;
 
+pub use all_pairs::AllPairsPaths;
+pub use components::{Components, Reachability};
+pub use grids::{
+    GridIds, GridIdsError, GridWrap, HexCoordinates, HexOrientation, OddWrappingWidth,
+};
+pub use history::{NoOpenTransaction, TransactionAlreadyOpen};
+pub use import::ParseError;
+pub use navmesh::{InvalidPolygon, NavigationPoints};
+pub use regions::RegionGraph;
+pub use results::{PathResults, ShortestPathIterator};
+pub use search::PathSearch;
+pub use setters::{PointAlreadyExists, PointNotFound};
+pub use spatial::Position;
+
+/// Hash map used for the graph of a [`DijkstraMap`].
+///
+/// With the `persistent` feature, this is a persistent map : cloning it
+/// takes constant time, and modifying a clone only copies the modified
+/// entries. This makes [`fork`](DijkstraMap::fork) cheap, but lookups are
+/// slower (about 40% slower recalculations on large grids), so it is
+/// opt-in.
+#[cfg(feature = "persistent")]
+type SharedMap<K, V> = im::HashMap<K, V, FnvBuildHasher>;
+#[cfg(not(feature = "persistent"))]
+type SharedMap<K, V> = FnvHashMap<K, V>;
+/// Hash set used for the graph of a [`DijkstraMap`], see [`SharedMap`].
+#[cfg(feature = "persistent")]
+type SharedSet<T> = im::HashSet<T, FnvBuildHasher>;
+#[cfg(not(feature = "persistent"))]
+type SharedSet<T> = FnvHashSet<T>;
+
 /// Weight of a connection between two points of the Dijkstra map.
 ///
 /// Wraps a [`f32`].
@@ -61,6 +126,7 @@ pub enum TerrainType {
 
 /// Controls the direction of the dijkstra map in
 /// [`recalculate`](DijkstraMap::recalculate).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Read {
     /// Input points are seen as *destinations*.
     ///
@@ -78,7 +144,7 @@ pub enum Read {
 ///
 /// We also keep an `id` field to differentiate between points that have the
 /// same cost, and keep the algorithm deterministic.
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 struct QueuePriority {
     /// Secondary comparison, used is `cost`s are equal
     id: PointId,
@@ -138,13 +204,35 @@ pub struct PointComputedInfo {
 #[derive(Debug, Clone)]
 pub struct DijkstraMap {
     /// Map a point to its informations
-    points: FnvHashMap<PointId, PointInfo>,
-    /// All the points in the map, sorted by their cost.
-    sorted_points: Vec<PointId>,
-    /// Cost and direction information for each point.
-    computed_info: FnvHashMap<PointId, PointComputedInfo>,
+    points: SharedMap<PointId, PointInfo>,
+    /// Paths computed by the last call to [`recalculate`](DijkstraMap::recalculate).
+    results: PathResults,
+    /// Recalculation in progress, started by
+    /// [`start_recalculation`](DijkstraMap::start_recalculation).
+    search: Option<PathSearch>,
     /// Points not treated by the algorithm.
-    disabled_points: FnvHashSet<PointId>,
+    disabled_points: SharedSet<PointId>,
+    /// Paths computed by [`recalculate_all_pairs`](DijkstraMap::recalculate_all_pairs).
+    ///
+    /// Reset to [`None`] whenever the graph is modified.
+    all_pairs: Option<AllPairsPaths>,
+    /// Incremented every time the graph is modified.
+    revision: u64,
+    /// Value of `revision` at the last call to [`recalculate`](DijkstraMap::recalculate).
+    computed_revision: u64,
+    /// Last revision at which each point was modified, including recently
+    /// removed points.
+    point_revisions: SharedMap<PointId, u64>,
+    /// Modifications recorded for undo/redo.
+    history: history::History,
+    /// Positions of the points.
+    ///
+    /// Shared with the forks of the map until either is modified.
+    spatial: Arc<spatial::SpatialIndex>,
+    /// Persistent grid built by [`extend_square_grid`](DijkstraMap::extend_square_grid).
+    ///
+    /// Shared with the forks of the map until either is modified.
+    grid_cells: Arc<chunks::GridCells>,
 }
 
 impl DijkstraMap {
@@ -184,98 +272,67 @@ impl DijkstraMap {
         terrain_weights: FnvHashMap<TerrainType, Weight>,
         termination_points: FnvHashSet<PointId>,
     ) {
-        let read = read.unwrap_or(Read::InputIsDestination);
-        let max_cost = max_cost.unwrap_or(Cost(std::f32::INFINITY));
-
-        // initialize containers
-        self.computed_info.clear();
-        self.sorted_points.clear();
-        let points_number = self.points.len();
-        let capacity = std::cmp::max(
-            (f32::sqrt(points_number as f32) as usize) * 6,
-            origins.len(),
+        let results = self.compute_paths(
+            origins,
+            read,
+            max_cost,
+            initial_costs,
+            terrain_weights,
+            termination_points,
         );
-        let mut open_queue =
-            priority_queue::PriorityQueue::<PointId, QueuePriority>::with_capacity(capacity);
-
-        // switches direction of connections
-        let points = &self.points;
-        let connections = |src: &PointId| -> Option<&FnvHashMap<PointId, Weight>> {
-            points.get(src).map(|info| match read {
-                Read::InputIsDestination => &info.reverse_connections,
-                Read::InputIsOrigin => &info.connections,
-            })
-        };
-
-        // add targets to open_queue
-        for (i, src) in origins.iter().enumerate() {
-            if connections(src).is_some() {
-                self.computed_info.insert(
-                    *src,
-                    PointComputedInfo {
-                        direction: *src,
-                        cost: *initial_costs.get(i).unwrap_or(&Cost(0.0)),
-                    },
-                );
-                open_queue.push(
-                    *src,
-                    QueuePriority {
-                        id: *src,
-                        cost: self.get_cost_at_point(*src),
-                    },
-                );
-            }
-        }
-
-        let mut c = points_number as i32;
-        // iterate over open_queue
-        while let Some((point1, _)) = open_queue.pop() {
-            if c < 0 {
-                break;
-            }
-            c -= 1;
-            // According to Dijkstra algorithm, this point has minimal cost among the points to process.
-            self.sorted_points.push(point1);
-            if termination_points.contains(&point1) {
-                break;
-            }
-            let point1_cost = self.get_cost_at_point(point1);
-            let point1_terrain = self.get_terrain_for_point(point1).unwrap();
-            let weight_of_point1 = match point1_terrain {
-                TerrainType::DefaultTerrain => Weight(1.0), // terrain is default terrain => weight is 1.0
-                x => *terrain_weights
-                    .get(&x) // you have x in passed dict => it is the weigh used
-                    .unwrap_or(&Weight::infinity()), // you dont have x => weight is infinity
-            };
-
-            // iterate over it's neighbours
-            let empty_connections = FnvHashMap::default();
-            for (&point2, &dir_cost) in connections(&point1).unwrap_or(&empty_connections).iter() {
-                let cost: Cost = point1_cost
-                    + dir_cost
-                        * Weight(0.5)
-                        * (weight_of_point1
-                            + *terrain_weights
-                                .get(&self.points.get(&point2).unwrap().terrain_type)
-                                .unwrap_or(&Weight(1.0))); // assumes default terrain
+        self.set_path_results(results);
+    }
 
-                // add to the open set (or update values if already present)
-                // if point is enabled and new cost is better than old one, but not bigger than maximum cost
-                if cost < self.get_cost_at_point(point2)
-                    && cost <= max_cost
-                    && !self.disabled_points.contains(&point2)
-                {
-                    open_queue.push_increase(point2, QueuePriority { id: point2, cost });
-                    self.computed_info.insert(
-                        point2,
-                        PointComputedInfo {
-                            direction: point1,
-                            cost,
-                        },
-                    );
-                }
-            }
-        }
+    /// Computes shortest paths, without modifying the map.
+    ///
+    /// This takes the same parameters as
+    /// [`recalculate`](DijkstraMap::recalculate), but returns the paths
+    /// instead of storing them. Since it only borrows the map immutably, it
+    /// can be called from several threads at once on a shared map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{Cost, DijkstraMap, TerrainType};
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let grid = dijkstra_map.add_square_grid(10, 10, None, TerrainType::DefaultTerrain, None, None);
+    /// let dijkstra_map = &dijkstra_map;
+    /// std::thread::scope(|scope| {
+    ///     for &origin in grid.values().take(4) {
+    ///         scope.spawn(move || {
+    ///             let results = dijkstra_map.compute_paths(
+    ///                 &[origin],
+    ///                 None,
+    ///                 None,
+    ///                 Vec::new(),
+    ///                 Default::default(),
+    ///                 Default::default(),
+    ///             );
+    ///             assert_eq!(results.get_cost_at_point(origin), Cost(0.0));
+    ///         });
+    ///     }
+    /// });
+    /// ```
+    pub fn compute_paths(
+        &self,
+        origins: &[PointId],
+        read: Option<Read>,
+        max_cost: Option<Cost>,
+        initial_costs: Vec<Cost>,
+        terrain_weights: FnvHashMap<TerrainType, Weight>,
+        termination_points: FnvHashSet<PointId>,
+    ) -> PathResults {
+        let mut search = self.start_search(
+            origins,
+            read,
+            max_cost,
+            initial_costs,
+            terrain_weights,
+            termination_points,
+        );
+        search.step(self, None);
+        search.into_results()
     }
 }
 
@@ -372,4 +429,43 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn compute_paths_is_thread_safe() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<DijkstraMap>();
+        assert_send_sync::<PathResults>();
+
+        let mut dijkstra_map = DijkstraMap::new();
+        dijkstra_map.add_square_grid(8, 8, None, TerrainType::DefaultTerrain, None, None);
+        let results: Vec<PathResults> = std::thread::scope(|scope| {
+            let dijkstra_map = &dijkstra_map;
+            let handles: Vec<_> = (0..4)
+                .map(|origin| {
+                    scope.spawn(move || {
+                        dijkstra_map.compute_paths(
+                            &[PointId(origin)],
+                            None,
+                            None,
+                            Vec::new(),
+                            FnvHashMap::default(),
+                            FnvHashSet::default(),
+                        )
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for (origin, result) in results.iter().enumerate() {
+            dijkstra_map.recalculate(
+                &[PointId(origin as i32)],
+                None,
+                None,
+                Vec::new(),
+                FnvHashMap::default(),
+                FnvHashSet::default(),
+            );
+            assert_eq!(result, dijkstra_map.get_path_results());
+        }
+    }
 }
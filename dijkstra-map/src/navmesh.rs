@@ -0,0 +1,237 @@
+use super::{DijkstraMap, FnvHashMap, PointId, Position, TerrainType, Weight};
+
+/// Error returned by [`DijkstraMap::add_navigation_polygons`] when a polygon
+/// has less than 3 vertices, or refers to a vertex that does not exist.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InvalidPolygon;
+
+/// Points created by [`DijkstraMap::add_navigation_polygons`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NavigationPoints {
+    /// Point of each polygon, in the order of the polygons.
+    pub polygons: Vec<PointId>,
+    /// Point of each portal (side shared by two polygons), keyed by the
+    /// indices of the side's vertices, smallest first.
+    ///
+    /// Empty if portals were not requested.
+    pub portals: FnvHashMap<(usize, usize), PointId>,
+}
+
+/// Returns the average of `positions`.
+pub(crate) fn center(positions: impl ExactSizeIterator<Item = Position>) -> Position {
+    let count = positions.len() as f32;
+    let sum = positions.fold(Position::origin(), |sum, position| {
+        sum + position.to_vector()
+    });
+    (sum.to_vector() / count).to_point()
+}
+
+impl DijkstraMap {
+    /// Adds the graph of a navigation mesh made of convex polygons, for
+    /// levels that are not tile-based.
+    ///
+    /// # Parameters
+    ///
+    /// - `vertices` : positions of the vertices of the mesh.
+    /// - `polygons` : indices in `vertices` of each polygon's vertices. Two
+    ///   polygons are neighbours if they share a side, i.e. two consecutive
+    ///   vertices.
+    /// - `terrain_type` : [`TerrainType`] of the new points.
+    /// - `portals` : if [`false`], neighbouring polygons are connected
+    ///   directly. If [`true`], a point is also added in the middle of every
+    ///   shared side (called a _portal_), and paths go from portal to portal.
+    ///   This gives shorter paths through large polygons.
+    ///
+    /// Each polygon becomes a point, whose
+    /// [position](DijkstraMap::get_point_position) is the average of its
+    /// vertices. Connections are bidirectional, and weighted by the distance
+    /// between the positions of their points.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] without modifying the map if a polygon has less than 3
+    /// vertices, or refers to a vertex that does not exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, Position, TerrainType};
+    ///
+    /// // two squares sharing the side (1, 2)
+    /// let vertices = [
+    ///     Position::new(0.0, 0.0, 0.0),
+    ///     Position::new(2.0, 0.0, 0.0),
+    ///     Position::new(2.0, 2.0, 0.0),
+    ///     Position::new(0.0, 2.0, 0.0),
+    ///     Position::new(4.0, 0.0, 0.0),
+    ///     Position::new(4.0, 2.0, 0.0),
+    /// ];
+    /// let polygons = [vec![0, 1, 2, 3], vec![1, 4, 5, 2]];
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let points = dijkstra_map
+    ///     .add_navigation_polygons(&vertices, &polygons, TerrainType::DefaultTerrain, false)
+    ///     .unwrap();
+    /// assert!(dijkstra_map.has_connection(points.polygons[0], points.polygons[1]));
+    /// assert_eq!(
+    ///     dijkstra_map.get_point_position(points.polygons[1]),
+    ///     Some(Position::new(3.0, 1.0, 0.0))
+    /// );
+    /// ```
+    pub fn add_navigation_polygons(
+        &mut self,
+        vertices: &[Position],
+        polygons: &[Vec<usize>],
+        terrain_type: TerrainType,
+        portals: bool,
+    ) -> Result<NavigationPoints, InvalidPolygon> {
+        for polygon in polygons {
+            if polygon.len() < 3 || polygon.iter().any(|&vertex| vertex >= vertices.len()) {
+                return Err(InvalidPolygon);
+            }
+        }
+
+        // polygons on each side of the sides
+        let mut sides = FnvHashMap::<(usize, usize), Vec<usize>>::default();
+        for (index, polygon) in polygons.iter().enumerate() {
+            for (i, &a) in polygon.iter().enumerate() {
+                let b = polygon[(i + 1) % polygon.len()];
+                sides.entry((a.min(b), a.max(b))).or_default().push(index);
+            }
+        }
+        let mut shared_sides: Vec<((usize, usize), Vec<usize>)> = sides
+            .into_iter()
+            .filter(|(_, polygons)| polygons.len() > 1)
+            .collect();
+        // keep the IDs deterministic
+        shared_sides.sort_unstable();
+
+        let mut id = self.get_available_id(None);
+        let mut new_point = |map: &mut DijkstraMap, position: Position| {
+            id = map.get_available_id(Some(id));
+            map.add_point_replace(id, terrain_type);
+            let _ = map.set_point_position(id, Some(position));
+            id
+        };
+        let mut points = NavigationPoints::default();
+        for polygon in polygons {
+            let centroid = center(polygon.iter().map(|&vertex| vertices[vertex]));
+            points.polygons.push(new_point(self, centroid));
+        }
+        let connect = |map: &mut DijkstraMap, a: PointId, b: PointId| {
+            let (position_a, position_b) = (
+                map.get_point_position(a).unwrap(),
+                map.get_point_position(b).unwrap(),
+            );
+            let weight = Weight((position_b - position_a).length());
+            map.connect_points(a, b, Some(weight), Some(true)).unwrap();
+        };
+
+        if portals {
+            // portals of each polygon
+            let mut polygon_portals = vec![Vec::new(); polygons.len()];
+            for ((a, b), side_polygons) in shared_sides {
+                let middle = center([vertices[a], vertices[b]].iter().copied());
+                let portal = new_point(self, middle);
+                points.portals.insert((a, b), portal);
+                for polygon in side_polygons {
+                    connect(self, points.polygons[polygon], portal);
+                    for &other in &polygon_portals[polygon] {
+                        connect(self, other, portal);
+                    }
+                    polygon_portals[polygon].push(portal);
+                }
+            }
+        } else {
+            for (_, side_polygons) in shared_sides {
+                for (i, &a) in side_polygons.iter().enumerate() {
+                    for &b in &side_polygons[i + 1..] {
+                        connect(self, points.polygons[a], points.polygons[b]);
+                    }
+                }
+            }
+        }
+        Ok(points)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Cost;
+    use fnv::FnvHashSet;
+
+    /// Creates the vertices and polygons of a row of `count` unit squares.
+    fn squares(count: usize) -> (Vec<Position>, Vec<Vec<usize>>) {
+        let vertices = (0..=count)
+            .flat_map(|x| {
+                vec![
+                    Position::new(x as f32, 0.0, 0.0),
+                    Position::new(x as f32, 1.0, 0.0),
+                ]
+            })
+            .collect();
+        let polygons = (0..count)
+            .map(|x| vec![2 * x, 2 * x + 2, 2 * x + 3, 2 * x + 1])
+            .collect();
+        (vertices, polygons)
+    }
+
+    #[test]
+    fn polygons_are_connected_by_shared_sides() {
+        let (vertices, polygons) = squares(3);
+        let mut d = DijkstraMap::new();
+        let points = d
+            .add_navigation_polygons(&vertices, &polygons, TerrainType::DefaultTerrain, false)
+            .unwrap();
+        assert_eq!(points.polygons.len(), 3);
+        assert!(points.portals.is_empty());
+        assert!(d.has_connection(points.polygons[0], points.polygons[1]));
+        assert!(d.has_connection(points.polygons[2], points.polygons[1]));
+        assert!(!d.has_connection(points.polygons[0], points.polygons[2]));
+
+        d.recalculate(
+            &[points.polygons[0]],
+            None,
+            None,
+            Vec::new(),
+            FnvHashMap::default(),
+            FnvHashSet::default(),
+        );
+        assert_eq!(d.get_cost_at_point(points.polygons[2]), Cost(2.0));
+    }
+
+    #[test]
+    fn portals_are_in_the_middle_of_shared_sides() {
+        let (vertices, polygons) = squares(2);
+        let mut d = DijkstraMap::new();
+        let points = d
+            .add_navigation_polygons(&vertices, &polygons, TerrainType::DefaultTerrain, true)
+            .unwrap();
+        assert_eq!(points.portals.len(), 1);
+        let portal = points.portals[&(2, 3)];
+        assert_eq!(
+            d.get_point_position(portal),
+            Some(Position::new(1.0, 0.5, 0.0))
+        );
+        assert!(d.has_connection(points.polygons[0], portal));
+        assert!(d.has_connection(portal, points.polygons[1]));
+        assert!(!d.has_connection(points.polygons[0], points.polygons[1]));
+    }
+
+    #[test]
+    fn invalid_polygons_are_rejected() {
+        let (vertices, _) = squares(1);
+        let mut d = DijkstraMap::new();
+        let res = d.add_navigation_polygons(
+            &vertices,
+            &[vec![0, 1, 2], vec![0, 1, 7]],
+            TerrainType::DefaultTerrain,
+            false,
+        );
+        assert_eq!(res, Err(InvalidPolygon));
+        let res =
+            d.add_navigation_polygons(&vertices, &[vec![0, 1]], TerrainType::DefaultTerrain, false);
+        assert_eq!(res, Err(InvalidPolygon));
+        assert!(!d.has_point(PointId(0)));
+    }
+}
@@ -0,0 +1,278 @@
+//! Property-based tests comparing [`DijkstraMap::recalculate`] against a
+//! naive Bellman-Ford solver.
+//!
+//! All weights, terrain weights and initial costs are multiples of `0.25`, so
+//! that every path cost is exactly representable as a [`f32`] : costs can then
+//! be compared for strict equality, regardless of summation order.
+
+use super::*;
+use proptest::collection::{btree_map, vec};
+use proptest::prelude::*;
+use proptest::sample::subsequence;
+
+/// Maximum number of points in a generated graph.
+const MAX_POINTS: usize = 24;
+
+/// Random graph, along with the parameters passed to
+/// [`recalculate`](DijkstraMap::recalculate).
+///
+/// Point `i` has the ID `PointId(i)`.
+#[derive(Clone, Debug)]
+struct RandomGraph {
+    /// Terrain of each point.
+    terrains: Vec<TerrainType>,
+    /// `(source, target, weight, bidirectional)`, in the order they are
+    /// passed to [`connect_points`](DijkstraMap::connect_points).
+    edges: Vec<(usize, usize, f32, bool)>,
+    /// Whether each point is disabled.
+    disabled: Vec<bool>,
+    /// Weights for `TerrainType::Terrain(_)` terrains.
+    terrain_weights: FnvHashMap<TerrainType, Weight>,
+    /// Origins of the computation (no duplicates).
+    origins: Vec<usize>,
+    /// Initial costs, paired with `origins` by index.
+    initial_costs: Vec<f32>,
+    /// Maximum cost.
+    max_cost: Option<f32>,
+}
+
+/// Multiple of `0.25` in `[0.25, max_quarters / 4]`.
+fn quarters(max_quarters: u16) -> impl Strategy<Value = f32> {
+    (1..=max_quarters).prop_map(|q| f32::from(q) / 4.0)
+}
+
+fn terrain() -> impl Strategy<Value = TerrainType> {
+    prop_oneof![
+        Just(TerrainType::DefaultTerrain),
+        (0..4).prop_map(TerrainType::Terrain),
+    ]
+}
+
+fn random_graph() -> impl Strategy<Value = RandomGraph> {
+    (1..=MAX_POINTS).prop_flat_map(|n| {
+        (
+            vec(terrain(), n),
+            vec((0..n, 0..n, quarters(40), any::<bool>()), 0..=n * 3),
+            vec(prop::bool::weighted(0.15), n),
+            btree_map(0..4, quarters(16), 0..=4),
+            subsequence((0..n).collect::<Vec<_>>(), 1..=std::cmp::min(n, 3)).prop_shuffle(),
+            vec((0u16..20).prop_map(|q| f32::from(q) / 4.0), 0..=3),
+            prop::option::of(quarters(120)),
+        )
+            .prop_map(
+                |(terrains, edges, disabled, weights, origins, initial_costs, max_cost)| {
+                    RandomGraph {
+                        terrains,
+                        edges,
+                        disabled,
+                        terrain_weights: weights
+                            .into_iter()
+                            .map(|(terrain, weight)| {
+                                (TerrainType::Terrain(terrain), Weight(weight))
+                            })
+                            .collect(),
+                        origins,
+                        initial_costs,
+                        max_cost,
+                    }
+                },
+            )
+    })
+}
+
+impl RandomGraph {
+    fn build(&self) -> DijkstraMap {
+        let mut dijkstra_map = DijkstraMap::new();
+        for (i, &terrain) in self.terrains.iter().enumerate() {
+            dijkstra_map.add_point(PointId(i as i32), terrain).unwrap();
+        }
+        for &(source, target, weight, bidirectional) in &self.edges {
+            dijkstra_map
+                .connect_points(
+                    PointId(source as i32),
+                    PointId(target as i32),
+                    Some(Weight(weight)),
+                    Some(bidirectional),
+                )
+                .unwrap();
+        }
+        for (i, &disabled) in self.disabled.iter().enumerate() {
+            if disabled {
+                dijkstra_map.disable_point(PointId(i as i32)).unwrap();
+            }
+        }
+        dijkstra_map
+    }
+
+    fn recalculate(&self, dijkstra_map: &mut DijkstraMap, read: Read) {
+        let origins: Vec<PointId> = self.origins.iter().map(|&i| PointId(i as i32)).collect();
+        dijkstra_map.recalculate(
+            &origins,
+            Some(read),
+            self.max_cost.map(Cost),
+            self.initial_costs.iter().copied().map(Cost).collect(),
+            self.terrain_weights.clone(),
+            FnvHashSet::default(),
+        );
+    }
+
+    /// Final weight of each directed connection, after overwrites.
+    fn connections(&self) -> FnvHashMap<(usize, usize), f32> {
+        let mut connections = FnvHashMap::default();
+        for &(source, target, weight, bidirectional) in &self.edges {
+            connections.insert((source, target), weight);
+            if bidirectional {
+                connections.insert((target, source), weight);
+            }
+        }
+        connections
+    }
+
+    /// Weight of a point's terrain, when the algorithm moves **from** it.
+    fn weight_from(&self, point: usize) -> f32 {
+        match self.terrains[point] {
+            TerrainType::DefaultTerrain => 1.0,
+            terrain => self
+                .terrain_weights
+                .get(&terrain)
+                .map_or(f32::INFINITY, |w| w.0),
+        }
+    }
+
+    /// Weight of a point's terrain, when the algorithm moves **to** it.
+    fn weight_to(&self, point: usize) -> f32 {
+        self.terrain_weights
+            .get(&self.terrains[point])
+            .map_or(1.0, |w| w.0)
+    }
+
+    /// Cost of a single step of the algorithm, from `from` to `to`, along a
+    /// connection of weight `weight`.
+    fn step_cost(&self, from: usize, to: usize, weight: f32) -> f32 {
+        weight * 0.5 * (self.weight_from(from) + self.weight_to(to))
+    }
+
+    /// Reference costs, computed with the Bellman-Ford algorithm.
+    fn reference_costs(&self, read: Read) -> Vec<f32> {
+        let n = self.terrains.len();
+        let max_cost = self.max_cost.unwrap_or(f32::INFINITY);
+        let mut costs = vec![f32::INFINITY; n];
+        for (i, &origin) in self.origins.iter().enumerate() {
+            costs[origin] = self.initial_costs.get(i).copied().unwrap_or(0.0);
+        }
+        let connections = self.connections();
+        for _ in 0..n {
+            let mut changed = false;
+            for (&(source, target), &weight) in &connections {
+                let (from, to) = match read {
+                    Read::InputIsOrigin => (source, target),
+                    Read::InputIsDestination => (target, source),
+                };
+                if self.disabled[to] {
+                    continue;
+                }
+                let cost = costs[from] + self.step_cost(from, to, weight);
+                if cost < costs[to] && cost <= max_cost {
+                    costs[to] = cost;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        costs
+    }
+
+    /// Check every property of the computed map for the given direction.
+    fn check(&self, read: Read) -> Result<(), TestCaseError> {
+        let mut dijkstra_map = self.build();
+        let expected = self.reference_costs(read);
+        self.recalculate(&mut dijkstra_map, read);
+        let connections = self.connections();
+
+        for (point, &expected_cost) in expected.iter().enumerate() {
+            let id = PointId(point as i32);
+            prop_assert_eq!(
+                dijkstra_map.get_cost_at_point(id),
+                Cost(expected_cost),
+                "cost of point {}",
+                point
+            );
+            prop_assert_eq!(
+                dijkstra_map.get_direction_at_point(id).is_some(),
+                expected_cost.is_finite(),
+                "direction of point {}",
+                point
+            );
+            if expected_cost.is_infinite() {
+                continue;
+            }
+
+            // walking the path must reproduce the reported cost
+            let mut current = point;
+            let mut path_cost = 0.0;
+            for next in dijkstra_map
+                .get_shortest_path_from_point(id)
+                .take(expected.len() + 1)
+            {
+                let next = i32::from(next) as usize;
+                let weight = match read {
+                    Read::InputIsDestination => connections.get(&(current, next)),
+                    Read::InputIsOrigin => connections.get(&(next, current)),
+                };
+                prop_assert!(
+                    weight.is_some(),
+                    "path from {} uses missing connection between {} and {}",
+                    point,
+                    current,
+                    next
+                );
+                path_cost += self.step_cost(next, current, *weight.unwrap());
+                current = next;
+            }
+            let end = PointId(current as i32);
+            prop_assert!(
+                self.origins.contains(&current),
+                "path from {} ends at {}",
+                point,
+                current
+            );
+            prop_assert_eq!(dijkstra_map.get_direction_at_point(end), Some(end));
+            prop_assert_eq!(
+                Cost(path_cost) + dijkstra_map.get_cost_at_point(end),
+                Cost(expected_cost),
+                "path cost from point {}",
+                point
+            );
+        }
+
+        // sorted points are monotone, and contain every reachable point
+        let sorted_points =
+            dijkstra_map.get_all_points_with_cost_between(Cost(-f32::INFINITY), Cost::infinity());
+        for pair in sorted_points.windows(2) {
+            prop_assert!(
+                dijkstra_map.get_cost_at_point(pair[0]) <= dijkstra_map.get_cost_at_point(pair[1]),
+                "sorted points are not sorted : {:?}",
+                sorted_points
+            );
+        }
+        prop_assert_eq!(
+            sorted_points.len(),
+            expected.iter().filter(|cost| cost.is_finite()).count()
+        );
+        Ok(())
+    }
+}
+
+proptest! {
+    #[test]
+    fn input_is_destination_matches_bellman_ford(graph in random_graph()) {
+        graph.check(Read::InputIsDestination)?;
+    }
+
+    #[test]
+    fn input_is_origin_matches_bellman_ford(graph in random_graph()) {
+        graph.check(Read::InputIsOrigin)?;
+    }
+}
@@ -0,0 +1,441 @@
+use super::navmesh::center;
+use super::{Cost, DijkstraMap, FnvHashMap, PointId, Position, QueuePriority, Weight};
+use priority_queue::PriorityQueue;
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::convert::TryFrom;
+
+/// Higher-level navigation graph, where each point is a region of a
+/// [`DijkstraMap`].
+///
+/// This is created via the
+/// [`extract_regions`](DijkstraMap::extract_regions) method.
+#[derive(Clone, Debug)]
+pub struct RegionGraph {
+    /// Graph of the regions. Region IDs are numbered from `0`.
+    map: DijkstraMap,
+    /// Map a point of the original map to its region.
+    region_of: FnvHashMap<PointId, PointId>,
+    /// Points of each region, sorted by ID.
+    members: Vec<Vec<PointId>>,
+    /// Central point of each region.
+    centers: Vec<PointId>,
+    /// Connections of the original map between two regions, sorted.
+    doorways: FnvHashMap<(PointId, PointId), Vec<(PointId, PointId)>>,
+}
+
+impl RegionGraph {
+    /// Returns the graph of the regions.
+    ///
+    /// Each region has the terrain type of its points, and there is a
+    /// connection from region `a` to region `b` if there is a connection from
+    /// a point of `a` to a point of `b`.
+    ///
+    /// The weight of this connection is the cost of going from the
+    /// [center](RegionGraph::center) of `a` to the center of `b`, through the
+    /// cheapest of their [doorways](RegionGraph::doorways) : the cost of the
+    /// path from the center of `a` to the doorway, plus the weight of the
+    /// doorway, plus the cost of the path from the doorway to the center of
+    /// `b`. Paths inside a region only follow the points of that region, and
+    /// their cost is the sum of the weights of their connections : terrain
+    /// weights are applied when the graph of the regions is
+    /// [recalculated](DijkstraMap::recalculate), like in any map. If a
+    /// doorway can not be reached from a center, or can not reach it, because
+    /// of one-way connections, that part of the cost is left out.
+    ///
+    /// A cost computed on this graph thus approximates the cost of a path in
+    /// the original map that goes through the same regions, so region-level
+    /// paths can be compared by cost, for example to restrict a search of
+    /// the original map to the regions along the way.
+    pub fn map(&self) -> &DijkstraMap {
+        &self.map
+    }
+
+    /// Consumes `self`, returning the graph of the regions.
+    pub fn into_map(self) -> DijkstraMap {
+        self.map
+    }
+
+    /// Returns the number of regions.
+    pub fn count(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns the region containing `point`, or [`None`] if `point` is
+    /// disabled or does not exist.
+    pub fn region_of(&self, point: PointId) -> Option<PointId> {
+        self.region_of.get(&point).copied()
+    }
+
+    /// Returns the region of every enabled point of the original map.
+    pub fn region_map(&self) -> &FnvHashMap<PointId, PointId> {
+        &self.region_of
+    }
+
+    /// Returns the points of `region`, sorted by ID.
+    pub fn members(&self, region: PointId) -> &[PointId] {
+        usize::try_from(i32::from(region))
+            .ok()
+            .and_then(|region| self.members.get(region))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the central point of `region`, or [`None`] if there is no such
+    /// region.
+    ///
+    /// If some points of the region have a
+    /// [position](DijkstraMap::get_point_position), this is the one nearest
+    /// to the average position of these points. Else, this is about halfway
+    /// along the longest path found by two successive searches in the
+    /// region.
+    pub fn center(&self, region: PointId) -> Option<PointId> {
+        usize::try_from(i32::from(region))
+            .ok()
+            .and_then(|region| self.centers.get(region))
+            .copied()
+    }
+
+    /// Returns the connections going from a point of region `source` to a
+    /// point of region `target`, as `(point in source, point in target)`
+    /// pairs.
+    pub fn doorways(&self, source: PointId, target: PointId) -> &[(PointId, PointId)] {
+        self.doorways
+            .get(&(source, target))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns an iterator over the connections of the original map between
+    /// two different regions, in no particular order.
+    pub fn iter_doorways(&self) -> impl Iterator<Item = (PointId, PointId)> + '_ {
+        self.doorways.values().flatten().copied()
+    }
+}
+
+impl DijkstraMap {
+    /// Splits the map into regions, and builds the graph of these regions.
+    ///
+    /// A region is a set of enabled points with the same terrain type, linked
+    /// by connections (ignoring their direction). On a grid created by
+    /// [`add_square_grid`](DijkstraMap::add_square_grid), where walls are
+    /// disabled points, giving rooms and corridors different terrain types
+    /// makes each room and corridor a separate region.
+    ///
+    /// Regions are numbered in increasing order of their smallest point ID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, PointId, TerrainType, Weight};
+    ///
+    /// // room (0, 1) -- corridor (2) -- room (3, 4)
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// for i in 0..5 {
+    ///     let terrain = if i == 2 { TerrainType::Terrain(1) } else { TerrainType::DefaultTerrain };
+    ///     dijkstra_map.add_point(PointId(i), terrain).unwrap();
+    ///     if i > 0 {
+    ///         dijkstra_map.connect_points(PointId(i - 1), PointId(i), None, None).unwrap();
+    ///     }
+    /// }
+    /// let regions = dijkstra_map.extract_regions();
+    /// assert_eq!(regions.count(), 3);
+    /// assert_eq!(regions.region_of(PointId(4)), Some(PointId(2)));
+    /// assert!(regions.map().has_connection(PointId(1), PointId(2)));
+    /// assert_eq!(regions.doorways(PointId(1), PointId(2)), [(PointId(2), PointId(3))]);
+    /// // from the center 0 of the room (0, 1) to the corridor : from 0 to 1, then to 2
+    /// assert_eq!(regions.center(PointId(0)), Some(PointId(0)));
+    /// let weight = regions.map().connections_of(PointId(0)).next().unwrap().1;
+    /// assert_eq!(weight, Weight(2.0));
+    /// ```
+    pub fn extract_regions(&self) -> RegionGraph {
+        let mut region_of = FnvHashMap::<PointId, PointId>::default();
+        let mut members = Vec::new();
+        let mut map = DijkstraMap::new();
+        let mut stack = Vec::new();
+        for root in self.sorted_enabled_points() {
+            if region_of.contains_key(&root) {
+                continue;
+            }
+            let region = PointId(members.len() as i32);
+            let terrain_type = self.points[&root].terrain_type;
+            map.add_point(region, terrain_type).unwrap();
+            region_of.insert(root, region);
+            stack.push(root);
+            let mut points = Vec::new();
+            while let Some(point) = stack.pop() {
+                points.push(point);
+                let outgoing = self.enabled_neighbours(point, false);
+                let incoming = self.enabled_neighbours(point, true);
+                for nbr in outgoing.into_iter().chain(incoming) {
+                    if self.points[&nbr].terrain_type != terrain_type {
+                        continue;
+                    }
+                    if let Entry::Vacant(entry) = region_of.entry(nbr) {
+                        entry.insert(region);
+                        stack.push(nbr);
+                    }
+                }
+            }
+            points.sort_unstable();
+            members.push(points);
+        }
+
+        // costs from the center of each region, and to it
+        let mut centers = Vec::with_capacity(members.len());
+        let mut from_center = FnvHashMap::<PointId, Cost>::default();
+        let mut to_center = FnvHashMap::<PointId, Cost>::default();
+        for points in members.iter() {
+            let center = self.region_center(points, &region_of);
+            centers.push(center);
+            from_center.extend(self.costs_in_region(center, &region_of, false));
+            to_center.extend(self.costs_in_region(center, &region_of, true));
+        }
+
+        // unreachable parts of the path are left out
+        let inside = |costs: &FnvHashMap<PointId, Cost>, point: &PointId| {
+            costs.get(point).map_or(0.0, |&cost| f32::from(cost))
+        };
+        let mut doorways = FnvHashMap::<(PointId, PointId), Vec<(PointId, PointId)>>::default();
+        let mut weights = FnvHashMap::<(PointId, PointId), Weight>::default();
+        for (&point, &region) in region_of.iter() {
+            for (nbr, &weight) in self.points[&point].connections.iter() {
+                let nbr_region = match region_of.get(nbr) {
+                    Some(&nbr_region) if nbr_region != region => nbr_region,
+                    _ => continue,
+                };
+                doorways
+                    .entry((region, nbr_region))
+                    .or_default()
+                    .push((point, *nbr));
+                let weight =
+                    Weight(inside(&from_center, &point) + weight.0 + inside(&to_center, nbr));
+                let min_weight = weights.entry((region, nbr_region)).or_insert(weight);
+                if weight < *min_weight {
+                    *min_weight = weight;
+                }
+            }
+        }
+        for pairs in doorways.values_mut() {
+            pairs.sort_unstable();
+        }
+        for ((source, target), weight) in weights {
+            map.connect_points(source, target, Some(weight), Some(false))
+                .unwrap();
+        }
+
+        RegionGraph {
+            map,
+            region_of,
+            members,
+            centers,
+            doorways,
+        }
+    }
+
+    /// Returns the costs of the paths from `source` to the points of its
+    /// region, or from these points to `source` if `reverse` is [`true`].
+    ///
+    /// Paths only follow the points of the region, and their cost is the sum
+    /// of the weights of their connections.
+    fn costs_in_region(
+        &self,
+        source: PointId,
+        region_of: &FnvHashMap<PointId, PointId>,
+        reverse: bool,
+    ) -> FnvHashMap<PointId, Cost> {
+        let region = region_of[&source];
+        let mut costs = FnvHashMap::<PointId, Cost>::default();
+        let mut open_queue = PriorityQueue::<PointId, QueuePriority>::new();
+        costs.insert(source, Cost(0.0));
+        open_queue.push(
+            source,
+            QueuePriority {
+                id: source,
+                cost: Cost(0.0),
+            },
+        );
+        while let Some((point, QueuePriority { cost, .. })) = open_queue.pop() {
+            let info = &self.points[&point];
+            let connections = if reverse {
+                &info.reverse_connections
+            } else {
+                &info.connections
+            };
+            for (&nbr, &weight) in connections.iter() {
+                if region_of.get(&nbr) != Some(&region) {
+                    continue;
+                }
+                let nbr_cost = cost + weight;
+                match costs.get(&nbr) {
+                    Some(&old) if old <= nbr_cost => continue,
+                    _ => {}
+                }
+                costs.insert(nbr, nbr_cost);
+                open_queue.push_increase(
+                    nbr,
+                    QueuePriority {
+                        id: nbr,
+                        cost: nbr_cost,
+                    },
+                );
+            }
+        }
+        costs
+    }
+
+    /// Returns a central point of `points`, the sorted points of a region,
+    /// see [`RegionGraph::center`].
+    ///
+    /// Without positions, the farthest point `a` from the first point is
+    /// found, then the farthest point `b` from `a` : the center is the point
+    /// that minimizes the largest of its costs from `a` and from `b`.
+    ///
+    /// Ties are broken by the smallest ID.
+    fn region_center(
+        &self,
+        points: &[PointId],
+        region_of: &FnvHashMap<PointId, PointId>,
+    ) -> PointId {
+        let positions: Vec<(PointId, Position)> = points
+            .iter()
+            .filter_map(|&point| Some((point, self.get_point_position(point)?)))
+            .collect();
+        if !positions.is_empty() {
+            let average = center(positions.iter().map(|&(_, position)| position));
+            let distance = |position: Position| (position - average).square_length();
+            // the first minimum is kept, which has the smallest ID
+            return positions
+                .iter()
+                .min_by(|(_, a), (_, b)| {
+                    distance(*a)
+                        .partial_cmp(&distance(*b))
+                        .unwrap_or(Ordering::Equal)
+                })
+                .map_or(points[0], |&(point, _)| point);
+        }
+
+        let farthest = |costs: &FnvHashMap<PointId, Cost>| {
+            costs
+                .iter()
+                .map(|(&point, &cost)| QueuePriority { id: point, cost })
+                // `QueuePriority` is reversed : the smallest is the farthest
+                .min()
+                .map_or(points[0], |priority| priority.id)
+        };
+        let a = farthest(&self.costs_in_region(points[0], region_of, false));
+        let from_a = self.costs_in_region(a, region_of, false);
+        let b = farthest(&from_a);
+        let from_b = self.costs_in_region(b, region_of, false);
+        from_a
+            .iter()
+            .filter_map(|(&point, &cost_a)| {
+                let cost_b = *from_b.get(&point)?;
+                let cost = if cost_a > cost_b { cost_a } else { cost_b };
+                Some(QueuePriority { id: point, cost })
+            })
+            // `QueuePriority` is reversed : the largest is the nearest
+            .max()
+            .map_or(a, |priority| priority.id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Cost, TerrainType};
+    use euclid::Vector2D;
+    use fnv::FnvHashSet;
+
+    const ROOM: TerrainType = TerrainType::Terrain(0);
+    const CORRIDOR: TerrainType = TerrainType::Terrain(1);
+
+    fn pos(x: i32, y: i32) -> Vector2D<i32, i32> {
+        Vector2D::from((x, y))
+    }
+
+    /// Creates a 7x3 grid with two 3x3 rooms linked by a corridor, surrounded
+    /// by disabled walls :
+    ///
+    /// ```text
+    /// ...#...
+    /// ...=...
+    /// ...#...
+    /// ```
+    fn setup() -> (DijkstraMap, FnvHashMap<Vector2D<i32, i32>, PointId>) {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_square_grid(7, 3, None, ROOM, None, None);
+        for (position, &id) in grid.iter() {
+            if position.x == 3 {
+                if position.y == 1 {
+                    d.set_terrain_for_point(id, CORRIDOR).unwrap();
+                } else {
+                    d.disable_point(id).unwrap();
+                }
+            }
+        }
+        (d, grid)
+    }
+
+    #[test]
+    fn rooms_and_corridors_are_regions() {
+        let (d, grid) = setup();
+        let regions = d.extract_regions();
+        assert_eq!(regions.count(), 3);
+        let left = regions.region_of(grid[&pos(0, 0)]).unwrap();
+        let corridor = regions.region_of(grid[&pos(3, 1)]).unwrap();
+        let right = regions.region_of(grid[&pos(6, 2)]).unwrap();
+        assert_eq!(regions.members(left).len(), 9);
+        assert_eq!(regions.members(corridor), [grid[&pos(3, 1)]]);
+        assert_eq!(regions.region_of(grid[&pos(3, 0)]), None);
+
+        let map = regions.map();
+        assert!(map.has_connection(left, corridor));
+        assert!(map.has_connection(corridor, right));
+        assert!(!map.has_connection(left, right));
+        assert_eq!(map.get_terrain_for_point(corridor), Some(CORRIDOR));
+        assert_eq!(regions.region_map().len(), 19);
+        assert_eq!(regions.iter_doorways().count(), 4);
+        assert_eq!(
+            regions.doorways(left, corridor),
+            [(grid[&pos(2, 1)], grid[&pos(3, 1)])]
+        );
+    }
+
+    #[test]
+    fn region_weights_include_the_cost_of_crossing_regions() {
+        let (d, grid) = setup();
+        let regions = d.extract_regions();
+        let left = regions.region_of(grid[&pos(0, 0)]).unwrap();
+        let corridor = regions.region_of(grid[&pos(3, 1)]).unwrap();
+        let right = regions.region_of(grid[&pos(6, 2)]).unwrap();
+        assert_eq!(regions.center(left), Some(grid[&pos(1, 1)]));
+        assert_eq!(regions.center(corridor), Some(grid[&pos(3, 1)]));
+        assert_eq!(regions.center(right), Some(grid[&pos(5, 1)]));
+        assert_eq!(regions.center(PointId(3)), None);
+        // from (1, 1) to (2, 1), then to the corridor
+        let map = regions.map();
+        assert_eq!(map.points[&left].connections[&corridor], Weight(2.0));
+        assert_eq!(map.points[&corridor].connections[&right], Weight(2.0));
+    }
+
+    #[test]
+    fn region_graph_can_be_recalculated() {
+        let (d, grid) = setup();
+        let regions = d.extract_regions();
+        let left = regions.region_of(grid[&pos(0, 0)]).unwrap();
+        let right = regions.region_of(grid[&pos(6, 0)]).unwrap();
+        let mut map = regions.into_map();
+        let mut terrain_weights = FnvHashMap::default();
+        terrain_weights.insert(ROOM, Weight(1.0));
+        terrain_weights.insert(CORRIDOR, Weight(1.0));
+        map.recalculate(
+            &[right],
+            None,
+            None,
+            Vec::new(),
+            terrain_weights,
+            FnvHashSet::default(),
+        );
+        // the same as from (1, 1) to (5, 1) in the original map
+        assert_eq!(map.get_cost_at_point(left), Cost(4.0));
+    }
+}
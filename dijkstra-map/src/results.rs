@@ -0,0 +1,133 @@
+use super::{Cost, DijkstraMap, FnvHashMap, PointComputedInfo, PointId};
+
+/// Shortest paths computed by Dijkstra's algorithm.
+///
+/// This is created via the [`compute_paths`](crate::DijkstraMap::compute_paths)
+/// method, and does not borrow the map : several threads can compute paths on
+/// the same [`DijkstraMap`](crate::DijkstraMap) at once, each one owning its
+/// results.
+///
+/// [`recalculate`](crate::DijkstraMap::recalculate) stores its results in the
+/// map, where they are accessible with the same getters.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PathResults {
+    /// Cost and direction information for each point.
+    pub(crate) computed_info: FnvHashMap<PointId, PointComputedInfo>,
+    /// All the points reached by the algorithm, sorted by their cost.
+    pub(crate) sorted_points: Vec<PointId>,
+    /// Revision of the graph the paths were computed on.
+    pub(crate) revision: u64,
+}
+
+impl PathResults {
+    /// Returns the [`revision`](DijkstraMap::revision) of the graph the paths
+    /// were computed on.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Given a `point`, returns the id of the next point along the shortest
+    /// path.
+    ///
+    /// If there is no path, returns [`None`].
+    pub fn get_direction_at_point(&self, point: PointId) -> Option<PointId> {
+        self.computed_info
+            .get(&point)
+            .map(|PointComputedInfo { direction, .. }| *direction)
+    }
+
+    /// Returns the cost of the shortest path.
+    ///
+    /// If there is no path, the cost is [`INFINITY`](Cost::infinity).
+    pub fn get_cost_at_point(&self, point: PointId) -> Cost {
+        self.computed_info
+            .get(&point)
+            .map(|PointComputedInfo { cost, .. }| *cost)
+            .unwrap_or_else(Cost::infinity)
+    }
+
+    /// Returns an iterator over the components of the shortest path from the
+    /// given `point` (note that `point` isn't included).
+    ///
+    /// If `point` is a target or is inaccessible, the iterator will be empty.
+    pub fn get_shortest_path_from_point(&self, point: PointId) -> ShortestPathIterator<'_> {
+        ShortestPathIterator {
+            results: self,
+            // a target's direction is itself
+            next_point: self
+                .get_direction_at_point(point)
+                .filter(|&direction| direction != point),
+        }
+    }
+
+    /// Returns the entire Dijkstra map of directions and costs.
+    pub fn get_direction_and_cost_map(&self) -> &FnvHashMap<PointId, PointComputedInfo> {
+        &self.computed_info
+    }
+
+    /// Returns a slice of all points with costs between `min_cost` and `max_cost` (inclusive), sorted by cost.
+    pub fn get_all_points_with_cost_between(&self, min_cost: Cost, max_cost: Cost) -> &[PointId] {
+        let start_point = match self.sorted_points.binary_search_by(|a| {
+            if self.get_cost_at_point(*a) < min_cost {
+                std::cmp::Ordering::Less
+            } else {
+                std::cmp::Ordering::Greater
+            }
+        }) {
+            Ok(a) | Err(a) => a,
+        };
+        let end_point = match self.sorted_points.binary_search_by(|a| {
+            if self.get_cost_at_point(*a) > max_cost {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Less
+            }
+        }) {
+            Ok(a) | Err(a) => a,
+        };
+        &self.sorted_points[start_point..end_point]
+    }
+}
+
+impl DijkstraMap {
+    /// Replaces the paths stored in the map, as if they were computed by
+    /// [`recalculate`](DijkstraMap::recalculate).
+    ///
+    /// This is meant for paths computed in the background on a
+    /// [`fork`](DijkstraMap::fork) of the map : if the map was modified since
+    /// the fork, it is [stale](DijkstraMap::is_stale).
+    ///
+    /// This cancels the time-sliced recalculation in progress, if any.
+    pub fn set_path_results(&mut self, results: PathResults) {
+        self.computed_revision = results.revision;
+        self.results = results;
+        self.search = None;
+    }
+}
+
+/// Iterator over the components of a shortest path in a [`PathResults`].
+///
+/// This is created via the
+/// [`get_shortest_path_from_point`](PathResults::get_shortest_path_from_point)
+/// function.
+pub struct ShortestPathIterator<'a> {
+    /// Reference to the computed paths
+    results: &'a PathResults,
+    /// next point to return
+    next_point: Option<PointId>,
+}
+
+impl<'a> Iterator for ShortestPathIterator<'a> {
+    type Item = PointId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current_point = self.next_point?;
+        self.next_point = self.results.get_direction_at_point(current_point);
+        if let Some(point) = self.next_point {
+            if point == current_point {
+                self.next_point = None;
+            }
+        }
+        Some(current_point)
+    }
+}
@@ -0,0 +1,234 @@
+use super::{DijkstraMap, PointId};
+
+/// Number of revisions during which
+/// [`changed_points_since`](DijkstraMap::changed_points_since) remembers
+/// removed points. Must be a power of two.
+const REMOVED_POINTS_LIFETIME: u64 = 1 << 16;
+
+impl DijkstraMap {
+    /// Records a modification of the graph affecting `points`.
+    ///
+    /// This must be called by every method that modifies the graph : it bumps
+    /// the [`revision`](DijkstraMap::revision) and invalidates cached results.
+    pub(crate) fn mark_changed(&mut self, points: &[PointId]) {
+        self.revision += 1;
+        for &point in points {
+            self.point_revisions.insert(point, self.revision);
+        }
+        if self.revision & (REMOVED_POINTS_LIFETIME - 1) == 0 {
+            self.forget_removed_points();
+        }
+        self.all_pairs = None;
+    }
+
+    /// Stops tracking the points removed more than
+    /// [`REMOVED_POINTS_LIFETIME`] revisions ago, so that maps where points
+    /// keep being added and removed do not grow forever.
+    fn forget_removed_points(&mut self) {
+        let horizon = self.revision.saturating_sub(REMOVED_POINTS_LIFETIME);
+        let points = &self.points;
+        self.point_revisions
+            .retain(|point, revision| *revision > horizon || points.contains_key(point));
+    }
+
+    /// Returns the current revision of the graph.
+    ///
+    /// The revision starts at `0`, and increases every time points,
+    /// connections, terrains or disabled points are modified.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Returns [`true`] if the graph was modified since the last call to
+    /// [`recalculate`](DijkstraMap::recalculate).
+    ///
+    /// A map that was never recalculated is stale as soon as it is modified.
+    pub fn is_stale(&self) -> bool {
+        self.computed_revision != self.revision
+    }
+
+    /// Returns the points that were added, removed or modified after the
+    /// given `revision`, sorted by ID.
+    ///
+    /// A point is modified when its terrain, enabled state, connections or
+    /// reverse connections change.
+    ///
+    /// Removed points are forgotten after `65536` revisions : if `revision`
+    /// is older than that, points removed long ago may be missing from the
+    /// result. Points still in the map are always returned.
+    pub fn changed_points_since(&self, revision: u64) -> Vec<PointId> {
+        let mut points: Vec<PointId> = self
+            .point_revisions
+            .iter()
+            .filter(|(_, &point_revision)| point_revision > revision)
+            .map(|(&point, _)| point)
+            .collect();
+        points.sort_unstable();
+        points
+    }
+
+    /// Replaces the whole map by `graph`, while keeping the
+    /// [`revision`](DijkstraMap::revision) of `self` increasing.
+    ///
+    /// The new revision is greater than the ones of both maps, and every
+    /// point of either map is returned by
+    /// [`changed_points_since`](DijkstraMap::changed_points_since) older
+    /// revisions. Everything else, including the computed paths and the
+    /// undo/redo history, comes from `graph` : if `graph` was not
+    /// [stale](DijkstraMap::is_stale), `self` is not either.
+    pub fn replace_graph(&mut self, graph: DijkstraMap) {
+        let mut points: Vec<PointId> = self
+            .points
+            .keys()
+            .chain(graph.points.keys())
+            .copied()
+            .collect();
+        points.sort_unstable();
+        points.dedup();
+        let revision = self.revision.max(graph.revision);
+        let stale = graph.is_stale();
+        *self = graph;
+        self.revision = revision;
+        self.mark_changed(&points);
+        if !stale {
+            self.computed_revision = self.revision;
+            self.results.revision = self.revision;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TerrainType;
+    use fnv::{FnvHashMap, FnvHashSet};
+
+    const ID0: PointId = PointId(0);
+    const ID1: PointId = PointId(1);
+    const ID2: PointId = PointId(2);
+    const TERRAIN: TerrainType = TerrainType::DefaultTerrain;
+
+    fn recalculate(d: &mut DijkstraMap) {
+        d.recalculate(
+            &[ID0],
+            None,
+            None,
+            Vec::new(),
+            FnvHashMap::default(),
+            FnvHashSet::default(),
+        );
+    }
+
+    #[test]
+    fn modifications_make_map_stale() {
+        let mut d = DijkstraMap::new();
+        assert!(!d.is_stale());
+        d.add_point(ID0, TERRAIN).unwrap();
+        assert!(d.is_stale());
+        recalculate(&mut d);
+        assert!(!d.is_stale());
+
+        // failed modifications do not change anything
+        let revision = d.revision();
+        d.add_point(ID0, TERRAIN).unwrap_err();
+        d.disable_point(ID1).unwrap_err();
+        assert_eq!(d.revision(), revision);
+        assert!(!d.is_stale());
+
+        d.set_terrain_for_point(ID0, TerrainType::Terrain(1))
+            .unwrap();
+        assert!(d.is_stale());
+    }
+
+    #[test]
+    fn changed_points_are_tracked() {
+        let mut d = DijkstraMap::new();
+        d.add_point(ID0, TERRAIN).unwrap();
+        d.add_point(ID1, TERRAIN).unwrap();
+        d.add_point(ID2, TERRAIN).unwrap();
+        let revision = d.revision();
+        assert_eq!(d.changed_points_since(revision), []);
+
+        d.connect_points(ID0, ID1, None, Some(false)).unwrap();
+        assert_eq!(d.changed_points_since(revision), [ID0, ID1]);
+
+        let revision = d.revision();
+        d.disable_point(ID2).unwrap();
+        assert_eq!(d.changed_points_since(revision), [ID2]);
+
+        // removing a point also modifies its neighbours
+        let revision = d.revision();
+        d.remove_point(ID1);
+        assert_eq!(d.changed_points_since(revision), [ID0, ID1]);
+
+        let revision = d.revision();
+        d.clear();
+        assert_eq!(d.changed_points_since(revision), [ID0, ID2]);
+        assert_eq!(d.changed_points_since(0), [ID0, ID1, ID2]);
+    }
+
+    #[test]
+    fn replacing_the_graph_keeps_revisions_increasing() {
+        let mut d = DijkstraMap::new();
+        d.add_point(ID0, TERRAIN).unwrap();
+        d.add_point(ID1, TERRAIN).unwrap();
+        let revision = d.revision();
+
+        let mut graph = DijkstraMap::new();
+        graph.add_point(ID0, TERRAIN).unwrap();
+        graph.add_point(ID2, TERRAIN).unwrap();
+        recalculate(&mut graph);
+        d.replace_graph(graph);
+        assert!(d.revision() > revision);
+        assert_eq!(d.changed_points_since(revision), [ID0, ID1, ID2]);
+        assert!(!d.is_stale());
+        assert_eq!(d.get_path_results().revision(), d.revision());
+        assert!(d.has_point(ID2));
+        assert!(!d.has_point(ID1));
+    }
+
+    #[test]
+    fn removed_points_are_eventually_forgotten() {
+        let mut d = DijkstraMap::new();
+        d.add_point(ID0, TERRAIN).unwrap();
+        d.add_point(ID1, TERRAIN).unwrap();
+        d.remove_point(ID1);
+        let revision = d.revision();
+        for _ in 0..REMOVED_POINTS_LIFETIME {
+            d.disable_point(ID0).unwrap();
+            d.enable_point(ID0).unwrap();
+        }
+        assert_eq!(d.changed_points_since(0), [ID0]);
+        assert_eq!(d.changed_points_since(revision), [ID0]);
+        assert_eq!(d.point_revisions.len(), 1);
+    }
+
+    #[test]
+    fn results_from_fork_keep_their_revision() {
+        let mut d = DijkstraMap::new();
+        d.add_point(ID0, TERRAIN).unwrap();
+        d.add_point(ID1, TERRAIN).unwrap();
+        d.connect_points(ID0, ID1, None, None).unwrap();
+
+        let fork = d.fork();
+        let compute = |map: &DijkstraMap| {
+            map.compute_paths(
+                &[ID0],
+                None,
+                None,
+                Vec::new(),
+                FnvHashMap::default(),
+                FnvHashSet::default(),
+            )
+        };
+        d.set_path_results(compute(&fork));
+        assert!(!d.is_stale());
+        assert_eq!(d.get_direction_at_point(ID1), Some(ID0));
+
+        // the map was modified while the paths were computed
+        let fork = d.fork();
+        d.disable_point(ID1).unwrap();
+        d.set_path_results(compute(&fork));
+        assert!(d.is_stale());
+    }
+}
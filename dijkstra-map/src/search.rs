@@ -0,0 +1,437 @@
+use super::{
+    Cost, DijkstraMap, FnvHashMap, FnvHashSet, PathResults, PointComputedInfo, PointId,
+    QueuePriority, Read, TerrainType, Weight,
+};
+use priority_queue::PriorityQueue;
+
+/// Resumable run of Dijkstra's algorithm on a [`DijkstraMap`].
+///
+/// This is created via [`start_search`](DijkstraMap::start_search), and
+/// advanced with [`step`](PathSearch::step), for example a few hundred points
+/// per frame. This spreads the cost of a search over time without threads.
+///
+/// The search does not borrow the map : if the map is modified between two
+/// steps, the next step restarts the search from the beginning.
+#[derive(Clone, Debug)]
+pub struct PathSearch {
+    origins: Vec<PointId>,
+    read: Read,
+    max_cost: Cost,
+    initial_costs: Vec<Cost>,
+    terrain_weights: FnvHashMap<TerrainType, Weight>,
+    termination_points: FnvHashSet<PointId>,
+    /// Points to expand, by increasing cost.
+    open_queue: PriorityQueue<PointId, QueuePriority>,
+    /// Paths computed so far.
+    results: PathResults,
+    /// Guard against infinite loops : maximum number of remaining expansions.
+    remaining: i32,
+    /// Wether or not the algorithm terminated.
+    finished: bool,
+}
+
+impl DijkstraMap {
+    /// Starts a resumable search, with the same parameters as
+    /// [`recalculate`](DijkstraMap::recalculate).
+    ///
+    /// No point is expanded until [`PathSearch::step`] is called.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{Cost, DijkstraMap, PointId, TerrainType};
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// for i in 0..10 {
+    ///     dijkstra_map.add_point(PointId(i), TerrainType::DefaultTerrain).unwrap();
+    ///     if i > 0 {
+    ///         dijkstra_map.connect_points(PointId(i - 1), PointId(i), None, None).unwrap();
+    ///     }
+    /// }
+    /// let mut search = dijkstra_map.start_search(
+    ///     &[PointId(0)],
+    ///     None,
+    ///     None,
+    ///     Vec::new(),
+    ///     Default::default(),
+    ///     Default::default(),
+    /// );
+    /// // expand 4 points per step
+    /// assert!(!search.step(&dijkstra_map, Some(4)));
+    /// assert_eq!(search.results().get_cost_at_point(PointId(3)), Cost(3.0));
+    /// while !search.step(&dijkstra_map, Some(4)) {}
+    /// assert_eq!(search.results().get_cost_at_point(PointId(9)), Cost(9.0));
+    /// ```
+    pub fn start_search(
+        &self,
+        origins: &[PointId],
+        read: Option<Read>,
+        max_cost: Option<Cost>,
+        initial_costs: Vec<Cost>,
+        terrain_weights: FnvHashMap<TerrainType, Weight>,
+        termination_points: FnvHashSet<PointId>,
+    ) -> PathSearch {
+        let mut search = PathSearch {
+            origins: origins.to_vec(),
+            read: read.unwrap_or(Read::InputIsDestination),
+            max_cost: max_cost.unwrap_or(Cost(f32::INFINITY)),
+            initial_costs,
+            terrain_weights,
+            termination_points,
+            open_queue: PriorityQueue::new(),
+            results: PathResults::default(),
+            remaining: 0,
+            finished: false,
+        };
+        search.restart(self);
+        search
+    }
+
+    /// Starts a time-sliced [`recalculate`](DijkstraMap::recalculate), with
+    /// the same parameters.
+    ///
+    /// The computation is advanced by
+    /// [`step_recalculation`](DijkstraMap::step_recalculation). In the
+    /// meantime, getters like
+    /// [`get_cost_at_point`](DijkstraMap::get_cost_at_point) return the paths
+    /// computed so far.
+    ///
+    /// This replaces the recalculation in progress, if any.
+    pub fn start_recalculation(
+        &mut self,
+        origins: &[PointId],
+        read: Option<Read>,
+        max_cost: Option<Cost>,
+        initial_costs: Vec<Cost>,
+        terrain_weights: FnvHashMap<TerrainType, Weight>,
+        termination_points: FnvHashSet<PointId>,
+    ) {
+        self.search = Some(self.start_search(
+            origins,
+            read,
+            max_cost,
+            initial_costs,
+            terrain_weights,
+            termination_points,
+        ));
+    }
+
+    /// Continues the recalculation started by
+    /// [`start_recalculation`](DijkstraMap::start_recalculation), processing
+    /// at most `max_expansions` points (default : no limit).
+    ///
+    /// If the map was modified since the recalculation started, it is
+    /// restarted.
+    ///
+    /// Returns [`true`] once the recalculation is finished, or if there is
+    /// none in progress.
+    pub fn step_recalculation(&mut self, max_expansions: Option<usize>) -> bool {
+        match self.search.take() {
+            None => true,
+            Some(mut search) => {
+                if search.step(self, max_expansions) {
+                    self.set_path_results(search.into_results());
+                    true
+                } else {
+                    self.search = Some(search);
+                    false
+                }
+            }
+        }
+    }
+}
+
+impl PathSearch {
+    /// Restarts the search from the beginning, on the current state of
+    /// `map`.
+    pub fn restart(&mut self, map: &DijkstraMap) {
+        // initialize containers
+        self.results = PathResults {
+            revision: map.revision,
+            ..PathResults::default()
+        };
+        let points_number = map.points.len();
+        let capacity = std::cmp::max(
+            (f32::sqrt(points_number as f32) as usize) * 6,
+            self.origins.len(),
+        );
+        self.open_queue = PriorityQueue::with_capacity(capacity);
+        self.remaining = points_number as i32;
+        self.finished = false;
+
+        // add targets to open_queue
+        for (i, src) in self.origins.iter().enumerate() {
+            if map.points.contains_key(src) {
+                self.results.computed_info.insert(
+                    *src,
+                    PointComputedInfo {
+                        direction: *src,
+                        cost: *self.initial_costs.get(i).unwrap_or(&Cost(0.0)),
+                    },
+                );
+                self.open_queue.push(
+                    *src,
+                    QueuePriority {
+                        id: *src,
+                        cost: self.results.get_cost_at_point(*src),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Continues the search, expanding at most `max_expansions` points
+    /// (default : no limit).
+    ///
+    /// If `map` was modified since the search started, it is restarted
+    /// first.
+    ///
+    /// The search should always be continued with the same map. If another
+    /// map with the same [revision](DijkstraMap::revision) is given, such as
+    /// a diverged [fork](DijkstraMap::fork), the search is not restarted :
+    /// points that it does not have are treated as unreachable.
+    ///
+    /// Returns [`true`] once the search is finished.
+    pub fn step(&mut self, map: &DijkstraMap, max_expansions: Option<usize>) -> bool {
+        if map.revision != self.results.revision {
+            self.restart(map);
+        }
+        let mut max_expansions = max_expansions.unwrap_or(usize::MAX);
+
+        // switches direction of connections
+        let read = self.read;
+        let points = &map.points;
+        let connections = |src: &PointId| -> Option<&FnvHashMap<PointId, Weight>> {
+            points.get(src).map(|info| match read {
+                Read::InputIsDestination => &info.reverse_connections,
+                Read::InputIsOrigin => &info.connections,
+            })
+        };
+
+        // iterate over open_queue
+        while !self.finished {
+            if self.open_queue.is_empty() || self.remaining < 0 {
+                self.finished = true;
+                break;
+            }
+            if max_expansions == 0 {
+                return false;
+            }
+            max_expansions -= 1;
+            let (point1, _) = self.open_queue.pop().unwrap();
+            let point1_terrain = match map.get_terrain_for_point(point1) {
+                Some(terrain) => terrain,
+                None => {
+                    self.results.computed_info.remove(&point1);
+                    continue;
+                }
+            };
+            self.remaining -= 1;
+            // According to Dijkstra algorithm, this point has minimal cost among the points to process.
+            self.results.sorted_points.push(point1);
+            if self.termination_points.contains(&point1) {
+                self.finished = true;
+                break;
+            }
+            let point1_cost = self.results.get_cost_at_point(point1);
+            let weight_of_point1 = match point1_terrain {
+                TerrainType::DefaultTerrain => Weight(1.0), // terrain is default terrain => weight is 1.0
+                x => *self
+                    .terrain_weights
+                    .get(&x) // you have x in passed dict => it is the weigh used
+                    .unwrap_or(&Weight::infinity()), // you dont have x => weight is infinity
+            };
+
+            // iterate over it's neighbours
+            let empty_connections = FnvHashMap::default();
+            for (&point2, &dir_cost) in connections(&point1).unwrap_or(&empty_connections).iter() {
+                let point2_terrain = match map.points.get(&point2) {
+                    Some(info) => info.terrain_type,
+                    None => continue,
+                };
+                let cost: Cost = point1_cost
+                    + dir_cost
+                        * Weight(0.5)
+                        * (weight_of_point1
+                            + *self
+                                .terrain_weights
+                                .get(&point2_terrain)
+                                .unwrap_or(&Weight(1.0))); // assumes default terrain
+
+                // add to the open set (or update values if already present)
+                // if point is enabled and new cost is better than old one, but not bigger than maximum cost
+                if cost < self.results.get_cost_at_point(point2)
+                    && cost <= self.max_cost
+                    && !map.disabled_points.contains(&point2)
+                {
+                    self.open_queue
+                        .push_increase(point2, QueuePriority { id: point2, cost });
+                    self.results.computed_info.insert(
+                        point2,
+                        PointComputedInfo {
+                            direction: point1,
+                            cost,
+                        },
+                    );
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns [`true`] if the search is finished.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the paths computed so far.
+    ///
+    /// Points that were not expanded yet may have a suboptimal cost.
+    pub fn results(&self) -> &PathResults {
+        &self.results
+    }
+
+    /// Consumes the search, returning the paths computed so far.
+    pub fn into_results(self) -> PathResults {
+        self.results
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TERRAIN: TerrainType = TerrainType::DefaultTerrain;
+
+    fn start(d: &DijkstraMap) -> PathSearch {
+        d.start_search(
+            &[PointId(0)],
+            None,
+            None,
+            Vec::new(),
+            FnvHashMap::default(),
+            FnvHashSet::default(),
+        )
+    }
+
+    #[test]
+    fn stepping_gives_same_results() {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_square_grid(7, 5, None, TERRAIN, None, Some(Weight(1.5)));
+        let origin = *grid.values().min().unwrap();
+        d.disable_point(*grid.values().max().unwrap()).unwrap();
+        let expected = d.compute_paths(
+            &[origin],
+            None,
+            None,
+            Vec::new(),
+            FnvHashMap::default(),
+            FnvHashSet::default(),
+        );
+        let mut search = d.start_search(
+            &[origin],
+            None,
+            None,
+            Vec::new(),
+            FnvHashMap::default(),
+            FnvHashSet::default(),
+        );
+        let mut steps = 0;
+        while !search.step(&d, Some(3)) {
+            steps += 1;
+            assert!(!search.is_finished());
+        }
+        assert!(steps > 5);
+        assert_eq!(search.results(), &expected);
+    }
+
+    #[test]
+    fn zero_expansions_does_nothing() {
+        let mut d = DijkstraMap::new();
+        d.add_point(PointId(0), TERRAIN).unwrap();
+        let mut search = start(&d);
+        assert!(!search.step(&d, Some(0)));
+        assert!(search.results().sorted_points.is_empty());
+        assert!(search.step(&d, None));
+        assert!(search.step(&d, Some(0)));
+    }
+
+    #[test]
+    fn restarts_when_map_changes() {
+        let mut d = DijkstraMap::new();
+        for i in 0..3 {
+            d.add_point(PointId(i), TERRAIN).unwrap();
+        }
+        d.connect_points(PointId(0), PointId(1), None, None)
+            .unwrap();
+        d.connect_points(PointId(1), PointId(2), None, None)
+            .unwrap();
+        let mut search = start(&d);
+        assert!(!search.step(&d, Some(2)));
+        assert_eq!(search.results().get_cost_at_point(PointId(2)), Cost(2.0));
+
+        d.disable_point(PointId(1)).unwrap();
+        assert!(search.step(&d, None));
+        assert_eq!(
+            search.results().get_cost_at_point(PointId(2)),
+            Cost::infinity()
+        );
+        assert_eq!(search.results().revision(), d.revision());
+    }
+
+    #[test]
+    fn points_missing_from_another_map_are_unreachable() {
+        let mut d = DijkstraMap::new();
+        for i in 0..4 {
+            d.add_point(PointId(i), TERRAIN).unwrap();
+            if i > 0 {
+                d.connect_points(PointId(i - 1), PointId(i), None, None)
+                    .unwrap();
+            }
+        }
+        let mut fork_1 = d.fork();
+        fork_1.disable_point(PointId(3)).unwrap();
+        let mut fork_2 = d.fork();
+        fork_2.remove_point(PointId(2));
+        assert_eq!(fork_1.revision(), fork_2.revision());
+
+        let mut search = start(&fork_1);
+        assert!(!search.step(&fork_1, Some(2)));
+        assert_eq!(search.results().get_cost_at_point(PointId(2)), Cost(2.0));
+        assert!(search.step(&fork_2, None));
+        assert_eq!(
+            search.results().get_cost_at_point(PointId(2)),
+            Cost::infinity()
+        );
+        assert_eq!(search.results().sorted_points, [PointId(0), PointId(1)]);
+    }
+
+    #[test]
+    fn time_sliced_recalculation() {
+        let mut d = DijkstraMap::new();
+        for i in 0..4 {
+            d.add_point(PointId(i), TERRAIN).unwrap();
+            if i > 0 {
+                d.connect_points(PointId(i - 1), PointId(i), None, None)
+                    .unwrap();
+            }
+        }
+        d.start_recalculation(
+            &[PointId(0)],
+            None,
+            None,
+            Vec::new(),
+            FnvHashMap::default(),
+            FnvHashSet::default(),
+        );
+        assert!(!d.step_recalculation(Some(2)));
+        // partial results are visible
+        assert_eq!(d.get_cost_at_point(PointId(2)), Cost(2.0));
+        assert_eq!(d.get_cost_at_point(PointId(3)), Cost::infinity());
+        assert!(d.is_stale());
+        assert!(d.step_recalculation(None));
+        assert_eq!(d.get_cost_at_point(PointId(3)), Cost(3.0));
+        assert!(!d.is_stale());
+        assert!(d.step_recalculation(Some(1)));
+    }
+}
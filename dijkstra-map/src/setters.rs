@@ -1,4 +1,6 @@
+use super::history::Edit;
 use super::{DijkstraMap, FnvHashMap, FnvHashSet, PointId, PointInfo, TerrainType, Weight};
+use std::sync::Arc;
 
 impl Default for DijkstraMap {
     fn default() -> Self {
@@ -20,19 +22,64 @@ impl DijkstraMap {
     /// Creates a new empty `DijkstraMap`.
     pub fn new() -> Self {
         DijkstraMap {
-            points: FnvHashMap::default(),
-            computed_info: FnvHashMap::default(),
-            sorted_points: Vec::<PointId>::new(),
-            disabled_points: FnvHashSet::default(),
+            points: Default::default(),
+            results: Default::default(),
+            search: None,
+            disabled_points: Default::default(),
+            all_pairs: None,
+            revision: 0,
+            computed_revision: 0,
+            point_revisions: Default::default(),
+            history: Default::default(),
+            spatial: Default::default(),
+            grid_cells: Default::default(),
+        }
+    }
+
+    /// Creates a copy of the graph, to try out modifications (disable a
+    /// point, remove a connection...) and
+    /// [`recalculate`](DijkstraMap::recalculate) on the copy.
+    ///
+    /// This copies the whole graph. With the `persistent` feature, the fork
+    /// shares memory with `self` instead : it takes constant time, and
+    /// points are only copied when they are modified in either map. The
+    /// positions of the points are shared in both cases, until they are
+    /// modified in either map.
+    ///
+    /// Unlike [`clone`](Clone::clone), the fork has no computed paths and an
+    /// empty undo/redo history. Its [`revision`](DijkstraMap::revision)
+    /// starts at the one of `self`.
+    pub fn fork(&self) -> DijkstraMap {
+        DijkstraMap {
+            points: self.points.clone(),
+            results: Default::default(),
+            search: None,
+            disabled_points: self.disabled_points.clone(),
+            all_pairs: None,
+            revision: self.revision,
+            computed_revision: self.revision,
+            point_revisions: self.point_revisions.clone(),
+            history: Default::default(),
+            spatial: self.spatial.clone(),
+            grid_cells: self.grid_cells.clone(),
         }
     }
 
     /// Clears the DijkstraMap.
     pub fn clear(&mut self) {
-        self.points.clear();
-        self.computed_info.clear();
-        self.sorted_points.clear();
-        self.disabled_points.clear();
+        let points: Vec<PointId> = self.points.keys().copied().collect();
+        self.mark_changed(&points);
+        let old_points = std::mem::take(&mut self.points);
+        let old_disabled_points = std::mem::take(&mut self.disabled_points);
+        let old_spatial = std::mem::take(&mut self.spatial);
+        let old_grid_cells = std::mem::take(&mut self.grid_cells);
+        self.record_edit(|| Edit::Clear {
+            points: old_points,
+            disabled_points: old_disabled_points,
+            spatial: old_spatial,
+            grid_cells: old_grid_cells,
+        });
+        self.results = Default::default();
     }
 
     /// Adds new point with given ID and terrain type into the graph.
@@ -59,15 +106,18 @@ impl DijkstraMap {
                     terrain_type,
                 },
             );
+            self.mark_changed(&[id]);
+            self.record_edit(|| Edit::AddPoint { id, terrain_type });
             Ok(())
         }
     }
 
     /// Adds new point with given ID and terrain type into the graph.
     ///
-    /// If a point was already associated with `id`, it is replaced.
+    /// If a point was already associated with `id`, it is replaced (along
+    /// with its position and its cell in the persistent grid).
     pub fn add_point_replace(&mut self, id: PointId, terrain_type: TerrainType) {
-        self.points.insert(
+        let previous = self.points.insert(
             id,
             PointInfo {
                 connections: FnvHashMap::default(),
@@ -75,18 +125,39 @@ impl DijkstraMap {
                 terrain_type,
             },
         );
+        let previous_position = Arc::make_mut(&mut self.spatial).set(id, None);
+        let previous_cell = Arc::make_mut(&mut self.grid_cells).set(id, None);
+        self.mark_changed(&[id]);
+        self.record_edit(|| Edit::ReplacePoint {
+            id,
+            terrain_type,
+            previous,
+            previous_position,
+            previous_cell,
+        });
     }
 
     /// Removes point from graph along with all of its connections.
     ///
-    /// If the point exists in the map, removes it and returns the associated
-    /// `PointInfo`. Else, returns `None`.
+    /// If the point exists in the map, removes it (along with its position
+    /// and its cell in the persistent grid) and returns the associated `PointInfo`. Else, returns `None`.
     pub fn remove_point(&mut self, point: PointId) -> Option<PointInfo> {
+        let disabled = self.disabled_points.contains(&point);
         self.disabled_points.remove(&point);
         // remove this point's entry from connections
         match self.points.remove(&point) {
             None => None,
             Some(point_info) => {
+                let mut changed: Vec<PointId> = point_info
+                    .connections
+                    .keys()
+                    .chain(point_info.reverse_connections.keys())
+                    .copied()
+                    .collect();
+                changed.push(point);
+                self.mark_changed(&changed);
+                let position = Arc::make_mut(&mut self.spatial).set(point, None);
+                let cell = Arc::make_mut(&mut self.grid_cells).set(point, None);
                 // remove reverse connections to this point from neighbours
                 for nbr in point_info.connections.keys() {
                     if let Some(point_info_nbr) = self.points.get_mut(nbr) {
@@ -99,6 +170,13 @@ impl DijkstraMap {
                         point_info_nbr.connections.remove(&point);
                     }
                 }
+                self.record_edit(|| Edit::RemovePoint {
+                    id: point,
+                    info: point_info.clone(),
+                    disabled,
+                    position,
+                    cell,
+                });
                 Some(point_info)
             }
         }
@@ -115,7 +193,14 @@ impl DijkstraMap {
     /// Points are enabled by default.
     pub fn disable_point(&mut self, point: PointId) -> Result<(), PointNotFound> {
         if self.points.contains_key(&point) {
+            let previous = self.disabled_points.contains(&point);
             self.disabled_points.insert(point);
+            self.mark_changed(&[point]);
+            self.record_edit(|| Edit::SetDisabled {
+                point,
+                disabled: true,
+                previous,
+            });
             Ok(())
         } else {
             Err(PointNotFound)
@@ -136,7 +221,14 @@ impl DijkstraMap {
     /// Points are enabled by default.
     pub fn enable_point(&mut self, point: PointId) -> Result<(), PointNotFound> {
         if self.points.contains_key(&point) {
+            let previous = self.disabled_points.contains(&point);
             self.disabled_points.remove(&point);
+            self.mark_changed(&[point]);
+            self.record_edit(|| Edit::SetDisabled {
+                point,
+                disabled: false,
+                previous,
+            });
             Ok(())
         } else {
             Err(PointNotFound)
@@ -170,18 +262,158 @@ impl DijkstraMap {
             self.connect_points(source, target, Some(weight), Some(false))
                 .and(self.connect_points(target, source, Some(weight), Some(false)))
         } else {
+            if !self.has_point(target) {
+                return Err(PointNotFound);
+            }
             let PointInfo { connections, .. } =
                 self.points.get_mut(&source).ok_or(PointNotFound)?;
-            connections.insert(target, weight);
+            let previous = connections.insert(target, weight);
             let PointInfo {
                 reverse_connections,
                 ..
             } = self.points.get_mut(&target).ok_or(PointNotFound)?;
             reverse_connections.insert(source, weight);
+            self.mark_changed(&[source, target]);
+            self.record_edit(|| Edit::Connect {
+                source,
+                target,
+                weight,
+                previous,
+            });
             Ok(())
         }
     }
 
+    /// Adds many points at once, with their terrain type.
+    ///
+    /// This is equivalent to calling [`add_point`](DijkstraMap::add_point)
+    /// on each point, but faster for large graphs.
+    ///
+    /// # Errors
+    ///
+    /// If a point already exists, or appears twice in `points`, returns
+    /// [`Err`] without modifying the map.
+    pub fn add_points(
+        &mut self,
+        points: &[(PointId, TerrainType)],
+    ) -> Result<(), PointAlreadyExists> {
+        let mut ids = FnvHashSet::with_capacity_and_hasher(points.len(), Default::default());
+        for &(id, _) in points {
+            if self.has_point(id) || !ids.insert(id) {
+                return Err(PointAlreadyExists);
+            }
+        }
+        // persistent maps have no capacity to reserve
+        #[cfg(not(feature = "persistent"))]
+        self.points.reserve(points.len());
+        for &(id, terrain_type) in points {
+            self.points.insert(
+                id,
+                PointInfo {
+                    connections: FnvHashMap::default(),
+                    reverse_connections: FnvHashMap::default(),
+                    terrain_type,
+                },
+            );
+            self.record_edit(|| Edit::AddPoint { id, terrain_type });
+        }
+        let ids: Vec<PointId> = points.iter().map(|&(id, _)| id).collect();
+        self.mark_changed(&ids);
+        Ok(())
+    }
+
+    /// Adds many connections at once, given as `(source, target, weight)`.
+    ///
+    /// This is equivalent to calling
+    /// [`connect_points`](DijkstraMap::connect_points) on each connection,
+    /// but faster for large graphs : the connections of each point are
+    /// allocated once.
+    ///
+    /// # Parameters
+    ///
+    /// - `connections` : source point, target point and weight of each
+    ///   connection.
+    /// - `bidirectional` (default : [`true`]) : wether or not the reciprocal
+    ///   connections should be made.
+    ///
+    /// # Errors
+    ///
+    /// If one of the points does not exist, returns [`Err`] without
+    /// modifying the map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, PointId, TerrainType, Weight};
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let points: Vec<_> = (0..3).map(|id| (PointId(id), TerrainType::DefaultTerrain)).collect();
+    /// dijkstra_map.add_points(&points).unwrap();
+    /// dijkstra_map
+    ///     .add_connections(
+    ///         &[(PointId(0), PointId(1), Weight(1.0)), (PointId(1), PointId(2), Weight(2.0))],
+    ///         Some(false),
+    ///     )
+    ///     .unwrap();
+    /// assert!(dijkstra_map.has_connection(PointId(1), PointId(2)));
+    /// assert!(!dijkstra_map.has_connection(PointId(2), PointId(1)));
+    /// ```
+    pub fn add_connections(
+        &mut self,
+        connections: &[(PointId, PointId, Weight)],
+        bidirectional: Option<bool>,
+    ) -> Result<(), PointNotFound> {
+        if connections
+            .iter()
+            .any(|&(source, target, _)| !self.has_point(source) || !self.has_point(target))
+        {
+            return Err(PointNotFound);
+        }
+        let reversed = connections
+            .iter()
+            .map(|&(source, target, weight)| (target, source, weight));
+        let all: Vec<(PointId, PointId, Weight)> = if bidirectional.unwrap_or(true) {
+            connections.iter().copied().chain(reversed).collect()
+        } else {
+            connections.to_vec()
+        };
+
+        // reserve the connections of each point
+        let mut counts = FnvHashMap::<PointId, (usize, usize)>::default();
+        for &(source, target, _) in all.iter() {
+            counts.entry(source).or_default().0 += 1;
+            counts.entry(target).or_default().1 += 1;
+        }
+        for (point, &(outgoing, incoming)) in counts.iter() {
+            let info = self.points.get_mut(point).unwrap();
+            info.connections.reserve(outgoing);
+            info.reverse_connections.reserve(incoming);
+        }
+
+        for &(source, target, weight) in all.iter() {
+            let previous = self
+                .points
+                .get_mut(&source)
+                .unwrap()
+                .connections
+                .insert(target, weight);
+            self.points
+                .get_mut(&target)
+                .unwrap()
+                .reverse_connections
+                .insert(source, weight);
+            self.record_edit(|| Edit::Connect {
+                source,
+                target,
+                weight,
+                previous,
+            });
+        }
+        let changed: Vec<PointId> = counts.keys().copied().collect();
+        self.mark_changed(&changed);
+        Ok(())
+    }
+
     /// Removes connection between source point and target point.
     ///
     /// # Parameters
@@ -207,12 +439,18 @@ impl DijkstraMap {
         } else {
             let PointInfo { connections, .. } =
                 self.points.get_mut(&source).ok_or(PointNotFound)?;
-            connections.remove(&target);
+            let previous = connections.remove(&target);
             let PointInfo {
                 reverse_connections,
                 ..
             } = self.points.get_mut(&target).ok_or(PointNotFound)?;
             reverse_connections.remove(&source);
+            self.mark_changed(&[source, target]);
+            self.record_edit(|| Edit::Disconnect {
+                source,
+                target,
+                previous,
+            });
             Ok(())
         }
     }
@@ -232,7 +470,13 @@ impl DijkstraMap {
                 terrain_type: terrain,
                 ..
             }) => {
-                *terrain = terrain_type;
+                let previous = std::mem::replace(terrain, terrain_type);
+                self.mark_changed(&[point]);
+                self.record_edit(|| Edit::SetTerrain {
+                    point,
+                    terrain_type,
+                    previous,
+                });
                 Ok(())
             }
             None => Err(PointNotFound),
@@ -243,6 +487,7 @@ impl DijkstraMap {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::Position;
     const ID0: PointId = PointId(0);
     const ID1: PointId = PointId(1);
     const ID2: PointId = PointId(2);
@@ -283,6 +528,47 @@ mod test {
         d.add_point(ID0, TERRAIN).unwrap();
     }
 
+    #[test]
+    fn bulk_import_matches_single_calls() {
+        let mut single = setup_add012();
+        single
+            .connect_points(ID0, ID1, Some(Weight(2.0)), None)
+            .unwrap();
+        single.connect_points(ID2, ID1, None, Some(false)).unwrap();
+        single
+            .connect_points(ID0, ID1, Some(Weight(3.0)), Some(false))
+            .unwrap();
+
+        let mut bulk = DijkstraMap::new();
+        bulk.add_points(&[(ID0, TERRAIN), (ID1, TERRAIN), (ID2, TERRAIN)])
+            .unwrap();
+        bulk.add_connections(&[(ID0, ID1, Weight(2.0))], None)
+            .unwrap();
+        bulk.add_connections(
+            &[(ID2, ID1, Weight(1.0)), (ID0, ID1, Weight(3.0))],
+            Some(false),
+        )
+        .unwrap();
+        assert_eq!(bulk.points, single.points);
+    }
+
+    #[test]
+    fn bulk_import_is_all_or_nothing() {
+        let mut d = setup_add012();
+        let res = d.add_points(&[(PointId(3), TERRAIN), (ID1, TERRAIN)]);
+        assert_eq!(res, Err(PointAlreadyExists));
+        let res = d.add_points(&[(PointId(3), TERRAIN), (PointId(3), TERRAIN)]);
+        assert_eq!(res, Err(PointAlreadyExists));
+        assert!(!d.has_point(PointId(3)));
+
+        let res = d.add_connections(
+            &[(ID0, ID1, Weight(1.0)), (ID1, PointId(3), Weight(1.0))],
+            None,
+        );
+        assert_eq!(res, Err(PointNotFound));
+        assert!(!d.has_connection(ID0, ID1));
+    }
+
     #[test]
     fn remove_points_works() {
         let mut d = DijkstraMap::new();
@@ -319,4 +605,44 @@ mod test {
         let terrain = d.get_terrain_for_point(ID0).unwrap();
         assert_eq!(terrain, TerrainType::Terrain(5));
     }
+
+    #[test]
+    fn fork_is_independent() {
+        let mut d = setup_add012();
+        d.connect_points(ID0, ID1, None, None).unwrap();
+        let mut fork = d.fork();
+        fork.disable_point(ID1).unwrap();
+        fork.remove_connection(ID0, ID1, Some(false)).unwrap();
+        fork.remove_point(ID2);
+        assert!(d.has_connection(ID0, ID1));
+        assert!(!d.is_point_disabled(ID1));
+        assert!(d.has_point(ID2));
+
+        d.set_terrain_for_point(ID0, TerrainType::Terrain(1))
+            .unwrap();
+        assert_eq!(
+            fork.get_terrain_for_point(ID0),
+            Some(TerrainType::DefaultTerrain)
+        );
+        assert!(fork.has_connection(ID1, ID0));
+        assert!(!fork.has_connection(ID0, ID1));
+    }
+
+    #[test]
+    fn fork_shares_positions_until_they_are_modified() {
+        let mut d = setup_add012();
+        d.set_point_position(ID0, Some(Position::new(1.0, 2.0, 0.0)))
+            .unwrap();
+        let mut fork = d.fork();
+        assert!(Arc::ptr_eq(&d.spatial, &fork.spatial));
+        assert!(Arc::ptr_eq(&d.grid_cells, &fork.grid_cells));
+
+        fork.set_point_position(ID0, None).unwrap();
+        assert!(!Arc::ptr_eq(&d.spatial, &fork.spatial));
+        assert_eq!(
+            d.get_point_position(ID0),
+            Some(Position::new(1.0, 2.0, 0.0))
+        );
+        assert_eq!(fork.get_point_position(ID0), None);
+    }
 }
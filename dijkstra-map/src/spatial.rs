@@ -0,0 +1,498 @@
+use super::history::Edit;
+use super::setters::PointNotFound;
+use super::{DijkstraMap, PointId, SharedMap, SharedSet};
+use euclid::default::Point3D;
+use std::sync::Arc;
+
+/// Position of a point in the world.
+///
+/// 2D positions have a `z` coordinate of `0`.
+pub type Position = Point3D<f32>;
+
+/// Position of a point, and whether it was set in 3D (see
+/// [`set_point_position_3d`](DijkstraMap::set_point_position_3d)).
+pub(crate) type StoredPosition = (Position, bool);
+
+/// Cell of the spatial index.
+type Cell = [i32; 3];
+
+/// Positions of the points of a [`DijkstraMap`], indexed by a uniform grid
+/// (also called _spatial hash_) for nearest-point queries.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SpatialIndex {
+    /// Position of each point.
+    positions: SharedMap<PointId, Position>,
+    /// Points whose position was set in 3D.
+    positions_3d: SharedSet<PointId>,
+    /// Points whose position is in each cell, sorted by ID.
+    cells: SharedMap<Cell, Vec<PointId>>,
+    /// Side of the cells.
+    cell_size: f32,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self {
+            positions: Default::default(),
+            positions_3d: Default::default(),
+            cells: Default::default(),
+            cell_size: 1.0,
+        }
+    }
+}
+
+impl SpatialIndex {
+    fn cell(&self, position: Position) -> Cell {
+        [
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+            (position.z / self.cell_size).floor() as i32,
+        ]
+    }
+
+    pub(crate) fn get(&self, point: PointId) -> Option<Position> {
+        self.positions.get(&point).copied()
+    }
+
+    /// Sets or removes the position of `point`, returning its previous one.
+    pub(crate) fn set(
+        &mut self,
+        point: PointId,
+        position: Option<StoredPosition>,
+    ) -> Option<StoredPosition> {
+        let was_3d = self.positions_3d.contains(&point);
+        self.positions_3d.remove(&point);
+        let previous = self.positions.remove(&point);
+        if let Some(previous) = previous {
+            let cell = self.cell(previous);
+            if let Some(points) = self.cells.get_mut(&cell) {
+                points.retain(|&other| other != point);
+                if points.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+        if let Some((position, is_3d)) = position {
+            self.positions.insert(point, position);
+            if is_3d {
+                self.positions_3d.insert(point);
+            }
+            let points = self.cells.entry(self.cell(position)).or_default();
+            if let Err(index) = points.binary_search(&point) {
+                points.insert(index, point);
+            }
+        }
+        previous.map(|previous| (previous, was_3d))
+    }
+
+    /// Rebuilds the index with cells of side `cell_size`.
+    fn set_cell_size(&mut self, cell_size: f32) {
+        let positions = std::mem::take(&mut self.positions);
+        self.cells = Default::default();
+        self.cell_size = cell_size;
+        for (&point, &position) in positions.iter() {
+            let is_3d = self.positions_3d.contains(&point);
+            self.set(point, Some((position, is_3d)));
+        }
+    }
+
+    /// Calls `f` on the points of the cells in the box between `min` and
+    /// `max` (inclusive).
+    ///
+    /// Returns [`false`] without calling `f` if the box contains more cells
+    /// than there are points : it is then faster to look at every point.
+    fn for_each_in_box(&self, min: Cell, max: Cell, mut f: impl FnMut(PointId, Position)) -> bool {
+        let cell_count = (0..3)
+            .map(|axis| (i64::from(max[axis]) - i64::from(min[axis]) + 1).max(0) as u64)
+            .fold(1u64, u64::saturating_mul);
+        if cell_count > self.positions.len() as u64 {
+            return false;
+        }
+        for x in min[0]..=max[0] {
+            for y in min[1]..=max[1] {
+                for z in min[2]..=max[2] {
+                    for &point in self.cells.get(&[x, y, z]).into_iter().flatten() {
+                        f(point, self.positions[&point]);
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+impl DijkstraMap {
+    /// Sets the position of `point` in the world, or removes it if
+    /// `position` is [`None`].
+    ///
+    /// Positions do not change the paths : they are used by
+    /// [`get_nearest_point`](DijkstraMap::get_nearest_point) and
+    /// [`get_points_within_radius`](DijkstraMap::get_points_within_radius).
+    /// The grids created by [`add_square_grid`](DijkstraMap::add_square_grid)
+    /// and [`add_hexagonal_grid`](DijkstraMap::add_hexagonal_grid) set the
+    /// position of their points to their coordinates.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if the point does not exist.
+    pub fn set_point_position(
+        &mut self,
+        point: PointId,
+        position: Option<Position>,
+    ) -> Result<(), PointNotFound> {
+        self.set_stored_position(point, position.map(|position| (position, false)))
+    }
+
+    /// Same as [`set_point_position`](DijkstraMap::set_point_position), but
+    /// marks the position as set in 3D :
+    /// [`is_point_position_3d`](DijkstraMap::is_point_position_3d) then
+    /// returns [`true`].
+    ///
+    /// Queries always use the 3 coordinates of positions : this only
+    /// remembers how the position was given, so that bindings can return it
+    /// the same way (as a 2D or 3D vector).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if the point does not exist.
+    pub fn set_point_position_3d(
+        &mut self,
+        point: PointId,
+        position: Position,
+    ) -> Result<(), PointNotFound> {
+        self.set_stored_position(point, Some((position, true)))
+    }
+
+    /// Sets or removes the position of `point`, recording the edit.
+    pub(crate) fn set_stored_position(
+        &mut self,
+        point: PointId,
+        position: Option<StoredPosition>,
+    ) -> Result<(), PointNotFound> {
+        if !self.has_point(point) {
+            return Err(PointNotFound);
+        }
+        let previous = Arc::make_mut(&mut self.spatial).set(point, position);
+        self.record_edit(|| Edit::SetPosition {
+            point,
+            position,
+            previous,
+        });
+        Ok(())
+    }
+
+    /// Returns the position of `point`, or [`None`] if it has none.
+    pub fn get_point_position(&self, point: PointId) -> Option<Position> {
+        self.spatial.get(point)
+    }
+
+    /// Returns [`true`] if the position of `point` was set with
+    /// [`set_point_position_3d`](DijkstraMap::set_point_position_3d).
+    pub fn is_point_position_3d(&self, point: PointId) -> bool {
+        self.spatial.positions_3d.contains(&point)
+    }
+
+    /// Sets the side of the cells of the spatial index (`1.0` by default).
+    ///
+    /// Queries are fastest when cells contain a few points : this should be
+    /// about the distance between neighbouring points.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_size` is not strictly positive and finite.
+    pub fn set_spatial_cell_size(&mut self, cell_size: f32) {
+        assert!(
+            cell_size > 0.0 && cell_size.is_finite(),
+            "invalid cell size : {}",
+            cell_size
+        );
+        Arc::make_mut(&mut self.spatial).set_cell_size(cell_size);
+    }
+
+    /// Returns the point at exactly `position`, if any.
+    ///
+    /// If several points share this position, returns the one with the
+    /// smallest ID.
+    pub fn get_point_at_position(&self, position: Position) -> Option<PointId> {
+        let cell = self.spatial.cell(position);
+        self.spatial
+            .cells
+            .get(&cell)?
+            .iter()
+            .copied()
+            .find(|&point| self.spatial.positions[&point] == position)
+    }
+
+    /// Returns the point closest to `position`, or [`None`] if no point has
+    /// a position.
+    ///
+    /// If `only_enabled` is [`true`], disabled points are ignored. Ties are
+    /// broken by taking the smallest ID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, Position, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let grid = dijkstra_map.add_square_grid(10, 10, None, TerrainType::DefaultTerrain, None, None);
+    /// let wall = grid[&Vector2D::new(3, 4)];
+    /// dijkstra_map.disable_point(wall).unwrap();
+    /// let click = Position::new(3.1, 3.6, 0.0);
+    /// assert_eq!(dijkstra_map.get_nearest_point(click, false), Some(wall));
+    /// assert_eq!(dijkstra_map.get_nearest_point(click, true), Some(grid[&Vector2D::new(3, 3)]));
+    /// ```
+    pub fn get_nearest_point(&self, position: Position, only_enabled: bool) -> Option<PointId> {
+        let mut best: Option<(f32, PointId)> = None;
+        let consider = |best: &mut Option<(f32, PointId)>, point: PointId, other: Position| {
+            if only_enabled && self.disabled_points.contains(&point) {
+                return;
+            }
+            let distance = (other - position).square_length();
+            let closer = match *best {
+                Some(best) => (distance, point) < best,
+                None => true,
+            };
+            if closer {
+                *best = Some((distance, point));
+            }
+        };
+
+        let center = self.spatial.cell(position);
+        let mut visited: usize = 0;
+        let mut radius: i32 = 0;
+        loop {
+            // points in cells at `radius` from `center` are at least this far
+            let min_distance = (radius - 1).max(0) as f32 * self.spatial.cell_size;
+            if let Some((distance, _)) = best {
+                if min_distance * min_distance > distance {
+                    break;
+                }
+            }
+            // number of cells at a distance of `radius` from `center`
+            let ring = if radius == 0 {
+                1
+            } else {
+                (2 * radius + 1).pow(3) - (2 * radius - 1).pow(3)
+            };
+            visited += ring as usize;
+            if visited > self.spatial.positions.len() {
+                // the cells are too sparse, look at every point instead
+                for (&point, &other) in self.spatial.positions.iter() {
+                    consider(&mut best, point, other);
+                }
+                break;
+            }
+            for x in -radius..=radius {
+                for y in -radius..=radius {
+                    for z in -radius..=radius {
+                        if x.abs().max(y.abs()).max(z.abs()) != radius {
+                            continue;
+                        }
+                        let cell = [center[0] + x, center[1] + y, center[2] + z];
+                        for &point in self.spatial.cells.get(&cell).into_iter().flatten() {
+                            consider(&mut best, point, self.spatial.positions[&point]);
+                        }
+                    }
+                }
+            }
+            radius += 1;
+        }
+        best.map(|(_, point)| point)
+    }
+
+    /// Returns the points at a distance of at most `radius` from `position`,
+    /// sorted by distance (then by ID).
+    ///
+    /// If `only_enabled` is [`true`], disabled points are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dijkstra_map::{DijkstraMap, Position, TerrainType};
+    /// use euclid::Vector2D;
+    ///
+    /// let mut dijkstra_map = DijkstraMap::new();
+    /// let grid = dijkstra_map.add_square_grid(10, 10, None, TerrainType::DefaultTerrain, None, None);
+    /// let around = dijkstra_map.get_points_within_radius(Position::new(5.0, 5.0, 0.0), 1.0, false);
+    /// assert_eq!(around.len(), 5);
+    /// assert_eq!(around[0], grid[&Vector2D::new(5, 5)]);
+    /// ```
+    pub fn get_points_within_radius(
+        &self,
+        position: Position,
+        radius: f32,
+        only_enabled: bool,
+    ) -> Vec<PointId> {
+        let mut found = Vec::new();
+        let mut consider = |point: PointId, other: Position| {
+            if only_enabled && self.disabled_points.contains(&point) {
+                return;
+            }
+            let distance = (other - position).square_length();
+            if distance <= radius * radius {
+                found.push((distance, point));
+            }
+        };
+        let offset = Position::new(radius, radius, radius).to_vector();
+        let min = self.spatial.cell(position - offset);
+        let max = self.spatial.cell(position + offset);
+        if !self.spatial.for_each_in_box(min, max, &mut consider) {
+            for (&point, &other) in self.spatial.positions.iter() {
+                consider(point, other);
+            }
+        }
+        found.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        found.into_iter().map(|(_, point)| point).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TerrainType;
+
+    /// Creates a map with `count` points, at random positions.
+    fn setup(count: i32) -> DijkstraMap {
+        let mut d = DijkstraMap::new();
+        // simple linear congruential generator, to stay deterministic
+        let mut seed: u32 = 12345;
+        let mut random = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 8) as f32 / (1 << 24) as f32 * 100.0
+        };
+        for i in 0..count {
+            d.add_point(PointId(i), TerrainType::DefaultTerrain)
+                .unwrap();
+            let position = Position::new(random(), random(), 0.0);
+            d.set_point_position(PointId(i), Some(position)).unwrap();
+        }
+        d
+    }
+
+    /// Reference implementation of `get_nearest_point`.
+    fn nearest_naive(d: &DijkstraMap, position: Position) -> Option<PointId> {
+        d.spatial
+            .positions
+            .iter()
+            .map(|(&point, &other)| ((other - position).square_length(), point))
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+            .map(|(_, point)| point)
+    }
+
+    #[test]
+    fn nearest_point_matches_naive_search() {
+        let mut d = setup(200);
+        for &cell_size in &[0.5, 5.0, 1000.0] {
+            d.set_spatial_cell_size(cell_size);
+            for &(x, y) in &[(0.0, 0.0), (50.0, 50.0), (99.0, 12.5), (-300.0, 40.0)] {
+                let position = Position::new(x, y, 0.0);
+                assert_eq!(
+                    d.get_nearest_point(position, false),
+                    nearest_naive(&d, position)
+                );
+            }
+        }
+        assert_eq!(
+            DijkstraMap::new().get_nearest_point(Position::origin(), false),
+            None
+        );
+    }
+
+    #[test]
+    fn points_within_radius_matches_naive_search() {
+        let mut d = setup(200);
+        let center = Position::new(40.0, 60.0, 0.0);
+        let mut expected: Vec<PointId> = (0..200)
+            .map(PointId)
+            .filter(|&point| (d.get_point_position(point).unwrap() - center).length() <= 15.0)
+            .collect();
+        expected.sort_by(|&a, &b| {
+            let distance = |point| (d.get_point_position(point).unwrap() - center).length();
+            distance(a).partial_cmp(&distance(b)).unwrap()
+        });
+        for &cell_size in &[1.0, 10.0, 1000.0] {
+            d.set_spatial_cell_size(cell_size);
+            assert_eq!(d.get_points_within_radius(center, 15.0, false), expected);
+        }
+        d.disable_point(expected[0]).unwrap();
+        assert_eq!(
+            d.get_points_within_radius(center, 15.0, true),
+            expected[1..]
+        );
+    }
+
+    #[test]
+    fn positions_follow_points() {
+        let mut d = DijkstraMap::new();
+        let grid = d.add_square_grid(3, 3, None, TerrainType::DefaultTerrain, None, None);
+        let point = grid[&euclid::Vector2D::new(1, 2)];
+        assert_eq!(
+            d.get_point_position(point),
+            Some(Position::new(1.0, 2.0, 0.0))
+        );
+        assert_eq!(
+            d.get_point_at_position(Position::new(1.0, 2.0, 0.0)),
+            Some(point)
+        );
+        d.remove_point(point);
+        assert_eq!(d.get_point_position(point), None);
+        assert_eq!(d.get_point_at_position(Position::new(1.0, 2.0, 0.0)), None);
+        assert_eq!(
+            d.set_point_position(point, Some(Position::origin())),
+            Err(PointNotFound)
+        );
+    }
+
+    #[test]
+    fn undo_restores_positions() {
+        let mut d = DijkstraMap::new();
+        d.add_point(PointId(0), TerrainType::DefaultTerrain)
+            .unwrap();
+        let position = Position::new(1.0, 2.0, 3.0);
+        d.set_point_position(PointId(0), Some(position)).unwrap();
+        d.begin_transaction().unwrap();
+        d.set_point_position(PointId(0), None).unwrap();
+        d.commit_transaction().unwrap();
+        d.begin_transaction().unwrap();
+        d.clear();
+        d.commit_transaction().unwrap();
+        assert_eq!(d.get_nearest_point(position, false), None);
+        assert!(d.undo());
+        assert!(d.has_point(PointId(0)));
+        assert!(d.undo());
+        assert_eq!(d.get_point_position(PointId(0)), Some(position));
+        assert_eq!(
+            d.get_nearest_point(Position::origin(), false),
+            Some(PointId(0))
+        );
+    }
+
+    #[test]
+    fn positions_remember_if_they_were_set_in_3d() {
+        let mut d = DijkstraMap::new();
+        d.add_point(PointId(0), TerrainType::DefaultTerrain)
+            .unwrap();
+        let position = Position::new(1.0, 2.0, 0.0);
+        d.set_point_position_3d(PointId(0), position).unwrap();
+        assert!(d.is_point_position_3d(PointId(0)));
+        d.set_spatial_cell_size(4.0);
+        assert!(d.is_point_position_3d(PointId(0)));
+
+        d.begin_transaction().unwrap();
+        d.set_point_position(PointId(0), Some(position)).unwrap();
+        d.commit_transaction().unwrap();
+        assert!(!d.is_point_position_3d(PointId(0)));
+        assert!(d.undo());
+        assert!(d.is_point_position_3d(PointId(0)));
+
+        d.begin_transaction().unwrap();
+        d.remove_point(PointId(0));
+        d.commit_transaction().unwrap();
+        assert!(!d.is_point_position_3d(PointId(0)));
+        assert!(d.undo());
+        assert!(d.is_point_position_3d(PointId(0)));
+        assert_eq!(d.get_point_position(PointId(0)), Some(position));
+    }
+}